@@ -67,10 +67,10 @@ fn main() -> std::io::Result<()> {
     // Naming rules: <filename>_flac_<picture_type>[_index].jpg
     flac_parser.write_image()?;
 
-    flac_parser.change_target("千千阙歌.flac");
+    flac_parser.change_target("千千阙歌.flac")?;
 
 
-    let mut ogg_parser = OggParser::new("xhh.ogg");
+    let mut ogg_parser = OggParser::new("xhh.ogg")?;
     ogg_parser.parse()?;
     println!("ogg_vorbis_comment = {:?}", ogg_parser.get_all());
     Ok(())
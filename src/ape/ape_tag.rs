@@ -0,0 +1,167 @@
+use std::fmt::Display;
+
+/// APEv2 tag, usually located at the end of the file before the ID3v1 tag.
+///
+/// https://wiki.hydrogenaud.io/index.php?title=APEv2_specification
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApeTag {
+    /// absolute position of the header, or of the first item if there is no header
+    pub position: u64,
+    pub footer: ApeFooter,
+    pub items: Vec<ApeItem>,
+}
+
+impl ApeTag {
+    pub fn new(position: u64, footer: ApeFooter, items: Vec<ApeItem>) -> Self {
+        ApeTag {
+            position,
+            footer,
+            items,
+        }
+    }
+
+    /// Keys are case insensitive.
+    pub fn get(&self, key: &str) -> Option<&ApeItem> {
+        self.items
+            .iter()
+            .find(|item| item.key.eq_ignore_ascii_case(key))
+    }
+
+    /// Size of the whole tag, including the header.
+    pub fn size(&self) -> u32 {
+        if self.footer.has_header {
+            self.footer.size + 32
+        } else {
+            self.footer.size
+        }
+    }
+}
+
+/// The header and the footer share the same 32-byte layout.
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApeFooter {
+    /// 1000 or 2000
+    pub version: u32,
+    /// items and footer, excluding the header
+    pub size: u32,
+    pub item_count: u32,
+    pub read_only: bool,
+    pub has_header: bool,
+    pub has_footer: bool,
+    pub is_header: bool,
+}
+
+impl ApeFooter {
+    pub fn new(version: u32, size: u32, item_count: u32, flags: u32) -> Self {
+        ApeFooter {
+            version,
+            size,
+            item_count,
+            read_only: flags & 0x01 == 0x01,
+            has_header: flags & 0x80000000 != 0,
+            // the bit means "contains no footer"
+            has_footer: flags & 0x40000000 == 0,
+            is_header: flags & 0x20000000 != 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApeItem {
+    pub key: String,
+    pub read_only: bool,
+    pub value: ApeValue,
+}
+
+impl ApeItem {
+    pub fn new(key: String, read_only: bool, value: ApeValue) -> Self {
+        ApeItem {
+            key,
+            read_only,
+            value,
+        }
+    }
+
+    /// Return the values of a text item, or the URL of an external locator.
+    pub fn text(&self) -> Option<Vec<String>> {
+        match &self.value {
+            ApeValue::Text(v) => Some(v.clone()),
+            ApeValue::ExternalLocator(v) => Some(vec![v.clone()]),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ApeValue {
+    /// UTF-8, multiple values are separated by 0x00
+    Text(Vec<String>),
+    Binary(Vec<u8>),
+    /// UTF-8 link to an external resource
+    ExternalLocator(String),
+    /// binary item whose key starts with "Cover Art"
+    CoverArt(ApeCoverArt),
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApeCoverArt {
+    pub filename: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::dump::base64"))]
+    pub data: Vec<u8>,
+}
+
+impl ApeCoverArt {
+    pub fn new(filename: String, data: Vec<u8>) -> Self {
+        ApeCoverArt { filename, data }
+    }
+}
+
+impl Display for ApeTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "
+ApeTag {{
+    version: {},
+    position: {},
+    size: {} Bytes,
+    items: {{",
+            self.footer.version,
+            self.position,
+            self.size()
+        )?;
+        for item in &self.items {
+            match &item.value {
+                ApeValue::Text(v) => write!(f, "\n        {}: {:?}", item.key, v)?,
+                ApeValue::ExternalLocator(v) => write!(f, "\n        {}: -> {}", item.key, v)?,
+                ApeValue::Binary(v) => write!(f, "\n        {}: {} Bytes", item.key, v.len())?,
+                ApeValue::CoverArt(v) => write!(
+                    f,
+                    "\n        {}: {}, {} Bytes",
+                    item.key,
+                    v.filename,
+                    v.data.len()
+                )?,
+            }
+        }
+        write!(
+            f,
+            "
+    }}
+}}"
+        )
+    }
+}
@@ -0,0 +1,64 @@
+use crate::util::{parse_4_bytes_with_little_endian, Buffer};
+
+use super::{
+    ape_tag::{ApeCoverArt, ApeFooter, ApeItem, ApeValue},
+    error::ApeError,
+};
+
+pub fn parse_ape_footer(buffer: &Buffer) -> Result<ApeFooter, ApeError> {
+    if buffer[0..8] != *b"APETAGEX" {
+        return Err(ApeError::WrongHeader);
+    }
+    let version = parse_4_bytes_with_little_endian(&buffer[8..12]);
+    let size = parse_4_bytes_with_little_endian(&buffer[12..16]);
+    let item_count = parse_4_bytes_with_little_endian(&buffer[16..20]);
+    let flags = parse_4_bytes_with_little_endian(&buffer[20..24]);
+    if size < 32 {
+        return Err(ApeError::WrongHeader);
+    }
+    Ok(ApeFooter::new(version, size, item_count, flags))
+}
+
+/// `buffer` holds all items, without the header and the footer.
+pub fn parse_ape_items(buffer: &Buffer, item_count: u32) -> Result<Vec<ApeItem>, ApeError> {
+    let mut items: Vec<ApeItem> = Vec::new();
+    let mut cursor: usize = 0;
+    while items.len() < item_count as usize && cursor + 8 < buffer.len() {
+        let value_size = parse_4_bytes_with_little_endian(&buffer[cursor..cursor + 4]) as usize;
+        let flags = parse_4_bytes_with_little_endian(&buffer[cursor + 4..cursor + 8]);
+        cursor += 8;
+        let key_length = buffer[cursor..]
+            .iter()
+            .position(|&b| b == 0x00)
+            .ok_or_else(|| ApeError::InvalidItem("Unterminated key".to_string()))?;
+        let key = String::from_utf8_lossy(&buffer[cursor..cursor + key_length]).into_owned();
+        cursor += key_length + 1;
+        if cursor + value_size > buffer.len() {
+            return Err(ApeError::InvalidItem(format!("{key} exceeds the tag")));
+        }
+        let raw = &buffer[cursor..cursor + value_size];
+        cursor += value_size;
+        let value = match (flags >> 1) & 0x03 {
+            0 => ApeValue::Text(
+                String::from_utf8_lossy(raw)
+                    .split('\0')
+                    .map(|s| s.to_string())
+                    .collect(),
+            ),
+            1 if key.to_ascii_lowercase().starts_with("cover art") => {
+                // a filename terminated by 0x00, followed by the image
+                match raw.iter().position(|&b| b == 0x00) {
+                    Some(index) => ApeValue::CoverArt(ApeCoverArt::new(
+                        String::from_utf8_lossy(&raw[..index]).into_owned(),
+                        raw[index + 1..].to_vec(),
+                    )),
+                    None => ApeValue::CoverArt(ApeCoverArt::new(String::default(), raw.to_vec())),
+                }
+            }
+            2 => ApeValue::ExternalLocator(String::from_utf8_lossy(raw).into_owned()),
+            _ => ApeValue::Binary(raw.to_vec()),
+        };
+        items.push(ApeItem::new(key, flags & 0x01 == 0x01, value));
+    }
+    Ok(items)
+}
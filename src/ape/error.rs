@@ -0,0 +1,16 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub enum ApeError {
+    WrongHeader,
+    InvalidItem(String),
+}
+
+impl Display for ApeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApeError::WrongHeader => write!(f, "not include APETAGEX"),
+            ApeError::InvalidItem(s) => write!(f, "invalid APE item: {s}"),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod ape_tag;
+pub mod core;
+pub mod error;
@@ -0,0 +1,320 @@
+use std::io::{self, SeekFrom};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::error::tolerate;
+use crate::flac::blocks::block_header::BlockHeader;
+use crate::flac::core::{parse_block_header, parse_flac_marker, picture_head_length};
+use crate::id3::core::{parse_extended_header, parse_footer_buffer, parse_protocol_header};
+use crate::id3::frames::header::FrameHeader;
+use crate::ogg::{
+    core::{collect_comment_packet, parse_page_header},
+    ogg_vorbis_comment::CommentHeader,
+};
+use crate::picture::{linked_picture, no_picture};
+use crate::probe::id3v2_length;
+use crate::util::Buffer;
+use crate::{
+    apic_data, block_read_length, check_capture_pattern, frame_rest, plan_frame,
+    record_skipped_frame, Error, FlacParser, FrameRead, ID3Parser, OggParser, PictureContent,
+    PictureLocation,
+};
+
+/// Read exactly `length` bytes.
+async fn read_buffer<R: AsyncRead + Unpin>(source: &mut R, length: usize) -> io::Result<Buffer> {
+    let mut buf = vec![0; length];
+    source.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Same as `read_picture`.
+async fn read_picture_async<R: AsyncRead + AsyncSeek + Unpin>(
+    source: &mut R,
+    location: Option<PictureLocation>,
+    data: Vec<u8>,
+) -> io::Result<Vec<u8>> {
+    match location {
+        Some(location) => {
+            source.seek(SeekFrom::Start(location.offset)).await?;
+            read_buffer(source, location.length as usize).await
+        }
+        None => Ok(data),
+    }
+}
+
+impl<R> ID3Parser<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Create a new parser over an async source, such as a `tokio::fs::File`.
+    ///
+    /// Use the `_async` methods to parse it.
+    pub async fn from_async_reader(mut source: R) -> io::Result<Self> {
+        let file_size = source.seek(SeekFrom::End(0)).await?;
+        Ok(ID3Parser::with_source(source, file_size))
+    }
+
+    /// Same as `parse_id3v1`.
+    pub async fn parse_id3v1_async(&mut self) -> io::Result<()> {
+        let length = self.file_size.min(128 + 227);
+        self.source
+            .seek(SeekFrom::Start(self.file_size - length))
+            .await?;
+        let tail = read_buffer(&mut self.source, length as usize).await?;
+        self.parse_id3v1_tail(&tail)
+    }
+
+    /// Same as `parse_id3v2`.
+    pub async fn parse_id3v2_async(&mut self) -> Result<(), Error> {
+        self.source.seek(SeekFrom::Start(0)).await?;
+        let mut buffer = read_buffer(&mut self.source, 10)
+            .await
+            .map_err(|e| Error::from_io(e, 0, "ID3v2 header"))?;
+        self.pheader = parse_protocol_header(&buffer).map_err(|e| e.at(0, "ID3v2 header"))?;
+        let mut start: u32 = 0;
+        if self.pheader.flags.ExtendedHeader {
+            let truncated = |e| Error::from_io(e, 10, "extended header");
+            buffer = read_buffer(&mut self.source, 10).await.map_err(truncated)?;
+            let mut ext = parse_extended_header(&buffer, &self.pheader.major_version);
+            ext.payload = read_buffer(&mut self.source, ext.len as usize)
+                .await
+                .map_err(truncated)?;
+            self.eheader = ext;
+            start += 10 + self.eheader.len as u32;
+        }
+
+        let mut pending: Vec<(u64, FrameHeader, Buffer)> = Vec::new();
+        if let Err(e) = self.read_frames_async(start, &mut pending).await {
+            // the following frames cannot be located
+            tolerate(self.parse_mode, &mut self.warnings, e)?;
+        }
+        self.decode_frames(pending)
+    }
+
+    /// Same as `read_frames`.
+    async fn read_frames_async(
+        &mut self,
+        mut start: u32,
+        pending: &mut Vec<(u64, FrameHeader, Buffer)>,
+    ) -> Result<(), Error> {
+        self.source.seek(SeekFrom::Start(10 + start as u64)).await?;
+        while start < self.pheader.size {
+            let offset = 10 + start as u64;
+            let buffer = read_buffer(&mut self.source, 10)
+                .await
+                .map_err(|e| Error::from_io(e, offset, "frame header"))?;
+            match plan_frame(&buffer, &self.pheader, start, self.picture_loading)? {
+                FrameRead::Payload(header, length) => {
+                    let context = header.identifier.to_string();
+                    let truncated = |e| Error::from_io(e, offset, &context);
+                    let mut payload = read_buffer(&mut self.source, length as usize)
+                        .await
+                        .map_err(truncated)?;
+                    let rest = frame_rest(&payload, &header, length, offset, self.file_size)?;
+                    if rest.read > 0 {
+                        payload.append(
+                            &mut read_buffer(&mut self.source, rest.read as usize)
+                                .await
+                                .map_err(truncated)?,
+                        );
+                    }
+                    if let Some(next) = rest.seek_to {
+                        self.source.seek(SeekFrom::Start(next)).await?;
+                    }
+                    start += 10 + header.size;
+                    pending.push((offset, header, payload));
+                }
+                FrameRead::Skip(id, skip) => {
+                    read_buffer(&mut self.source, skip as usize)
+                        .await
+                        .map_err(|e| Error::from_io(e, offset, &id))?;
+                    start += 10 + skip;
+                    record_skipped_frame(
+                        &mut self.warnings,
+                        &mut self.frame_order,
+                        offset,
+                        id,
+                        skip,
+                    );
+                }
+                FrameRead::Padding(padding_size, footer) => {
+                    self.padding_size = padding_size;
+                    if let Some(offset) = footer {
+                        self.source.seek(SeekFrom::Start(offset)).await?;
+                        let buffer = read_buffer(&mut self.source, 10)
+                            .await
+                            .map_err(|e| Error::from_io(e, offset, "footer"))?;
+                        self.footer =
+                            parse_footer_buffer(&buffer).map_err(|e| e.at(offset, "footer"))?;
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as `load_picture`.
+    pub async fn load_picture_async(&mut self, index: usize) -> io::Result<Vec<u8>> {
+        match self.picture_content_async(index).await? {
+            PictureContent::Image(data) => Ok(data),
+            PictureContent::Url(_) => Err(linked_picture(index)),
+        }
+    }
+
+    /// Same as `picture_content`.
+    pub async fn picture_content_async(&mut self, index: usize) -> io::Result<PictureContent> {
+        let frame = self.apic(index)?;
+        let mime = frame.mime_type().unwrap_or_default();
+        let (location, raw) = (frame.location(), apic_data(frame));
+        let data = read_picture_async(&mut self.source, location, raw).await?;
+        Ok(PictureContent::new(&mime, data))
+    }
+}
+
+impl<R> FlacParser<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Create a new FlacParser over an async source, such as a `tokio::fs::File`.
+    ///
+    /// Use `parse_async` to parse it.
+    pub fn from_async_reader(source: R) -> io::Result<Self> {
+        Ok(FlacParser::with_source(source))
+    }
+
+    /// Same as `parse`.
+    pub async fn parse_async(&mut self) -> Result<(), Error> {
+        self.marker_offset = 0;
+        self.block_order.clear();
+        self.source.seek(SeekFrom::Start(0)).await?;
+        let mut buffer = read_buffer(&mut self.source, 4)
+            .await
+            .map_err(|e| Error::from_io(e, 0, "flac header"))?;
+        if buffer.starts_with(b"ID3") {
+            if let Ok(mut rest) = read_buffer(&mut self.source, 6).await {
+                buffer.append(&mut rest);
+            }
+            if let Some(length) = id3v2_length(&buffer) {
+                self.marker_offset = length;
+                self.source.seek(SeekFrom::Start(length)).await?;
+                buffer = read_buffer(&mut self.source, 4)
+                    .await
+                    .map_err(|e| Error::from_io(e, length, "flac header"))?;
+            }
+        }
+        parse_flac_marker(buffer).map_err(|e| e.at(self.marker_offset, "flac header"))?;
+        let mut block_header: BlockHeader = BlockHeader::default();
+        // "fLaC"
+        let mut position: u64 = self.marker_offset + 4;
+        while !block_header.is_last {
+            let buffer = match read_buffer(&mut self.source, 4).await {
+                Ok(v) => v,
+                Err(e) => {
+                    let e = Error::from_io(e, position, "block header");
+                    // the following blocks cannot be located
+                    return tolerate(self.parse_mode, &mut self.warnings, e);
+                }
+            };
+            block_header =
+                parse_block_header(buffer).map_err(|e| e.at(position, "block header"))?;
+            let length = block_read_length(&block_header, self.picture_loading);
+            let mut rst = read_buffer(&mut self.source, length as usize).await;
+            if let Ok(v) = &mut rst {
+                if length < block_header.length {
+                    // the description is longer than the head
+                    if !matches!(picture_head_length(v), Some(n) if n <= v.len()) {
+                        let rest = (block_header.length - length) as usize;
+                        match read_buffer(&mut self.source, rest).await {
+                            Ok(mut rest) => v.append(&mut rest),
+                            Err(e) => rst = Err(e),
+                        }
+                    }
+                    let next = position + 4 + block_header.length as u64;
+                    self.source.seek(SeekFrom::Start(next)).await?;
+                }
+            }
+            let buffer = match rst {
+                Ok(v) => v,
+                Err(e) => {
+                    let context = format!("{:?}", block_header.block_type);
+                    let e = Error::from_io(e, position, &context);
+                    return tolerate(self.parse_mode, &mut self.warnings, e);
+                }
+            };
+            self.parse_block(&block_header, buffer, position)?;
+            position += 4 + block_header.length as u64;
+        }
+        self.audio_offset = position;
+        Ok(())
+    }
+
+    /// Same as `load_picture`.
+    pub async fn load_picture_async(&mut self, index: usize) -> io::Result<Vec<u8>> {
+        match self.picture_content_async(index).await? {
+            PictureContent::Image(data) => Ok(data),
+            PictureContent::Url(_) => Err(linked_picture(index)),
+        }
+    }
+
+    /// Same as `picture_content`.
+    pub async fn picture_content_async(&mut self, index: usize) -> io::Result<PictureContent> {
+        let picture = self.picture.get(index).ok_or_else(|| no_picture(index))?;
+        let (mime, data) = (picture.mime.clone(), picture.data.clone());
+        let data = read_picture_async(&mut self.source, picture.location, data).await?;
+        Ok(PictureContent::new(&mime, data))
+    }
+}
+
+impl<R> OggParser<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Create a new OggParser over an async source, such as a `tokio::fs::File`.
+    ///
+    /// Use `parse_async` to parse it.
+    pub fn from_async_reader(source: R) -> Self {
+        OggParser::with_source(source)
+    }
+
+    /// Same as `parse`.
+    pub async fn parse_async(&mut self) -> Result<(), Error> {
+        let mut comment_header = CommentHeader::default();
+        let comment_offset = match self.read_comment_packet_async(&mut comment_header).await {
+            Ok(offset) => offset,
+            // the comments cannot be located
+            Err(e) => return tolerate(self.parse_mode, &mut self.warnings, e),
+        };
+        self.parse_comment_packet(comment_header, comment_offset)
+    }
+
+    /// Same as `read_comment_packet`.
+    async fn read_comment_packet_async(
+        &mut self,
+        comment_header: &mut CommentHeader,
+    ) -> Result<u64, Error> {
+        self.source.seek(SeekFrom::Start(0)).await?;
+        // where the comment packet starts, for the errors
+        let mut comment_offset: u64 = 0;
+        while !comment_header.end {
+            let offset = self.source.stream_position().await?;
+            let truncated = |e: io::Error| Error::from_io(e, offset, "page");
+            let mut buffer = read_buffer(&mut self.source, 4).await.map_err(truncated)?;
+            check_capture_pattern(&buffer, offset)?;
+            buffer.append(&mut read_buffer(&mut self.source, 23).await.map_err(truncated)?);
+            let segment_table = read_buffer(&mut self.source, buffer[26] as usize)
+                .await
+                .map_err(truncated)?;
+            let body_length: usize = segment_table.iter().map(|&x| x as usize).sum();
+            let body = read_buffer(&mut self.source, body_length)
+                .await
+                .map_err(truncated)?;
+            let page_header = parse_page_header(&buffer, segment_table);
+            if collect_comment_packet(page_header, &body, &mut self.comment_buffer, comment_header)
+            {
+                comment_offset = offset;
+            }
+        }
+        Ok(comment_offset)
+    }
+}
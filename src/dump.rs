@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ApeTag, ApeValue, AudioFile, AudioProperties, BlockApplication, BlockCueSheet, BlockPicture,
+    BlockSeekTable, BlockStreamInfo, BlockVorbisComment, CharsetDetection, CommentBody,
+    ExtendedHeader, FlacParser, Footer, ID3Parser, ID3v1, Lyrics3, OggParser, PictureLocation,
+    ProtocolHeader, StreamScan, Warning,
+};
+
+/// What `dump` does with the image bytes of pictures.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PictureData {
+    /// keep them, they are written as base64
+    #[default]
+    Base64,
+    /// leave them empty, the rest of the picture is kept
+    Omit,
+}
+
+/// Everything `ID3Parser` has parsed, see `ID3Parser::dump`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ID3Dump {
+    pub pheader: ProtocolHeader,
+    pub eheader: ExtendedHeader,
+    pub padding_size: u32,
+    pub footer: Footer,
+    /// in the order of `get`, frames with the same identifier are together
+    pub frames: Vec<FrameDump>,
+    pub id3v1: ID3v1,
+    pub lyrics3: Lyrics3,
+    pub ape: ApeTag,
+    pub audio_properties: AudioProperties,
+    pub stream_scan: StreamScan,
+    pub id3v2_charset: Option<CharsetDetection>,
+    pub id3v1_charset: Option<CharsetDetection>,
+    pub warnings: Vec<Warning>,
+}
+
+/// An ID3v2 frame, as returned by `get` and `get_raw`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameDump {
+    pub identifier: String,
+    pub description: Option<String>,
+    pub message: String,
+    /// same as `get_raw`, written as base64
+    #[serde(with = "base64")]
+    pub raw: Vec<u8>,
+    pub location: Option<PictureLocation>,
+}
+
+/// Everything `FlacParser` has parsed, see `FlacParser::dump`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlacDump {
+    pub stream_info: BlockStreamInfo,
+    pub application: BlockApplication,
+    pub seek_table: BlockSeekTable,
+    pub vorbis_comment: BlockVorbisComment,
+    pub picture: Vec<BlockPicture>,
+    pub cue_sheet: BlockCueSheet,
+    pub padding_length: u32,
+    pub marker_offset: u64,
+    pub audio_offset: u64,
+    pub warnings: Vec<Warning>,
+}
+
+/// Everything `OggParser` has parsed, see `OggParser::dump`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OggDump {
+    pub audio_channels: u8,
+    pub audio_sample_rate: u32,
+    pub vorbis_comment: CommentBody,
+    pub warnings: Vec<Warning>,
+}
+
+/// Dump of any format, see `AudioFile::dump`.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Dump {
+    Mp3(ID3Dump),
+    Flac(FlacDump),
+    Ogg(OggDump),
+}
+
+impl<R> ID3Parser<R> {
+    /// Copy the parsed metadata into a structure that serde can serialize and deserialize.
+    ///
+    /// The APIC frames and the APEv2 cover art follow `pictures`.
+    pub fn dump(&self, pictures: PictureData) -> ID3Dump {
+        let mut frames: Vec<FrameDump> = Vec::new();
+        for frame in self.frames.iter().flatten() {
+            let identifier = frame.identifier();
+            let raw = match (identifier.as_str(), pictures) {
+                ("APIC", PictureData::Omit) => Vec::new(),
+                _ => frame.raw(),
+            };
+            frames.push(FrameDump {
+                identifier,
+                description: frame.description(),
+                message: frame.message(),
+                raw,
+                location: frame.location(),
+            });
+        }
+        let mut ape = self.ape.clone();
+        if pictures == PictureData::Omit {
+            for item in ape.items.iter_mut() {
+                if let ApeValue::CoverArt(cover) = &mut item.value {
+                    cover.data.clear();
+                }
+            }
+        }
+        ID3Dump {
+            pheader: self.pheader.clone(),
+            eheader: self.eheader.clone(),
+            padding_size: self.padding_size,
+            footer: self.footer.clone(),
+            frames,
+            id3v1: self.id3v1.clone(),
+            lyrics3: self.lyrics3.clone(),
+            ape,
+            audio_properties: self.audio_properties.clone(),
+            stream_scan: self.stream_scan.clone(),
+            id3v2_charset: self.id3v2_charset,
+            id3v1_charset: self.id3v1_charset,
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+impl<R> FlacParser<R> {
+    /// Copy the parsed metadata into a structure that serde can serialize and deserialize.
+    ///
+    /// The PICTURE blocks follow `pictures`.
+    pub fn dump(&self, pictures: PictureData) -> FlacDump {
+        let mut picture = self.picture.clone();
+        if pictures == PictureData::Omit {
+            for v in picture.iter_mut() {
+                v.data.clear();
+            }
+        }
+        FlacDump {
+            stream_info: self.stream_info.clone(),
+            application: self.application.clone(),
+            seek_table: self.seek_table.clone(),
+            vorbis_comment: self.vorbis_comment.clone(),
+            picture,
+            cue_sheet: self.cue_sheet.clone(),
+            padding_length: self.padding_length,
+            marker_offset: self.marker_offset,
+            audio_offset: self.audio_offset,
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+impl<R> OggParser<R> {
+    /// Copy the parsed metadata into a structure that serde can serialize and deserialize.
+    pub fn dump(&self) -> OggDump {
+        OggDump {
+            audio_channels: self.audio_channels,
+            audio_sample_rate: self.audio_sample_rate,
+            vorbis_comment: self.vorbis_comment.clone(),
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+impl AudioFile {
+    /// Same as the `dump` of the parser.
+    pub fn dump(&self, pictures: PictureData) -> Dump {
+        match self {
+            AudioFile::Mp3(parser) => Dump::Mp3(parser.dump(pictures)),
+            AudioFile::Flac(parser) => Dump::Flac(parser.dump(pictures)),
+            AudioFile::Ogg(parser) => Dump::Ogg(parser.dump()),
+        }
+    }
+}
+
+/// Byte fields written as base64 strings, for `#[serde(with = "base64")]`.
+pub(crate) mod base64 {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        STANDARD.decode(text).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,187 @@
+use std::{fmt::Display, io};
+
+/// Error returned by `ID3Parser::parse_id3v2`, `FlacParser::parse` and `OggParser::parse`.
+///
+/// Offsets are absolute positions from the start of the file.
+#[derive(Debug)]
+pub enum Error {
+    /// the file cannot be opened or read
+    Io(io::Error),
+    /// the magic of the expected format is missing, e.g. "ID3", "fLaC" or "OggS"
+    NotThisFormat {
+        expected: &'static str,
+    },
+    /// the data ends before `context` is complete
+    Truncated {
+        offset: u64,
+        context: String,
+    },
+    /// the text of `context` is not valid in its declared encoding
+    InvalidEncoding {
+        offset: u64,
+        context: String,
+    },
+    /// a frame, block or packet is malformed
+    InvalidFrame {
+        offset: u64,
+        context: String,
+        reason: String,
+    },
+    /// the header declares a `version` this crate cannot read, e.g. ID3v2.2
+    UnsupportedVersion {
+        offset: u64,
+        version: String,
+    },
+}
+
+impl Error {
+    /// Map a read error to `Truncated` if the file ends too early.
+    pub(crate) fn from_io(e: io::Error, offset: u64, context: &str) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            Error::Truncated {
+                offset,
+                context: context.to_string(),
+            }
+        } else {
+            Error::Io(e)
+        }
+    }
+
+    /// Position of the problem, if known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Error::Truncated { offset, .. }
+            | Error::InvalidEncoding { offset, .. }
+            | Error::InvalidFrame { offset, .. }
+            | Error::UnsupportedVersion { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::NotThisFormat { expected } => write!(f, "not include {expected} header"),
+            Error::Truncated { offset, context } => {
+                write!(f, "{context} at {offset} is truncated")
+            }
+            Error::InvalidEncoding { offset, context } => {
+                write!(f, "{context} at {offset} has invalid text encoding")
+            }
+            Error::InvalidFrame {
+                offset,
+                context,
+                reason,
+            } => write!(f, "{context} at {offset} is invalid: {reason}"),
+            Error::UnsupportedVersion { offset, version } => {
+                write!(f, "version {version} at {offset} is not supported")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// So that the methods returning `io::Result` can still use `?` on the parsers.
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e,
+            Error::Truncated { .. } => io::Error::new(io::ErrorKind::UnexpectedEof, e),
+            _ => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// How the parsers react to a broken frame, block or comment.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ParseMode {
+    /// stop at the first problem and return it
+    #[default]
+    Strict,
+    /// skip what is broken, keep going and record a `Warning`
+    Lenient,
+}
+
+/// A problem skipped by a parser.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Warning {
+    /// absolute position of the frame, block or page
+    pub offset: u64,
+    /// frame identifier, block type or "comment header"
+    pub identifier: String,
+    pub problem: String,
+}
+
+impl Warning {
+    pub fn new(offset: u64, identifier: &str, problem: &str) -> Self {
+        Warning {
+            offset,
+            identifier: identifier.to_string(),
+            problem: problem.to_string(),
+        }
+    }
+}
+
+impl From<Error> for Warning {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Truncated { offset, context } => Warning::new(offset, &context, "truncated"),
+            Error::InvalidEncoding { offset, context } => {
+                Warning::new(offset, &context, "invalid text encoding")
+            }
+            Error::InvalidFrame {
+                offset,
+                context,
+                reason,
+            } => Warning::new(offset, &context, &reason),
+            e => Warning::new(e.offset().unwrap_or_default(), "", &e.to_string()),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {}: {}",
+            self.identifier, self.offset, self.problem
+        )
+    }
+}
+
+/// Record `e` in `warnings` in lenient mode, or return it in strict mode.
+///
+/// I/O errors, unknown formats and unsupported versions are always returned.
+pub(crate) fn tolerate(
+    mode: ParseMode,
+    warnings: &mut Vec<Warning>,
+    e: Error,
+) -> Result<(), Error> {
+    match (mode, &e) {
+        (
+            ParseMode::Lenient,
+            Error::Truncated { .. } | Error::InvalidEncoding { .. } | Error::InvalidFrame { .. },
+        ) => {
+            warnings.push(e.into());
+            Ok(())
+        }
+        _ => Err(e),
+    }
+}
@@ -1,6 +1,8 @@
 #[derive(Debug)]
 #[allow(dead_code)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockApplication {
     pub id: u32,
     pub data: Vec<u8>,
@@ -1,6 +1,8 @@
 #[derive(Debug)]
 #[allow(dead_code)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockCueSheet {
     pub media_catalog: String,
     pub lead_in_samples_number: u64,
@@ -32,6 +34,8 @@ impl BlockCueSheet {
 #[derive(Debug)]
 #[allow(dead_code)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Track {
     offset: u64,
     number: u8,
@@ -68,6 +72,8 @@ impl Track {
 #[derive(Debug)]
 #[allow(dead_code)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrackIndex {
     offset: u64,
     index_point_number: u8,
@@ -16,10 +16,8 @@ impl BlockHeader {
     }
 }
 
-
-
-#[derive(Debug)]
-#[derive(Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlockType {
     STREAMINFO,
     PADDING,
@@ -28,8 +26,8 @@ pub enum BlockType {
     VORBISCOMMENT,
     CUESHEET,
     PICTURE,
+    /// 7 to 126, to be skipped
+    RESERVED,
     #[default]
     INVALID,
 }
-
-
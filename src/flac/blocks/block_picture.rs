@@ -1,5 +1,10 @@
-#[derive(Debug)]
-#[derive(Default)]
+use std::io;
+
+use crate::image::ImageInfo;
+use crate::picture::PictureLocation;
+
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockPicture {
     pub pic_type: PicType,
     pub mime: String,
@@ -8,39 +13,40 @@ pub struct BlockPicture {
     pub height: u32,
     pub bit_depth: u32,
     pub index_color_number: u32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::dump::base64"))]
     pub data: Vec<u8>,
     pub size: u32,
+    /// only with `PictureLoading::Lazy`, `data` is then empty
+    pub location: Option<PictureLocation>,
 }
 impl BlockPicture {
-    pub fn new(
-        pic_type: PicType,
-        mime: String,
-        description: String,
-        width: u32,
-        height: u32,
-        bit_depth: u32,
-        index_color_number: u32,
-        data: Vec<u8>,
-        size: u32,
-    ) -> Self {
-        BlockPicture {
+    /// A picture of the image bytes `data`, with the MIME type, dimensions, depth and number of colours read from them.
+    ///
+    /// Return an error if `data` is not a JPEG, PNG, GIF, WebP or BMP image.
+    pub fn from_image(pic_type: PicType, description: &str, data: Vec<u8>) -> io::Result<Self> {
+        let info = ImageInfo::detect(&data, "").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a JPEG, PNG, GIF, WebP or BMP image",
+            )
+        })?;
+        Ok(BlockPicture {
             pic_type,
-            mime,
-            description,
-            width,
-            height,
-            bit_depth,
-            index_color_number,
+            mime: info.format.mime().to_string(),
+            description: description.to_string(),
+            width: info.width,
+            height: info.height,
+            bit_depth: info.depth,
+            index_color_number: info.colors,
+            size: data.len() as u32,
             data,
-            size,
-        }
+            location: None,
+        })
     }
 }
 
-
-
-#[derive(Debug)]
-#[derive(Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PicType {
     #[default]
     Other,
@@ -64,9 +70,11 @@ pub enum PicType {
     Illustration,
     BandOrArtistLogotype,
     PublisherOrStudioLogoType,
+    /// a value that the specification does not define
+    Unknown(u32),
 }
-impl From<u8> for PicType {
-    fn from(value: u8) -> Self {
+impl From<u32> for PicType {
+    fn from(value: u32) -> Self {
         match value {
             0x00 => PicType::Other,
             0x01 => PicType::FileIcon32x32,
@@ -89,7 +97,35 @@ impl From<u8> for PicType {
             0x12 => PicType::Illustration,
             0x13 => PicType::BandOrArtistLogotype,
             0x14 => PicType::PublisherOrStudioLogoType,
-            _ => panic!("Invalid value for conversion to PicType"),
+            v => PicType::Unknown(v),
+        }
+    }
+}
+impl From<&PicType> for u32 {
+    fn from(value: &PicType) -> Self {
+        match value {
+            PicType::Other => 0x00,
+            PicType::FileIcon32x32 => 0x01,
+            PicType::OtherFileIcon => 0x02,
+            PicType::FrontCover => 0x03,
+            PicType::BackCover => 0x04,
+            PicType::LeafletPage => 0x05,
+            PicType::Media => 0x06,
+            PicType::LeadArtist => 0x07,
+            PicType::ArtistOrPerformer => 0x08,
+            PicType::Conductor => 0x09,
+            PicType::BandOrOrchestra => 0x0A,
+            PicType::Composer => 0x0B,
+            PicType::LyricistWriter => 0x0C,
+            PicType::RecordingLocation => 0x0D,
+            PicType::DuringRecording => 0x0E,
+            PicType::DuringPerformence => 0x0F,
+            PicType::MovieOrVideoScreenCapture => 0x10,
+            PicType::ABrightColouredFish => 0x11,
+            PicType::Illustration => 0x12,
+            PicType::BandOrArtistLogotype => 0x13,
+            PicType::PublisherOrStudioLogoType => 0x14,
+            PicType::Unknown(v) => *v,
         }
     }
 }
@@ -117,6 +153,7 @@ impl ToString for PicType {
             Self::Illustration => String::from("Illustration"),
             Self::BandOrArtistLogotype => String::from("BandOrArtistLogotype"),
             Self::PublisherOrStudioLogoType => String::from("PublisherOrStudioLogoType"),
+            Self::Unknown(v) => format!("Unknown{v}"),
         }
     }
 }
@@ -1,11 +1,15 @@
 #[derive(Debug)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockSeekTable {
     pub seekpoints: Vec<SeekPoint>,
 }
 
 #[derive(Debug)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SeekPoint {
     pub sample_number_of_first_sample: u64,
     pub offset: u64,
@@ -1,6 +1,8 @@
 #[derive(Debug)]
 #[allow(dead_code)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockStreamInfo {
     pub min_block_size: u16,
     pub max_block_size: u16,
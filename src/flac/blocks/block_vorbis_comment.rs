@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 #[derive(Debug)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockVorbisComment {
     pub encoder: String,
     pub hm: HashMap<String, usize>,
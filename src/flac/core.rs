@@ -1,9 +1,10 @@
-use std::io;
-
 use crate::{
     flac::{blocks::block_picture::PicType, error::FlacError},
+    layout::Span,
+    picture::PictureLocation,
     util::{
-        parse_4_bytes_with_big_endian, parse_4_bytes_with_little_endian, parse_8_bytes_with_big_endian, update_start_end
+        parse_4_bytes_with_big_endian, parse_4_bytes_with_little_endian,
+        parse_8_bytes_with_big_endian, update_start_end,
     },
 };
 
@@ -23,7 +24,7 @@ pub fn parse_flac_marker(buffer: Vec<u8>) -> Result<(), FlacError> {
     }
     Err(FlacError::WrongHeader)
 }
-pub fn parse_block_header(buffer: Vec<u8>) -> io::Result<BlockHeader> {
+pub fn parse_block_header(buffer: Vec<u8>) -> Result<BlockHeader, FlacError> {
     let is_last = (buffer[0] & 0x80) == 0x80;
     let block_type: BlockType = match buffer[0] & 0x7F {
         0 => BlockType::STREAMINFO,
@@ -33,13 +34,81 @@ pub fn parse_block_header(buffer: Vec<u8>) -> io::Result<BlockHeader> {
         4 => BlockType::VORBISCOMMENT,
         5 => BlockType::CUESHEET,
         6 => BlockType::PICTURE,
-        _ => BlockType::INVALID,
+        127 => BlockType::INVALID,
+        _ => BlockType::RESERVED,
     };
     let length = buffer[1] as u32 * 0x10000 + buffer[2] as u32 * 0x100 + buffer[3] as u32;
     Ok(BlockHeader::new(is_last, block_type, length))
 }
 
-pub fn parse_stream_info_block(buffer: Vec<u8>) -> io::Result<BlockStreamInfo> {
+/// Largest length of the data of a block, it is written on 24 bits.
+pub const MAX_BLOCK_LENGTH: u32 = 0xFF_FFFF;
+
+/// Length of the PADDING block written when the metadata blocks have to be moved.
+pub const DEFAULT_PADDING: u32 = 4096;
+
+/// Number of `block_type` in a block header, 7 for RESERVED which stands for 7 to 126.
+pub fn block_type_number(block_type: BlockType) -> u8 {
+    match block_type {
+        BlockType::STREAMINFO => 0,
+        BlockType::PADDING => 1,
+        BlockType::APPLICATION => 2,
+        BlockType::SEEKTABLE => 3,
+        BlockType::VORBISCOMMENT => 4,
+        BlockType::CUESHEET => 5,
+        BlockType::PICTURE => 6,
+        BlockType::RESERVED => 7,
+        BlockType::INVALID => 127,
+    }
+}
+
+/// Serialize a block header, `number` is the block type as returned by `block_type_number`.
+pub fn block_header_bytes(is_last: bool, number: u8, length: u32) -> [u8; 4] {
+    let flag = if is_last { 0x80 } else { 0x00 };
+    let length = length.to_be_bytes();
+    [flag | (number & 0x7F), length[1], length[2], length[3]]
+}
+
+/// Serialize the data of a PICTURE block, `data` is the image bytes or the URL of the image.
+pub fn block_picture_bytes(picture: &BlockPicture, data: &[u8]) -> Vec<u8> {
+    let mut buffer: Vec<u8> = Vec::new();
+    buffer.extend_from_slice(&u32::from(&picture.pic_type).to_be_bytes());
+    buffer.extend_from_slice(&(picture.mime.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(picture.mime.as_bytes());
+    buffer.extend_from_slice(&(picture.description.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(picture.description.as_bytes());
+    buffer.extend_from_slice(&picture.width.to_be_bytes());
+    buffer.extend_from_slice(&picture.height.to_be_bytes());
+    buffer.extend_from_slice(&picture.bit_depth.to_be_bytes());
+    buffer.extend_from_slice(&picture.index_color_number.to_be_bytes());
+    buffer.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(data);
+    buffer
+}
+
+/// Serialize the data of a VORBIS_COMMENT block, the lengths are little-endian unlike the rest of FLAC.
+pub fn vorbis_comment_bytes<'a>(
+    encoder: &str,
+    comments: impl ExactSizeIterator<Item = (&'a str, &'a str)>,
+) -> Vec<u8> {
+    let mut buffer: Vec<u8> = Vec::new();
+    buffer.extend_from_slice(&(encoder.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(encoder.as_bytes());
+    buffer.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for (key, value) in comments {
+        let length = key.len() + 1 + value.len();
+        buffer.extend_from_slice(&(length as u32).to_le_bytes());
+        buffer.extend_from_slice(key.as_bytes());
+        buffer.push(b'=');
+        buffer.extend_from_slice(value.as_bytes());
+    }
+    buffer
+}
+
+pub fn parse_stream_info_block(buffer: Vec<u8>) -> Result<BlockStreamInfo, FlacError> {
+    if buffer.len() < 34 {
+        return Err(FlacError::Truncated);
+    }
     let min_block_size: u16 = buffer[0] as u16 * 0x100 + buffer[1] as u16; // 2 Bytes
     let max_block_size: u16 = buffer[2] as u16 * 0x100 + buffer[3] as u16; // 2 Bytes
     let min_frame_size: u32 =
@@ -85,106 +154,164 @@ pub fn parse_stream_info_block(buffer: Vec<u8>) -> io::Result<BlockStreamInfo> {
         md5,
     ))
 }
-pub fn parse_vorbis_comment(buffer: Vec<u8>) -> io::Result<BlockVorbisComment> {
+/// Comments that are not valid UTF-8 or have no '=' are skipped and pushed to `invalid`.
+///
+/// The others are also pushed to `order` in file order, `data_offset` is the position of `buffer` in the file.
+pub fn parse_vorbis_comment(
+    buffer: Vec<u8>,
+    invalid: &mut Vec<FlacError>,
+    order: &mut Vec<(Span, String, String)>,
+    data_offset: u64,
+) -> Result<BlockVorbisComment, FlacError> {
     let mut vorbis_comment = BlockVorbisComment::default();
     // let buffer: Vec<u8> = buf.to_owned();
     let mut start = 0;
     let mut end = 3;
-    let encoder_length = parse_4_bytes_with_little_endian(&buffer[start..=end]);
+    let encoder_length = parse_4_bytes_with_little_endian(field(&buffer, start, end)?);
 
     update_start_end(&mut start, &mut end, encoder_length as usize);
     // start = end + 1;
     // end = start - 1 + encoder_length as usize;
-    let encoder: String = String::from_utf8(buffer[start..=end].to_vec()).unwrap();
+    let encoder: String = String::from_utf8(field(&buffer, start, end)?.to_vec())
+        .map_err(|_| FlacError::InvalidEncoding("vendor".to_string()))?;
     vorbis_comment.encoder = encoder;
 
     update_start_end(&mut start, &mut end, 4);
     // start = end + 1;
     // end = start - 1 + 4;
-    let tags_number = parse_4_bytes_with_little_endian(&buffer[start..=end]);
+    let tags_number = parse_4_bytes_with_little_endian(field(&buffer, start, end)?);
 
     let mut tag_index = 0;
 
     while tag_index < tags_number {
         tag_index += 1;
         update_start_end(&mut start, &mut end, 4);
+        let comment_offset = data_offset + start as u64;
         // start = end + 1;
         // end = start - 1 + 4;
-        let tag_length = parse_4_bytes_with_little_endian(&buffer[start..=end]);
+        let tag_length = parse_4_bytes_with_little_endian(field(&buffer, start, end)?);
         update_start_end(&mut start, &mut end, tag_length as usize);
         // start = end + 1;
         // end = start - 1 + tag_length as usize;
-        let tag_content_raw = String::from_utf8(buffer[start..=end].to_vec()).unwrap();
-        let kv: Vec<&str> = tag_content_raw.split('=').collect();
-        let tag_key: String = kv[0].to_owned();
-        let tag_value = kv[1].to_owned();
+        let Ok(tag_content_raw) = String::from_utf8(field(&buffer, start, end)?.to_vec()) else {
+            invalid.push(FlacError::InvalidEncoding(format!("comment {tag_index}")));
+            continue;
+        };
+        let Some((tag_key, tag_value)) = tag_content_raw.split_once('=') else {
+            invalid.push(FlacError::InvalidBlock(format!(
+                "comment {tag_content_raw:?} has no '='"
+            )));
+            continue;
+        };
+        let span = Span::new(comment_offset, 4, tag_length);
+        order.push((span, tag_key.to_owned(), tag_value.to_owned()));
+        // the names are case insensitive
+        let tag_key: String = tag_key.to_uppercase();
+        let tag_value = tag_value.to_owned();
         if let Some(index) = vorbis_comment.hm.get(&tag_key) {
             vorbis_comment.comment[*index].push(tag_value);
         } else {
             let tag_index = vorbis_comment.comment.len();
-            vorbis_comment.hm.insert(tag_key.to_uppercase(), tag_index);
+            vorbis_comment.hm.insert(tag_key, tag_index);
             vorbis_comment.comment.push(Vec::default());
             vorbis_comment.comment[tag_index].push(tag_value);
         }
     }
     Ok(vorbis_comment)
 }
-pub fn parse_block_picture(buffer: Vec<u8>) -> io::Result<BlockPicture> {
+pub fn parse_block_picture(buffer: Vec<u8>) -> Result<BlockPicture, FlacError> {
+    let (mut picture, start) = parse_block_picture_head(&buffer)?;
+    picture.data = buffer[start..].to_vec();
+    Ok(picture)
+}
+
+/// Parse a PICTURE block of `length` bytes whose data starts at `data_offset` of the file,
+///
+/// without its image bytes, `buffer` may hold only the first bytes of the data.
+pub fn parse_lazy_block_picture(
+    buffer: &[u8],
+    data_offset: u64,
+    length: u32,
+) -> Result<BlockPicture, FlacError> {
+    let (mut picture, start) = parse_block_picture_head(buffer)?;
+    if start > length as usize {
+        return Err(FlacError::Truncated);
+    }
+    picture.location = Some(PictureLocation::new(
+        data_offset + start as u64,
+        (length as usize - start) as u64,
+    ));
+    Ok(picture)
+}
+
+/// Return the length of the PICTURE fields before the image bytes,
+///
+/// or None if `buffer` is too short to tell.
+pub fn picture_head_length(buffer: &[u8]) -> Option<usize> {
+    let mime_length = parse_4_bytes_with_big_endian(buffer.get(4..8)?) as usize;
+    let desc_start = 8 + mime_length;
+    let desc_length = parse_4_bytes_with_big_endian(buffer.get(desc_start..desc_start + 4)?);
+    // width, height, bit depth, index color number and size
+    Some(desc_start + 4 + desc_length as usize + 20)
+}
+
+/// Parse the fields of a PICTURE block before the image bytes,
+///
+/// returning them and the position of the image bytes in `buffer`.
+fn parse_block_picture_head(buffer: &[u8]) -> Result<(BlockPicture, usize), FlacError> {
     // let buffer: Vec<u8> = buf.to_owned();
-    let mut start = 4 ;
+    let mut start = 4;
     let mut end = 3;
-    // let raw_pic_type = parse_4_bytes_with_big_endian(&buffer[start..=end]);
-    let raw_pic_type = buffer[end];
-    let pic_type: PicType = PicType::from(raw_pic_type);
+    let pic_type: PicType = PicType::from(parse_4_bytes_with_big_endian(field(buffer, 0, end)?));
     update_start_end(&mut start, &mut end, 4);
     // start = end + 1;
     // end = start - 1 + 4;
-    let mime_length = parse_4_bytes_with_big_endian(&buffer[start..=end]);
-    
+    let mime_length = parse_4_bytes_with_big_endian(field(buffer, start, end)?);
+
     update_start_end(&mut start, &mut end, mime_length as usize);
     // start = end + 1;
     // end = start - 1 + mime_length as usize;
-    let mime: String = String::from_utf8(buffer[start..=end].to_vec()).unwrap();
-    
+    let mime: String = String::from_utf8(field(buffer, start, end)?.to_vec())
+        .map_err(|_| FlacError::InvalidEncoding("MIME type".to_string()))?;
+
     update_start_end(&mut start, &mut end, 4);
     // start = end + 1;
     // end = start - 1 + 4;
-    let desc_length = parse_4_bytes_with_big_endian(&buffer[start..=end]);
+    let desc_length = parse_4_bytes_with_big_endian(field(buffer, start, end)?);
 
     update_start_end(&mut start, &mut end, desc_length as usize);
     // start = end + 1;
     // end = start - 1 + desc_length as usize;
-    let description: String = String::from_utf8(buffer[start..=end].to_vec()).unwrap();
+    let description: String = String::from_utf8(field(buffer, start, end)?.to_vec())
+        .map_err(|_| FlacError::InvalidEncoding("description".to_string()))?;
 
     update_start_end(&mut start, &mut end, 4);
     // start = end + 1;
     // end = start - 1 + 4;
-    let width = parse_4_bytes_with_big_endian(&buffer[start..=end]);
+    let width = parse_4_bytes_with_big_endian(field(buffer, start, end)?);
 
     update_start_end(&mut start, &mut end, 4);
     // start = end + 1;
     // end = start - 1 + 4;
-    let height = parse_4_bytes_with_big_endian(&buffer[start..=end]);
+    let height = parse_4_bytes_with_big_endian(field(buffer, start, end)?);
 
     update_start_end(&mut start, &mut end, 4);
     // start = end + 1;
     // end = start - 1 + 4;
-    let bit_depth = parse_4_bytes_with_big_endian(&buffer[start..=end]);
+    let bit_depth = parse_4_bytes_with_big_endian(field(buffer, start, end)?);
 
     update_start_end(&mut start, &mut end, 4);
     // start = end + 1;
     // end = start - 1 + 4;
-    let index_color_number = parse_4_bytes_with_big_endian(&buffer[start..=end]);
+    let index_color_number = parse_4_bytes_with_big_endian(field(buffer, start, end)?);
 
     update_start_end(&mut start, &mut end, 4);
     // start = end + 1;
     // end = start - 1 + 4;
-    let size = parse_4_bytes_with_big_endian(&buffer[start..=end]);
+    let size = parse_4_bytes_with_big_endian(field(buffer, start, end)?);
 
     start = end + 1;
-    let mut data: Vec<u8> = buffer[start..].to_vec();
-    data.push(raw_pic_type);
-    Ok(BlockPicture::new(
+    let picture = BlockPicture {
         pic_type,
         mime,
         description,
@@ -192,16 +319,17 @@ pub fn parse_block_picture(buffer: Vec<u8>) -> io::Result<BlockPicture> {
         height,
         bit_depth,
         index_color_number,
-        data,
         size,
-    ))
+        ..Default::default()
+    };
+    Ok((picture, start))
 }
-pub fn parse_block_application(buffer: Vec<u8>) -> io::Result<BlockApplication> {
-    let id = parse_4_bytes_with_big_endian(&buffer[0..=3]);
+pub fn parse_block_application(buffer: Vec<u8>) -> Result<BlockApplication, FlacError> {
+    let id = parse_4_bytes_with_big_endian(field(&buffer, 0, 3)?);
     let data: Vec<u8> = buffer[4..].to_owned();
     Ok(BlockApplication::new(id, data))
 }
-pub fn parse_block_seektable(buffer: Vec<u8>) -> io::Result<BlockSeekTable> {
+pub fn parse_block_seektable(buffer: Vec<u8>) -> Result<BlockSeekTable, FlacError> {
     // let buffer = buf.to_owned();
     let mut start = 0;
     let mut end = 7; // for the first loop
@@ -210,7 +338,7 @@ pub fn parse_block_seektable(buffer: Vec<u8>) -> io::Result<BlockSeekTable> {
     while end < length {
         let mut seek_point = SeekPoint::default();
 
-        let snfs = parse_8_bytes_with_big_endian(&buffer[start..=end]);
+        let snfs = parse_8_bytes_with_big_endian(field(&buffer, start, end)?);
         seek_point.sample_number_of_first_sample = snfs;
         if snfs == 0xFFFFFFFFFFFFFFFF {
             seek_table.seekpoints.push(seek_point);
@@ -220,17 +348,18 @@ pub fn parse_block_seektable(buffer: Vec<u8>) -> io::Result<BlockSeekTable> {
             // end = start - 1 + 8; // for next loop
             continue;
         }
-        
+
         update_start_end(&mut start, &mut end, 8);
         // start = end + 1;
         // end = start - 1 + 8;
-        let offset = parse_8_bytes_with_big_endian(&buffer[start..=end]);
+        let offset = parse_8_bytes_with_big_endian(field(&buffer, start, end)?);
         seek_point.offset = offset;
 
         update_start_end(&mut start, &mut end, 2);
         // start = end + 1;
         // end = start - 1 + 2;
-        let na = buffer[start] as u16 * 0x100 + buffer[end] as u16;
+        let na = field(&buffer, start, end)?;
+        let na = na[0] as u16 * 0x100 + na[1] as u16;
         seek_point.number_of_samples = na;
 
         seek_table.seekpoints.push(seek_point);
@@ -242,21 +371,22 @@ pub fn parse_block_seektable(buffer: Vec<u8>) -> io::Result<BlockSeekTable> {
     Ok(seek_table)
 }
 
-pub fn parse_block_cue_sheet(buffer: Vec<u8>) -> io::Result<BlockCueSheet> {
+pub fn parse_block_cue_sheet(buffer: Vec<u8>) -> Result<BlockCueSheet, FlacError> {
     // let buffer = buf.to_owned();
     let mut start: usize = 0;
     let mut end: usize = 127;
-    let media_catalog = String::from_utf8(buffer[start..=end].to_vec()).unwrap();
+    let media_catalog = String::from_utf8(field(&buffer, start, end)?.to_vec())
+        .map_err(|_| FlacError::InvalidEncoding("media catalog number".to_string()))?;
 
     update_start_end(&mut start, &mut end, 8);
     // start = end + 1;
     // end = start - 1 + 8;
-    let lead_in_samples_number: u64 = parse_8_bytes_with_big_endian(&buffer[start..=end]);
+    let lead_in_samples_number: u64 = parse_8_bytes_with_big_endian(field(&buffer, start, end)?);
 
     update_start_end(&mut start, &mut end, 1);
     // start = end + 1;
     // end = start - 1 + 1;
-    let is_cd: bool = ((buffer[start] & 0x80) >> 7) == 1;
+    let is_cd: bool = ((field(&buffer, start, start)?[0] & 0x80) >> 7) == 1;
 
     update_start_end(&mut start, &mut end, 258);
     // start = end + 1;
@@ -266,7 +396,7 @@ pub fn parse_block_cue_sheet(buffer: Vec<u8>) -> io::Result<BlockCueSheet> {
     update_start_end(&mut start, &mut end, 1);
     // start = end + 1;
     // end = start - 1 + 1;
-    let tracks_number: u8 = buffer[start];
+    let tracks_number: u8 = field(&buffer, start, start)?[0];
 
     let mut tracks: Vec<Track> = Vec::default();
 
@@ -277,26 +407,25 @@ pub fn parse_block_cue_sheet(buffer: Vec<u8>) -> io::Result<BlockCueSheet> {
         update_start_end(&mut start, &mut end, 8);
         // start = end + 1;
         // end = start - 1 + 8;
-        let offset: u64 = parse_8_bytes_with_big_endian(&buffer[start..=end]);
+        let offset: u64 = parse_8_bytes_with_big_endian(field(&buffer, start, end)?);
 
         update_start_end(&mut start, &mut end, 1);
         // start = end + 1;
         // end = start - 1 + 1;
-        let number = buffer[start];
+        let number = field(&buffer, start, start)?[0];
 
         update_start_end(&mut start, &mut end, 12);
         // start = end + 1;
         // end = start - 1 + 12;
-        let isrc = String::from_utf8(buffer[start..=end].to_vec())
-            .to_owned()
-            .unwrap();
-        
+        let isrc = String::from_utf8(field(&buffer, start, end)?.to_vec())
+            .map_err(|_| FlacError::InvalidEncoding("ISRC".to_string()))?;
+
         update_start_end(&mut start, &mut end, 1);
         // start = end + 1;
         // end = start - 1 + 1;
-        let is_audio_track: bool = ((buffer[start] & 0x80) >> 7) == 0;
+        let is_audio_track: bool = ((field(&buffer, start, start)?[0] & 0x80) >> 7) == 0;
 
-        let pre_emphasis: bool = ((buffer[start] & 0x40) >> 6) == 1;
+        let pre_emphasis: bool = ((field(&buffer, start, start)?[0] & 0x40) >> 6) == 1;
 
         update_start_end(&mut start, &mut end, 13);
         // start = end + 1;
@@ -306,7 +435,7 @@ pub fn parse_block_cue_sheet(buffer: Vec<u8>) -> io::Result<BlockCueSheet> {
         update_start_end(&mut start, &mut end, 1);
         // start = end + 1;
         // end = start - 1 + 1;
-        let track_index_points_number: u8 = buffer[start];
+        let track_index_points_number: u8 = field(&buffer, start, start)?[0];
 
         let mut j = 0;
         let mut track_indices: Vec<TrackIndex> = Vec::default();
@@ -315,12 +444,12 @@ pub fn parse_block_cue_sheet(buffer: Vec<u8>) -> io::Result<BlockCueSheet> {
             update_start_end(&mut start, &mut end, 8);
             // start = end + 1;
             // end = start - 1 + 8;
-            let index_offset = parse_8_bytes_with_big_endian(&buffer[start..=end]);
+            let index_offset = parse_8_bytes_with_big_endian(field(&buffer, start, end)?);
 
             update_start_end(&mut start, &mut end, 1);
             // start = end + 1;
             // end = start - 1 + 1;
-            let index_point_number = buffer[start];
+            let index_point_number = field(&buffer, start, start)?[0];
 
             update_start_end(&mut start, &mut end, 3);
             // start = end + 1;
@@ -347,3 +476,58 @@ pub fn parse_block_cue_sheet(buffer: Vec<u8>) -> io::Result<BlockCueSheet> {
         tracks,
     ))
 }
+
+/// Bytes from `start` to `end` inclusive, or an error if the block is too short.
+fn field(buffer: &[u8], start: usize, end: usize) -> Result<&[u8], FlacError> {
+    buffer.get(start..=end).ok_or(FlacError::Truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flac::blocks::block_picture::PicType;
+
+    fn picture() -> BlockPicture {
+        BlockPicture {
+            pic_type: PicType::BackCover,
+            mime: "image/gif".to_string(),
+            description: "back \u{e9}".to_string(),
+            width: 640,
+            height: 480,
+            bit_depth: 8,
+            index_color_number: 256,
+            size: 6,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn picture_round_trip() {
+        let bytes = block_picture_bytes(&picture(), b"GIF89a");
+        let parsed = parse_block_picture(bytes).unwrap();
+        let expected = picture();
+        assert_eq!(parsed.pic_type, expected.pic_type);
+        assert_eq!(parsed.mime, expected.mime);
+        assert_eq!(parsed.description, expected.description);
+        assert_eq!((parsed.width, parsed.height), (640, 480));
+        assert_eq!((parsed.bit_depth, parsed.index_color_number), (8, 256));
+        assert_eq!(parsed.size, 6);
+        assert_eq!(parsed.data, b"GIF89a");
+    }
+
+    #[test]
+    fn truncated_picture() {
+        let bytes = block_picture_bytes(&picture(), b"GIF89a");
+        // cut in the MIME type, the description and the dimensions
+        for length in [0, 6, 24, 40] {
+            assert!(
+                parse_block_picture(bytes[..length].to_vec()).is_err(),
+                "{length}"
+            );
+        }
+        let mut bytes = bytes;
+        // a MIME type longer than the block
+        bytes[4..8].copy_from_slice(&1000u32.to_be_bytes());
+        assert!(parse_block_picture(bytes).is_err());
+    }
+}
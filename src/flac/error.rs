@@ -1,3 +1,30 @@
+use crate::error::Error;
+
+#[derive(Debug)]
 pub enum FlacError {
-    WrongHeader
-}
\ No newline at end of file
+    WrongHeader,
+    /// the block is shorter than its fields
+    Truncated,
+    InvalidEncoding(String),
+    InvalidBlock(String),
+}
+
+impl FlacError {
+    /// Convert into the public error, `context` is usually the block type.
+    pub fn at(self, offset: u64, context: &str) -> Error {
+        let context = context.to_string();
+        match self {
+            FlacError::WrongHeader => Error::NotThisFormat { expected: "FLAC" },
+            FlacError::Truncated => Error::Truncated { offset, context },
+            FlacError::InvalidEncoding(field) => Error::InvalidEncoding {
+                offset,
+                context: format!("{context} {field}"),
+            },
+            FlacError::InvalidBlock(reason) => Error::InvalidFrame {
+                offset,
+                context,
+                reason,
+            },
+        }
+    }
+}
@@ -1,16 +1,16 @@
-use std::{fs::File, io::{self, Read}, path::Path};
+use std::io::{self, Read, Seek};
 
 use crate::util::Buffer;
 
-pub struct FlacBufferReader {
-    file: File,
+pub struct FlacBufferReader<'a, R: Read + Seek> {
+    file: &'a mut R,
 }
 
-impl FlacBufferReader {
-    pub fn new<T: AsRef<Path>>(f: T) -> io::Result<Self> {
-        Ok(FlacBufferReader {
-            file: File::open(f)?,
-        })
+impl<'a, R: Read + Seek> FlacBufferReader<'a, R> {
+    /// Start reading `f` from the beginning.
+    pub fn new(f: &'a mut R) -> io::Result<Self> {
+        f.rewind()?;
+        Ok(FlacBufferReader { file: f })
     }
     pub fn read_block_header(&mut self) -> io::Result<Buffer> {
         let mut buf = vec![0; 4];
@@ -22,4 +22,8 @@ impl FlacBufferReader {
         self.file.read_exact(&mut buf)?;
         Ok(buf)
     }
-}
\ No newline at end of file
+    /// absolute position from file start
+    pub fn seek_to(&mut self, location: u64) -> io::Result<u64> {
+        self.file.seek(io::SeekFrom::Start(location))
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    fmt::Display,
+    io::{self, Read, Seek, SeekFrom},
+};
+
+use sha2::{Digest, Sha256};
+
+/// Digest of the audio payload only, so that retagging a file does not change it.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioHash {
+    /// SHA-256
+    pub digest: [u8; 32],
+    /// absolute position of the first hashed byte
+    ///
+    /// For Ogg, the range spans the audio pages but only their bodies are hashed, not the page headers.
+    pub start: u64,
+    /// absolute position right after the last hashed byte
+    pub end: u64,
+}
+
+impl AudioHash {
+    pub fn new(digest: [u8; 32], start: u64, end: u64) -> Self {
+        AudioHash { digest, start, end }
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl Display for AudioHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "
+AudioHash {{
+    digest: {},
+    range: {}..{}
+}}",
+            self.to_hex(),
+            self.start,
+            self.end
+        )
+    }
+}
+
+/// Hash the bytes from `start` to `end` of a file.
+pub fn hash_range<R: Read + Seek>(file: &mut R, start: u64, end: u64) -> io::Result<AudioHash> {
+    file.seek(SeekFrom::Start(start))?;
+    let mut sha = Sha256::default();
+    let mut buf = vec![0; 0x10000];
+    let mut remaining = end.saturating_sub(start);
+    while remaining > 0 {
+        let n = (remaining as usize).min(buf.len());
+        file.read_exact(&mut buf[..n])?;
+        sha.update(&buf[..n]);
+        remaining -= n as u64;
+    }
+    Ok(AudioHash::new(sha.finalize().into(), start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn range_of_a_file() {
+        let mut file: Vec<u8> = vec![0xFF; 10];
+        file.extend_from_slice(b"abc");
+        file.extend_from_slice(&[0xFF; 5]);
+        let hash = hash_range(&mut Cursor::new(&file), 10, 13).unwrap();
+        assert_eq!(
+            hash.to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!((hash.start, hash.end), (10, 13));
+        assert!(hash_range(&mut Cursor::new(&file), 10, 100).is_err());
+    }
+}
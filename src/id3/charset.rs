@@ -0,0 +1,339 @@
+use std::fmt::Display;
+
+use encoding_rs::{Encoding, GBK, SHIFT_JIS, WINDOWS_1251};
+
+use crate::util;
+
+/// Charset of text stored without an encoding byte, such as ID3v1 fields,
+///
+/// or of text that is declared as ISO-8859-1 but actually written in a legacy charset.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Charset {
+    #[default]
+    ISO_8859_1,
+    UTF8,
+    /// Simplified Chinese, a superset of GB2312
+    GBK,
+    /// Japanese
+    Shift_JIS,
+    /// Cyrillic
+    CP1251,
+}
+
+impl Charset {
+    /// Encode `text`, keeping at most `max_len` bytes.
+    ///
+    /// Characters that cannot be represented in the charset are replaced with '?',
+    ///
+    /// and a multi-byte character is never cut in the middle.
+    pub fn encode(&self, text: &str, max_len: usize) -> Vec<u8> {
+        let mut buf: Vec<u8> = Vec::new();
+        match self {
+            Charset::ISO_8859_1 => {
+                for c in text.chars().take(max_len) {
+                    buf.push(if (c as u32) <= 0xFF { c as u8 } else { b'?' });
+                }
+            }
+            Charset::UTF8 => {
+                for c in text.chars() {
+                    if buf.len() + c.len_utf8() > max_len {
+                        break;
+                    }
+                    let mut tmp = [0; 4];
+                    buf.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+                }
+            }
+            _ => {
+                let encoding = self.encoding();
+                for c in text.chars() {
+                    let mut tmp = [0; 4];
+                    let (bytes, _, had_errors) = encoding.encode(c.encode_utf8(&mut tmp));
+                    let bytes: &[u8] = if had_errors { b"?" } else { &bytes };
+                    if buf.len() + bytes.len() > max_len {
+                        break;
+                    }
+                    buf.extend_from_slice(bytes);
+                }
+            }
+        }
+        buf
+    }
+
+    /// Decode `bytes`, dropping the leading and trailing 0x00 as `util::latin1_to_string` does,
+    ///
+    /// which remains the decoder of ISO-8859-1.
+    ///
+    /// Invalid sequences are replaced with U+FFFD.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        if let Charset::ISO_8859_1 = self {
+            return util::latin1_to_string(bytes);
+        }
+        let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+        let end = bytes.iter().rposition(|&b| b != 0).map_or(start, |i| i + 1);
+        let bytes = &bytes[start..end];
+        match self {
+            Charset::UTF8 => String::from_utf8_lossy(bytes).into_owned(),
+            _ => self
+                .encoding()
+                .decode_without_bom_handling(bytes)
+                .0
+                .into_owned(),
+        }
+    }
+
+    /// Guess the charset of `bytes` that is declared as ISO-8859-1.
+    ///
+    /// Pure ASCII is reported as ISO-8859-1 with full confidence.
+    pub fn detect(bytes: &[u8]) -> CharsetDetection {
+        if bytes.is_ascii() {
+            return CharsetDetection::new(Charset::ISO_8859_1, 1.0);
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return CharsetDetection::new(Charset::UTF8, 1.0);
+        }
+        let mut scores = [
+            (Charset::ISO_8859_1, score_latin1(bytes)),
+            (Charset::GBK, score_gbk(bytes)),
+            (Charset::Shift_JIS, score_shift_jis(bytes)),
+            (Charset::CP1251, score_cp1251(bytes)),
+        ];
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let (charset, best) = scores[0];
+        if best < 0.5 {
+            return CharsetDetection::new(Charset::ISO_8859_1, 1.0 - best);
+        }
+        // a close runner-up lowers the confidence
+        let confidence = best * (1.0 - scores[1].1 / best / 2.0);
+        CharsetDetection::new(charset, confidence)
+    }
+
+    fn encoding(&self) -> &'static Encoding {
+        match self {
+            Charset::GBK => GBK,
+            Charset::Shift_JIS => SHIFT_JIS,
+            Charset::CP1251 => WINDOWS_1251,
+            _ => encoding_rs::UTF_8,
+        }
+    }
+}
+
+/// How text declared as ISO-8859-1 is decoded.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TextDecoding {
+    /// trust the encoding byte
+    #[default]
+    Latin1,
+    /// guess the legacy charset from all ISO-8859-1 text of the tag
+    Detect,
+    /// decode as the given charset
+    Force(Charset),
+}
+
+/// Result of `Charset::detect`.
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CharsetDetection {
+    pub charset: Charset,
+    /// 0.0 to 1.0
+    pub confidence: f32,
+}
+
+impl CharsetDetection {
+    pub fn new(charset: Charset, confidence: f32) -> Self {
+        CharsetDetection {
+            charset,
+            confidence,
+        }
+    }
+}
+
+impl Display for CharsetDetection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "
+CharsetDetection {{
+    charset: {:?},
+    confidence: {:.2}
+}}",
+            self.charset, self.confidence
+        )
+    }
+}
+
+/// Western text has isolated accented letters between ASCII letters.
+fn score_latin1(bytes: &[u8]) -> f32 {
+    let mut total = 0;
+    let mut good = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        if b < 0x80 {
+            continue;
+        }
+        total += 1;
+        let prev_ascii = i == 0 || bytes[i - 1] < 0x80;
+        let next_ascii = i + 1 == bytes.len() || bytes[i + 1] < 0x80;
+        if b >= 0xC0 && b != 0xD7 && b != 0xF7 && prev_ascii && next_ascii {
+            good += 1;
+        }
+    }
+    ratio(good, total)
+}
+
+/// Cyrillic words are runs of letters, capitalized at most at the start.
+fn score_cp1251(bytes: &[u8]) -> f32 {
+    let is_upper = |b: u8| (0xC0..=0xDF).contains(&b) || b == 0xA8;
+    let is_lower = |b: u8| b >= 0xE0 || b == 0xB8;
+    let mut total = 0;
+    let mut good = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        if b < 0x80 {
+            continue;
+        }
+        total += 1;
+        let prev = if i == 0 { b' ' } else { bytes[i - 1] };
+        let next = bytes.get(i + 1).copied().unwrap_or(b' ');
+        let in_word = is_upper(prev) || is_lower(prev) || is_upper(next) || is_lower(next);
+        if (is_lower(b) && !is_upper(next) && in_word)
+            || (is_upper(b) && (prev < 0x80 || is_upper(prev)))
+        {
+            good += 1;
+        }
+    }
+    ratio(good, total)
+}
+
+/// Chinese text mostly uses GB2312 punctuation and level-1 hanzi.
+fn score_gbk(bytes: &[u8]) -> f32 {
+    let mut total = 0;
+    let mut good = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b < 0x80 {
+            i += 1;
+            continue;
+        }
+        total += 1;
+        let Some(&t) = bytes.get(i + 1) else {
+            return 0.0;
+        };
+        if b == 0x80 || b == 0xFF || !(0x40..=0xFE).contains(&t) || t == 0x7F {
+            return 0.0;
+        }
+        if (0xA1..=0xFE).contains(&t) && ((0xA1..=0xA3).contains(&b) || (0xB0..=0xD7).contains(&b))
+        {
+            good += 1;
+        }
+        i += 2;
+    }
+    ratio(good, total)
+}
+
+/// Japanese text mostly uses kana, punctuation and level-1 kanji.
+fn score_shift_jis(bytes: &[u8]) -> f32 {
+    let mut total = 0;
+    let mut good = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b < 0x80 {
+            i += 1;
+            continue;
+        }
+        total += 1;
+        if (0xA1..=0xDF).contains(&b) {
+            // half-width katakana, rare in titles
+            i += 1;
+            continue;
+        }
+        let Some(&t) = bytes.get(i + 1) else {
+            return 0.0;
+        };
+        if !(0x81..=0x9F).contains(&b) && !(0xE0..=0xEF).contains(&b)
+            || !(0x40..=0xFC).contains(&t)
+            || t == 0x7F
+        {
+            return 0.0;
+        }
+        if (0x81..=0x83).contains(&b) || (0x88..=0x98).contains(&b) {
+            good += 1;
+        }
+        i += 2;
+    }
+    ratio(good, total)
+}
+
+fn ratio(good: u32, total: u32) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        good as f32 / total as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latin1_keeps_the_old_output() {
+        let bytes = b"\0\0caf\xE9\0bar\0\0";
+        assert_eq!(Charset::ISO_8859_1.decode(bytes), "caf\u{e9}\0bar");
+        assert_eq!(
+            Charset::ISO_8859_1.decode(bytes),
+            util::latin1_to_string(bytes)
+        );
+        assert_eq!(Charset::ISO_8859_1.decode(&[0; 30]), "");
+    }
+
+    #[test]
+    fn legacy_charsets() {
+        let cases: [(Charset, &[u8], &str); 4] = [
+            (Charset::GBK, b"\xD6\xD0\xCE\xC4", "\u{4e2d}\u{6587}"),
+            (Charset::Shift_JIS, b"\x93\xFA\x96\x7B", "\u{65e5}\u{672c}"),
+            (
+                Charset::CP1251,
+                b"\xCF\xF0\xE8\xE2\xE5\xF2",
+                "\u{41f}\u{440}\u{438}\u{432}\u{435}\u{442}",
+            ),
+            (Charset::UTF8, b"\xC3\xA9", "\u{e9}"),
+        ];
+        for (charset, bytes, text) in cases {
+            assert_eq!(charset.decode(bytes), text, "{charset:?}");
+            assert_eq!(charset.encode(text, 30), bytes, "{charset:?}");
+            let mut padded = bytes.to_vec();
+            padded.resize(30, 0);
+            assert_eq!(charset.decode(&padded), text, "{charset:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_sequences_are_replaced() {
+        assert_eq!(Charset::UTF8.decode(b"a\xFFb"), "a\u{fffd}b");
+        assert_eq!(Charset::GBK.decode(b"a\x81"), "a\u{fffd}");
+    }
+
+    #[test]
+    fn encode_keeps_whole_characters() {
+        assert_eq!(Charset::GBK.encode("\u{4e2d}\u{6587}", 3), b"\xD6\xD0");
+        assert_eq!(Charset::ISO_8859_1.encode("\u{4e2d}a", 30), b"?a");
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(Charset::detect(b"plain").charset, Charset::ISO_8859_1);
+        assert_eq!(
+            Charset::detect("caf\u{e9}".as_bytes()).charset,
+            Charset::UTF8
+        );
+        let gbk = Charset::GBK.encode("\u{6211}\u{7684}\u{4e2d}\u{6587}\u{6b4c}\u{66f2}", 30);
+        assert_eq!(Charset::detect(&gbk).charset, Charset::GBK);
+        let cp1251 = Charset::CP1251.encode(
+            "\u{41f}\u{440}\u{438}\u{432}\u{435}\u{442} \u{43c}\u{438}\u{440}",
+            30,
+        );
+        assert_eq!(Charset::detect(&cp1251).charset, Charset::CP1251);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::VecDeque;
 
 use crate::{
-    id3::{ frames::common::Encoding, version::Version},
+    id3::{charset::Charset, frames::common::Encoding, version::Version},
     util,
 };
 
@@ -9,14 +9,14 @@ use super::error::ID3Error;
 
 // use super::version::Version;
 
-pub fn get_text(encoding: &Encoding, payload: &[u8]) -> Result<String, ID3Error> {
+/// `latin1` is the charset actually used by text declared as ISO-8859-1.
+pub fn get_text(encoding: &Encoding, payload: &[u8], latin1: &Charset) -> Result<String, ID3Error> {
     let text = match encoding {
-        Encoding::ISO_8859_1 => util::latin1_to_string(payload),
-        Encoding::UTF16_BE => {
-            String::from_utf16(&util::into_big_endian_u16(payload, false)?).expect("")
-        }
+        Encoding::ISO_8859_1 => latin1.decode(payload),
+        Encoding::UTF16_BE => String::from_utf16(&util::into_big_endian_u16(payload, false)?)
+            .map_err(invalid_utf16)?,
         Encoding::UTF16_LE => {
-            String::from_utf16(&util::into_big_endian_u16(payload, true)?).expect("")
+            String::from_utf16(&util::into_big_endian_u16(payload, true)?).map_err(invalid_utf16)?
         }
         Encoding::UTF8 => {
             // 掐头去尾，防止出现0x00
@@ -27,7 +27,7 @@ pub fn get_text(encoding: &Encoding, payload: &[u8]) -> Result<String, ID3Error>
             while let Some(0x00) = vq.back() {
                 vq.pop_back();
             }
-            String::from_utf8(vq.into()).expect("")
+            String::from_utf8(vq.into()).map_err(invalid_utf8)?
         }
         _ => "".to_string(),
     };
@@ -41,74 +41,103 @@ pub fn get_encoding(payload: u8) -> Result<Encoding, ID3Error> {
         0x02 => Encoding::UTF16_BE,
         0x03 => Encoding::UTF8,
         _ => {
-            return Err(ID3Error::UnknownError(
-                "Out-of-bounds indexing".to_string(),
-            ))
+            return Err(ID3Error::InvalidFrame(format!(
+                "unknown text encoding {payload}"
+            )))
         }
     };
     Ok(encoding)
 }
 
+/// Read a string up to its terminator.
+///
+/// Return the text and the number of bytes read, including the terminator if any.
 pub fn get_text_according_to_encoding(
     payload: &[u8],
     encoding: &Encoding,
+    latin1: &Charset,
 ) -> Result<(String, usize), ID3Error> {
     let mut cursor: usize = 0;
     let mut text_vec: Vec<u8> = Vec::new();
-    let mut text: String;
+    let text: String;
     match encoding {
         Encoding::ISO_8859_1 => {
             while cursor < payload.len() && payload[cursor] != 0 {
                 text_vec.push(payload[cursor]);
                 cursor += 1;
             }
-            text = util::latin1_to_string(&text_vec);
-            if text.is_empty() {
-                text = "null".to_string();
-            }
-            Ok((text, cursor + 1))
+            text = latin1.decode(&text_vec);
+            Ok((text, (cursor + 1).min(payload.len())))
         }
         Encoding::UTF16_LE => {
-            while cursor < payload.len() && payload[cursor] != 0 && payload[cursor + 1] != 0 {
+            // a UTF-16 terminator is two 0x00 aligned to a code unit
+            while cursor + 1 < payload.len() && (payload[cursor] != 0 || payload[cursor + 1] != 0) {
                 text_vec.push(payload[cursor]);
-                cursor += 1;
-            }
-            text = String::from_utf16(&util::into_big_endian_u16(&text_vec, true)?).expect("");
-            if text.is_empty() {
-                text = "null".to_string();
+                text_vec.push(payload[cursor + 1]);
+                cursor += 2;
             }
-            Ok((text, cursor + 2))
+            text = String::from_utf16(&util::into_big_endian_u16(&text_vec, true)?)
+                .map_err(invalid_utf16)?;
+            Ok((text, (cursor + 2).min(payload.len())))
         }
         Encoding::UTF16_BE => {
-            while cursor < payload.len() && payload[cursor] != 0 && payload[cursor + 1] != 0 {
+            // a UTF-16 terminator is two 0x00 aligned to a code unit
+            while cursor + 1 < payload.len() && (payload[cursor] != 0 || payload[cursor + 1] != 0) {
                 text_vec.push(payload[cursor]);
-                cursor += 1;
-            }
-            text = String::from_utf16(&util::into_big_endian_u16(&text_vec, false)?).expect("");
-            if text.is_empty() {
-                text = "null".to_string();
+                text_vec.push(payload[cursor + 1]);
+                cursor += 2;
             }
-            Ok((text, cursor + 2))
+            text = String::from_utf16(&util::into_big_endian_u16(&text_vec, false)?)
+                .map_err(invalid_utf16)?;
+            Ok((text, (cursor + 2).min(payload.len())))
         }
         Encoding::UTF8 => {
             while cursor < payload.len() && payload[cursor] != 0 {
                 text_vec.push(payload[cursor]);
                 cursor += 1;
             }
-            text = String::from_utf8(text_vec).expect("");
-            if text.is_empty() {
-                text = "null".to_string();
-            }
-            Ok((text, cursor + 1))
+            text = String::from_utf8(text_vec).map_err(invalid_utf8)?;
+            Ok((text, (cursor + 1).min(payload.len())))
         }
-        _ => Err(ID3Error::UnknownError(
+        _ => Err(ID3Error::InvalidFrame(
             "UTF16_WITH_BOM is not allowed".to_string(),
         )),
     }
 }
 
+/// Whether the `length` bytes read by `get_text_according_to_encoding` end with the terminator.
+pub fn is_terminated(payload: &[u8], length: usize, encoding: &Encoding) -> bool {
+    let width = match encoding {
+        Encoding::UTF16_LE | Encoding::UTF16_BE => 2,
+        _ => 1,
+    };
+    length >= width
+        && length.is_multiple_of(width)
+        && payload[length - width..length].iter().all(|v| *v == 0)
+}
+
+/// Like `get_text_according_to_encoding`, but reads the BOM first if `encoding` is UTF16_WITH_BOM.
+///
+/// The returned length includes the BOM.
+pub fn get_text_with_bom(
+    payload: &[u8],
+    encoding: &Encoding,
+    latin1: &Charset,
+) -> Result<(String, usize), ID3Error> {
+    if let Encoding::UTF16_WITH_BOM = encoding {
+        if payload.len() < 2 {
+            return Err(ID3Error::Truncated("BOM".to_string()));
+        }
+        let (text, skip) =
+            get_text_according_to_encoding(&payload[2..], &refine_encoding(payload), latin1)?;
+        Ok((text, skip + 2))
+    } else {
+        get_text_according_to_encoding(payload, encoding, latin1)
+    }
+}
+
 pub fn refine_encoding(payload: &[u8]) -> Encoding {
-    if payload[0] == 0xFF && payload[1] == 0xFE {
+    if payload.starts_with(&[0xFF, 0xFE]) {
         Encoding::UTF16_LE
     } else {
         Encoding::UTF16_BE
@@ -132,3 +161,78 @@ pub fn get_size(size: Vec<u8>, v: &Version) -> u32 {
         Version::Default => 0,
     }
 }
+
+fn invalid_utf8(_: std::string::FromUtf8Error) -> ID3Error {
+    ID3Error::InvalidEncoding("UTF-8".to_string())
+}
+
+fn invalid_utf16(_: std::string::FromUtf16Error) -> ID3Error {
+    ID3Error::InvalidEncoding("UTF-16".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_terminator() {
+        let latin1 = Charset::default();
+        // "Aā" then the terminator, both characters have a 0x00 byte
+        let payload = [0x41, 0x00, 0x01, 0x01, 0x00, 0x00, 0x42, 0x00];
+        let (text, length) =
+            get_text_according_to_encoding(&payload, &Encoding::UTF16_LE, &latin1).unwrap();
+        assert_eq!((text.as_str(), length), ("Aā", 6));
+        let payload = [0x00, 0x41, 0x01, 0x00, 0x00, 0x00, 0x00, 0x42];
+        let (text, length) =
+            get_text_according_to_encoding(&payload, &Encoding::UTF16_BE, &latin1).unwrap();
+        assert_eq!((text.as_str(), length), ("AĀ", 6));
+    }
+
+    #[test]
+    fn utf16_without_terminator() {
+        let latin1 = Charset::default();
+        // an odd length leaves a lone byte
+        let (text, length) =
+            get_text_according_to_encoding(&[0x41, 0x00, 0x42], &Encoding::UTF16_LE, &latin1)
+                .unwrap();
+        assert_eq!((text.as_str(), length), ("A", 3));
+        let (text, length) =
+            get_text_according_to_encoding(&[0x00, 0x42], &Encoding::UTF16_BE, &latin1).unwrap();
+        assert_eq!((text.as_str(), length), ("B", 2));
+        let (text, length) =
+            get_text_according_to_encoding(&[], &Encoding::UTF16_BE, &latin1).unwrap();
+        assert_eq!((text.as_str(), length), ("", 0));
+    }
+
+    #[test]
+    fn terminated_or_not() {
+        assert!(is_terminated(b"AB\0", 3, &Encoding::ISO_8859_1));
+        assert!(!is_terminated(b"AB", 2, &Encoding::ISO_8859_1));
+        assert!(!is_terminated(b"", 0, &Encoding::UTF8));
+        assert!(is_terminated(&[0x41, 0x00, 0x00, 0x00], 4, &Encoding::UTF16_LE));
+        assert!(!is_terminated(&[0x41, 0x00, 0x00], 3, &Encoding::UTF16_LE));
+        assert!(!is_terminated(&[0x00, 0x41], 2, &Encoding::UTF16_BE));
+    }
+
+    #[test]
+    fn text_without_terminator() {
+        let latin1 = Charset::default();
+        let (text, length) =
+            get_text_according_to_encoding(b"AB", &Encoding::ISO_8859_1, &latin1).unwrap();
+        assert_eq!((text.as_str(), length), ("AB", 2));
+        let (text, length) =
+            get_text_according_to_encoding(b"AB", &Encoding::UTF8, &latin1).unwrap();
+        assert_eq!((text.as_str(), length), ("AB", 2));
+    }
+
+    #[test]
+    fn text_with_bom() {
+        let latin1 = Charset::default();
+        let payload = [0xFF, 0xFE, 0x41, 0x00, 0x00, 0x00, 0x42, 0x00];
+        let (text, length) =
+            get_text_with_bom(&payload, &Encoding::UTF16_WITH_BOM, &latin1).unwrap();
+        assert_eq!((text.as_str(), length), ("A", 6));
+        let (text, length) = get_text_with_bom(b"AB\0C", &Encoding::ISO_8859_1, &latin1).unwrap();
+        assert_eq!((text.as_str(), length), ("AB", 3));
+    }
+}
@@ -1,7 +1,13 @@
+use crate::picture::PictureLocation;
 use crate::util::{self, Buffer};
 
 use super::{
-    common, extended_header::ExtendedHeader, footer::Footer, frames::{
+    charset::Charset,
+    common,
+    error::ID3Error,
+    extended_header::ExtendedHeader,
+    footer::Footer,
+    frames::{
         common::{Encoding, Tape},
         header::FrameHeader,
         identifiers::{
@@ -13,11 +19,14 @@ use super::{
         url_link_frame::URLLinkFrame,
         APIC::{PicType, APIC},
         COMM::COMM,
-        SYLT::SYLT,
+        SYLT::{SyncedText, SYLT},
         TXXX::TXXX,
         USLT::USLT,
         WXXX::WXXX,
-    }, error::ID3Error, protocol_header::{Flag, ProtocolHeader}, version::Version
+    },
+    lyrics3::Lyrics3,
+    protocol_header::{Flag, ProtocolHeader},
+    version::Version,
 };
 
 pub fn parse_protocol_header(header: &Buffer) -> Result<ProtocolHeader, ID3Error> {
@@ -32,8 +41,10 @@ pub fn parse_protocol_header(header: &Buffer) -> Result<ProtocolHeader, ID3Error
                 || header[0..=3] == [0x33, 0x44, 0x49, 0x04]
             {
                 Version::V4
+            } else if header[0..=2] == [0x49, 0x44, 0x33] || header[0..=2] == [0x33, 0x44, 0x49] {
+                return Err(ID3Error::UnsupportedVersion(header[3]));
             } else {
-                return Err(ID3Error::Unimplement("Wrong Header".to_string(), 1));
+                return Err(ID3Error::WrongHeader);
             }
         },
         revision: header[4],
@@ -73,139 +84,161 @@ pub fn parse_frame_header(header: &Buffer, version: &Version) -> Result<FrameHea
 pub fn parse_frame_payload(
     payload: &Buffer,
     header: &FrameHeader,
+    latin1: &Charset,
 ) -> Result<Box<dyn Tape>, ID3Error> {
+    // encoding byte, then 3-byte language for COMM and USLT, and 2 more bytes for SYLT
+    let min_length = match &header.identifier {
+        IDFactory::COMM | IDFactory::USLT => 4,
+        IDFactory::SYLT => 6,
+        IDFactory::R(_) | IDFactory::PADDING => 0,
+        _ => 1,
+    };
+    if payload.len() < min_length {
+        return Err(ID3Error::Truncated("payload".to_string()));
+    }
     match &header.identifier {
         IDFactory::T(id) => {
             if let TextInformationFrameIdentifier::TXXX = id {
-                let txxx = parse_TXXX(payload.clone())?;
+                let txxx = parse_TXXX(payload.clone(), latin1)?;
                 Ok(Box::new(txxx))
             } else {
-                let text_infomation_frame =
-                    parse_text_infomation_frame(header.identifier.to_string(), payload.clone())?;
+                let text_infomation_frame = parse_text_infomation_frame(
+                    header.identifier.to_string(),
+                    payload.clone(),
+                    latin1,
+                )?;
                 Ok(Box::new(text_infomation_frame))
             }
         }
         IDFactory::W(id) => {
             if let URLLinkFrameIdentifier::WXXX = id {
-                let wxxx = parse_WXXX(payload.clone())?;
+                let wxxx = parse_WXXX(payload.clone(), latin1)?;
                 Ok(Box::new(wxxx))
             } else {
                 let url_link_frame =
-                    parse_url_link_frame(header.identifier.to_string(), payload.clone())?;
+                    parse_url_link_frame(header.identifier.to_string(), payload.clone(), latin1)?;
                 Ok(Box::new(url_link_frame))
             }
         }
         IDFactory::APIC => {
-            let apic = parse_APIC(payload.clone())?;
+            let apic = parse_APIC(payload.clone(), latin1)?;
             Ok(Box::new(apic))
         }
         IDFactory::COMM => {
-            let comm = parse_COMM(payload.clone())?;
+            let comm = parse_COMM(payload.clone(), latin1)?;
             Ok(Box::new(comm))
         }
         IDFactory::USLT => {
-            let uslt = parse_USLT(payload.clone())?;
+            let uslt = parse_USLT(payload.clone(), latin1)?;
             Ok(Box::new(uslt))
         }
         IDFactory::SYLT => {
-            let sylt = parse_SYLT(payload.clone())?;
+            let sylt = parse_SYLT(payload.clone(), latin1)?;
             Ok(Box::new(sylt))
         }
         IDFactory::R(_) => {
             let rarely_used = parse_RarelyUsed(header.identifier.to_string(), payload.clone())?;
             Ok(Box::new(rarely_used))
         }
-        _ => {
-            panic!("Unexpected Error")
-        }
+        IDFactory::PADDING => Err(ID3Error::IsPadding),
     }
 }
 
+/// The payload from `cursor`, or nothing if `cursor` is beyond the end.
+fn rest(payload: &[u8], cursor: usize) -> &[u8] {
+    payload.get(cursor..).unwrap_or_default()
+}
+
 #[allow(non_snake_case)]
 fn parse_text_infomation_frame(
     identifier: String,
     payload: Buffer,
+    latin1: &Charset,
 ) -> Result<TextInfomationFrame, ID3Error> {
     let mut encoding = common::get_encoding(payload[0])?;
     let mut cursor: usize = 1;
     if let Encoding::UTF16_WITH_BOM = encoding {
-        encoding = common::refine_encoding(&payload[1..=2]);
+        encoding = common::refine_encoding(rest(&payload, 1));
         cursor += 2;
     }
-    let data = common::get_text(&encoding, &payload[cursor..])?;
+    let data = common::get_text(&encoding, rest(&payload, cursor), latin1)?;
     Ok(TextInfomationFrame::new(identifier, data))
 }
 
 fn parse_url_link_frame(
     identifier: String,
     payload: Buffer,
+    latin1: &Charset,
 ) -> Result<URLLinkFrame, ID3Error> {
-    let data = common::get_text(&Encoding::UTF8, &payload[..])?;
+    let data = common::get_text(&Encoding::UTF8, &payload[..], latin1)?;
     Ok(URLLinkFrame::new(identifier, data))
 }
 
 #[allow(non_snake_case)]
-fn parse_TXXX(payload: Buffer) -> Result<TXXX, ID3Error> {
+fn parse_TXXX(payload: Buffer, latin1: &Charset) -> Result<TXXX, ID3Error> {
     let mut encoding = common::get_encoding(payload[0])?;
     let mut cursor = 1;
     if let Encoding::UTF16_WITH_BOM = encoding {
-        encoding = common::refine_encoding(&payload[cursor..=cursor + 1]);
+        encoding = common::refine_encoding(rest(&payload, cursor));
         cursor += 2;
     }
     let (description, skip) =
-        common::get_text_according_to_encoding(&payload[cursor..], &encoding)?;
+        common::get_text_according_to_encoding(rest(&payload, cursor), &encoding, latin1)?;
     cursor += skip;
-    let data = common::get_text(&Encoding::UTF8, &payload[cursor..])?;
+    let data = common::get_text(&Encoding::UTF8, rest(&payload, cursor), latin1)?;
     Ok(TXXX::new(encoding, description, data))
 }
 
 #[allow(non_snake_case)]
-fn parse_WXXX(payload: Buffer) -> Result<WXXX, ID3Error> {
+fn parse_WXXX(payload: Buffer, latin1: &Charset) -> Result<WXXX, ID3Error> {
     let mut encoding = common::get_encoding(payload[0])?;
     let mut cursor = 1;
     if let Encoding::UTF16_WITH_BOM = encoding {
-        encoding = common::refine_encoding(&payload[cursor..=cursor + 1]);
+        encoding = common::refine_encoding(rest(&payload, cursor));
         cursor += 2;
     }
     let (description, skip) =
-        common::get_text_according_to_encoding(&payload[cursor..], &encoding)?;
+        common::get_text_according_to_encoding(rest(&payload, cursor), &encoding, latin1)?;
     cursor += skip;
-    let data = common::get_text(&Encoding::UTF8, &payload[cursor..])?;
+    let data = common::get_text(&Encoding::UTF8, rest(&payload, cursor), latin1)?;
     Ok(WXXX::new(encoding, description, data))
 }
 
 #[allow(non_snake_case)]
-fn parse_USLT(payload: Buffer) -> Result<USLT, ID3Error> {
+fn parse_USLT(payload: Buffer, latin1: &Charset) -> Result<USLT, ID3Error> {
     let frame_encoding = common::get_encoding(payload[0])?;
     let mut data_encoding = common::get_encoding(payload[0])?;
-    let language: String = String::from_utf8(payload[1..=3].into()).expect("");
+    let language: String = String::from_utf8(payload[1..=3].into())
+        .map_err(|_| ID3Error::InvalidEncoding("language".to_string()))?;
     let mut cursor: usize = 4;
     if let Encoding::UTF16_WITH_BOM = frame_encoding {
-        data_encoding = common::refine_encoding(&payload[cursor..=cursor + 1]);
+        data_encoding = common::refine_encoding(rest(&payload, cursor));
         cursor += 2;
     }
     let (description, skip): (String, usize) =
-        common::get_text_according_to_encoding(&payload[cursor..], &data_encoding)?;
+        common::get_text_according_to_encoding(rest(&payload, cursor), &data_encoding, latin1)?;
     cursor += skip;
     if let Encoding::UTF16_WITH_BOM = frame_encoding {
-        data_encoding = common::refine_encoding(&payload[cursor..=cursor + 1]);
+        data_encoding = common::refine_encoding(rest(&payload, cursor));
         cursor += 2;
     }
-    let data = common::get_text(&data_encoding, &payload[cursor..])?;
+    let data = common::get_text(&data_encoding, rest(&payload, cursor), latin1)?;
     Ok(USLT::new(data_encoding, language, description, data))
 }
 
 #[allow(non_snake_case)]
-fn parse_SYLT(payload: Buffer) -> Result<SYLT, ID3Error> {
+fn parse_SYLT(payload: Buffer, latin1: &Charset) -> Result<SYLT, ID3Error> {
     let frame_encoding = common::get_encoding(payload[0])?;
-    let language: String = String::from_utf8(payload[1..=3].into()).expect("");
+    let language: String = String::from_utf8(payload[1..=3].into())
+        .map_err(|_| ID3Error::InvalidEncoding("language".to_string()))?;
     let timestamp_format: u8 = payload[4];
     let ctype: u8 = payload[5];
     let mut cursor: usize = 6;
     let (description, skip) =
-        common::get_text_according_to_encoding(&payload[cursor..], &frame_encoding)?;
+        common::get_text_with_bom(rest(&payload, cursor), &frame_encoding, latin1)?;
     cursor += skip;
-    let data: Vec<u8> = payload[cursor..].into();
+    let data: Vec<u8> = rest(&payload, cursor).into();
+    let synced_text = parse_synced_text(&data, &frame_encoding, latin1)?;
     Ok(SYLT::new(
         frame_encoding,
         language,
@@ -213,59 +246,247 @@ fn parse_SYLT(payload: Buffer) -> Result<SYLT, ID3Error> {
         ctype,
         description,
         data,
+        synced_text,
     ))
 }
 
+/// Each entry is a terminated string followed by a 4-byte timestamp.
+fn parse_synced_text(
+    data: &[u8],
+    encoding: &Encoding,
+    latin1: &Charset,
+) -> Result<Vec<SyncedText>, ID3Error> {
+    let (bom, terminator): (usize, usize) = match encoding {
+        Encoding::UTF16_WITH_BOM => (2, 2),
+        Encoding::UTF16_BE | Encoding::UTF16_LE => (0, 2),
+        _ => (0, 1),
+    };
+    let mut synced_text: Vec<SyncedText> = Vec::new();
+    let mut cursor: usize = 0;
+    while cursor + bom + terminator + 4 <= data.len() {
+        let (text, skip) = common::get_text_with_bom(&data[cursor..], encoding, latin1)?;
+        cursor += skip;
+        if cursor + 4 > data.len() {
+            break;
+        }
+        let timestamp = util::parse_4_bytes_with_big_endian(&data[cursor..cursor + 4]);
+        cursor += 4;
+        synced_text.push(SyncedText::new(timestamp, text));
+    }
+    Ok(synced_text)
+}
+
 #[allow(non_snake_case)]
-fn parse_COMM(payload: Buffer) -> Result<COMM, ID3Error> {
+fn parse_COMM(payload: Buffer, latin1: &Charset) -> Result<COMM, ID3Error> {
     let frame_encoding = common::get_encoding(payload[0])?;
     let mut data_encoding = common::get_encoding(payload[0])?;
-    let language: String = String::from_utf8(payload[1..=3].into()).expect("");
+    let language: String = String::from_utf8(payload[1..=3].into())
+        .map_err(|_| ID3Error::InvalidEncoding("language".to_string()))?;
     let mut cursor: usize = 4;
     if let Encoding::UTF16_WITH_BOM = frame_encoding {
-        data_encoding = common::refine_encoding(&payload[cursor..=cursor + 1]);
+        data_encoding = common::refine_encoding(rest(&payload, cursor));
         cursor += 2;
     }
     let (description, skip): (String, usize) =
-        common::get_text_according_to_encoding(&payload[cursor..], &data_encoding)?;
+        common::get_text_according_to_encoding(rest(&payload, cursor), &data_encoding, latin1)?;
     cursor += skip;
     if let Encoding::UTF16_WITH_BOM = frame_encoding {
-        data_encoding = common::refine_encoding(&payload[cursor..=cursor + 1]);
+        data_encoding = common::refine_encoding(rest(&payload, cursor));
         cursor += 2;
     }
-    let data = common::get_text(&data_encoding, &payload[cursor..])?;
+    let data = common::get_text(&data_encoding, rest(&payload, cursor), latin1)?;
     Ok(COMM::new(data_encoding, language, description, data))
 }
 
 #[allow(non_snake_case)]
-fn parse_APIC(payload: Buffer) -> Result<APIC, ID3Error> {
-    let mut encoding = common::get_encoding(payload[0])?;
+fn parse_APIC(payload: Buffer, latin1: &Charset) -> Result<APIC, ID3Error> {
+    let (encoding, MIME_type, raw_pic_type, description, cursor) =
+        parse_APIC_head(&payload, latin1)?;
+    let mut data: Vec<u8> = rest(&payload, cursor).into();
+    data.push(raw_pic_type);
+    Ok(APIC::new(
+        encoding,
+        MIME_type,
+        PicType::from(raw_pic_type),
+        description,
+        data,
+        None,
+    ))
+}
+
+/// Parse an APIC frame of `size` bytes whose payload starts at `payload_offset` of the file,
+///
+/// without its image bytes, `payload` may hold only the first bytes of the payload.
+#[allow(non_snake_case)]
+pub fn parse_lazy_APIC(
+    payload: &Buffer,
+    latin1: &Charset,
+    payload_offset: u64,
+    size: u32,
+) -> Result<APIC, ID3Error> {
+    let (encoding, MIME_type, raw_pic_type, description, cursor) =
+        parse_APIC_head(payload, latin1)?;
+    if cursor > size as usize {
+        return Err(ID3Error::Truncated("description".to_string()));
+    }
+    let location = PictureLocation::new(
+        payload_offset + cursor as u64,
+        (size as usize - cursor) as u64,
+    );
+    Ok(APIC::new(
+        encoding,
+        MIME_type,
+        PicType::from(raw_pic_type),
+        description,
+        vec![raw_pic_type],
+        Some(location),
+    ))
+}
+
+/// Return the length of the APIC fields before the image bytes,
+///
+/// or None if `payload` is too short to hold them.
+#[allow(non_snake_case)]
+pub fn APIC_head_length(payload: &Buffer) -> Option<usize> {
+    match parse_APIC_head(payload, &Charset::ISO_8859_1) {
+        Ok((.., cursor)) if cursor <= payload.len() => Some(cursor),
+        _ => None,
+    }
+}
+
+/// Parse the fields of an APIC frame before the image bytes,
+///
+/// returning them and the position of the image bytes in `payload`.
+#[allow(non_snake_case)]
+fn parse_APIC_head(
+    payload: &Buffer,
+    latin1: &Charset,
+) -> Result<(Encoding, String, u8, String, usize), ID3Error> {
+    let raw_encoding = *payload
+        .first()
+        .ok_or_else(|| ID3Error::Truncated("text encoding".to_string()))?;
+    let mut encoding = common::get_encoding(raw_encoding)?;
     let mut cursor: usize = 1;
     let (MIME_type, skip): (String, usize) =
-        common::get_text_according_to_encoding(&payload[cursor..], &Encoding::UTF8)?;
+        common::get_text_according_to_encoding(rest(payload, cursor), &Encoding::UTF8, latin1)?;
     cursor += skip;
-    let raw_pic_type = payload[cursor];
-    let picture_type: PicType = PicType::from(payload[cursor]);
+    let raw_pic_type = *payload
+        .get(cursor)
+        .ok_or_else(|| ID3Error::Truncated("picture type".to_string()))?;
     cursor += 1;
     if let Encoding::UTF16_WITH_BOM = encoding {
-        encoding = common::refine_encoding(&payload[cursor..=cursor + 1]);
+        encoding = common::refine_encoding(rest(payload, cursor));
         cursor += 2;
     }
     let (description, skip): (String, usize) =
-        common::get_text_according_to_encoding(&payload[cursor..], &encoding)?;
+        common::get_text_according_to_encoding(rest(payload, cursor), &encoding, latin1)?;
+    // the image bytes follow the terminator, which lazy parsing may not have read yet
+    if !common::is_terminated(rest(payload, cursor), skip, &encoding) {
+        return Err(ID3Error::Truncated("description".to_string()));
+    }
     cursor += skip;
-    let mut data: Vec<u8> = payload[cursor..].into();
-    data.push(raw_pic_type);
-    Ok(APIC::new(
-        encoding,
-        MIME_type,
-        picture_type,
-        description,
-        data,
-    ))
+    Ok((encoding, MIME_type, raw_pic_type, description, cursor))
 }
 
 #[allow(non_snake_case)]
 fn parse_RarelyUsed(identifier: String, payload: Buffer) -> Result<RarelyUsed, ID3Error> {
     Ok(RarelyUsed::new(identifier, payload))
 }
+
+/// Parse the fields of a Lyrics3v2 block, `buffer` starts after "LYRICSBEGIN" and ends before the size.
+///
+/// Each field is a 3-byte identifier, a 5-digit size and the data.
+pub fn parse_lyrics3v2_fields(buffer: &[u8], lyrics3: &mut Lyrics3) -> Result<(), ID3Error> {
+    let mut cursor: usize = 0;
+    while cursor + 8 <= buffer.len() {
+        let id = util::latin1_to_string(&buffer[cursor..cursor + 3]);
+        let size = parse_decimal(&buffer[cursor + 3..cursor + 8]).ok_or_else(|| {
+            ID3Error::InvalidFrame(format!("Invalid Lyrics3v2 field size of {id}"))
+        })? as usize;
+        cursor += 8;
+        if cursor + size > buffer.len() {
+            return Err(ID3Error::InvalidFrame(format!(
+                "Lyrics3v2 field {id} exceeds the block"
+            )));
+        }
+        lyrics3.set_field(&id, util::latin1_to_string(&buffer[cursor..cursor + size]));
+        cursor += size;
+    }
+    lyrics3.synced_lyrics = parse_lyrics3_timestamps(&lyrics3.lyrics);
+    Ok(())
+}
+
+/// Convert lines like `[00:12][01:30]text` into synced text in milliseconds.
+///
+/// Lines without timestamps are skipped.
+pub fn parse_lyrics3_timestamps(lyrics: &str) -> Vec<SyncedText> {
+    let mut synced_text: Vec<SyncedText> = Vec::new();
+    for line in lyrics.lines() {
+        let mut rest = line;
+        let mut timestamps: Vec<u32> = Vec::new();
+        while let Some(stamp) = rest.strip_prefix('[').and_then(|r| r.get(..6)) {
+            let stamp = stamp.as_bytes();
+            if stamp[2] != b':' || stamp[5] != b']' {
+                break;
+            }
+            let (Some(mm), Some(ss)) = (parse_decimal(&stamp[0..2]), parse_decimal(&stamp[3..5]))
+            else {
+                break;
+            };
+            timestamps.push((mm * 60 + ss) * 1000);
+            rest = &rest[7..];
+        }
+        for timestamp in timestamps {
+            synced_text.push(SyncedText::new(timestamp, rest.to_string()));
+        }
+    }
+    synced_text.sort_by_key(|t| t.timestamp);
+    synced_text
+}
+
+/// Parse ASCII digits, e.g. the sizes in Lyrics3v2.
+pub fn parse_decimal(buffer: &[u8]) -> Option<u32> {
+    std::str::from_utf8(buffer).ok()?.parse::<u32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synced_text() {
+        let mut data = b"One\0".to_vec();
+        data.extend_from_slice(&1000u32.to_be_bytes());
+        data.extend_from_slice(b"\0");
+        data.extend_from_slice(&2500u32.to_be_bytes());
+        // a string without its timestamp is dropped
+        data.extend_from_slice(b"Two\0");
+        let lines = parse_synced_text(&data, &Encoding::ISO_8859_1, &Charset::default()).unwrap();
+        let lines: Vec<(u32, &str)> = lines
+            .iter()
+            .map(|v| (v.timestamp, v.text.as_str()))
+            .collect();
+        assert_eq!(lines, [(1000, "One"), (2500, "")]);
+    }
+
+    #[test]
+    fn apic_head_cut_in_the_description() {
+        let mut payload = b"\0image/png\0\x03cover\0".to_vec();
+        assert_eq!(APIC_head_length(&payload), Some(payload.len()));
+        payload.extend_from_slice(b"\x89PNG");
+        assert_eq!(APIC_head_length(&payload), Some(payload.len() - 4));
+        assert_eq!(APIC_head_length(&payload[..15].to_vec()), None);
+        // UTF-16 cut after the first byte of the terminator
+        let payload = b"\x01image/png\0\x03\xFF\xFEA\0\0";
+        assert_eq!(APIC_head_length(&payload.to_vec()), None);
+    }
+
+    #[test]
+    fn synced_text_with_bom() {
+        let mut data = vec![0xFF, 0xFE, 0x41, 0x00, 0x00, 0x00];
+        data.extend_from_slice(&40u32.to_be_bytes());
+        let lines =
+            parse_synced_text(&data, &Encoding::UTF16_WITH_BOM, &Charset::default()).unwrap();
+        assert_eq!((lines[0].timestamp, lines[0].text.as_str()), (40, "A"));
+    }
+}
@@ -1,6 +1,51 @@
+use crate::error::Error;
+
 #[derive(Debug)]
 pub enum ID3Error {
     Unimplement(String, u32),
     IsPadding,
-    UnknownError(String),
+    /// not include "ID3"
+    WrongHeader,
+    /// major version other than 3 and 4
+    UnsupportedVersion(u8),
+    Truncated(String),
+    InvalidEncoding(String),
+    InvalidFrame(String),
+}
+
+impl ID3Error {
+    /// Convert into the public error, `context` is usually the frame identifier.
+    pub fn at(self, offset: u64, context: &str) -> Error {
+        let context = context.to_string();
+        match self {
+            ID3Error::WrongHeader => Error::NotThisFormat { expected: "ID3v2" },
+            ID3Error::UnsupportedVersion(v) => Error::UnsupportedVersion {
+                offset,
+                version: format!("ID3v2.{v}"),
+            },
+            ID3Error::Truncated(field) => Error::Truncated {
+                offset,
+                context: format!("{context} {field}"),
+            },
+            ID3Error::InvalidEncoding(field) => Error::InvalidEncoding {
+                offset,
+                context: format!("{context} {field}"),
+            },
+            ID3Error::InvalidFrame(reason) => Error::InvalidFrame {
+                offset,
+                context,
+                reason,
+            },
+            ID3Error::Unimplement(id, _) => Error::InvalidFrame {
+                offset,
+                context,
+                reason: format!("{id} is not implemented"),
+            },
+            ID3Error::IsPadding => Error::InvalidFrame {
+                offset,
+                context,
+                reason: "unexpected padding".to_string(),
+            },
+        }
+    }
 }
@@ -4,6 +4,8 @@ use super::version::Version;
 
 #[derive(Debug)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExtendedHeader {
     pub ver: Version,
     pub len: u8,
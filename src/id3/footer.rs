@@ -7,6 +7,8 @@ use super::{
 
 #[derive(Debug)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Footer {
     pub identifier: String,
     pub major_version: Version,
@@ -1,6 +1,11 @@
 use std::{fmt::Display, fs, io, path::Path};
 
-use super::common::{Encoding, Tape};
+use crate::picture::PictureLocation;
+
+use super::{
+    common::{or_null, Encoding, Tape},
+    frame::{optional, Frame},
+};
 
 #[allow(non_snake_case)]
 #[allow(dead_code)]
@@ -13,6 +18,8 @@ pub struct APIC {
     description: String,
     // the last byte is picrture type
     data: Vec<u8>,
+    /// only with `PictureLoading::Lazy`, `data` then holds the picture type only
+    location: Option<PictureLocation>,
 }
 
 impl APIC {
@@ -23,6 +30,7 @@ impl APIC {
         picture_type: PicType,
         description: String,
         data: Vec<u8>,
+        location: Option<PictureLocation>,
     ) -> Self {
         APIC {
             identifier: "APIC".to_string(),
@@ -31,6 +39,7 @@ impl APIC {
             picture_type,
             description,
             data,
+            location,
         }
     }
 
@@ -56,12 +65,13 @@ APIC {{
             self.MIME_type,
             self.picture_type,
             self.description,
-            self.data.len()
+            self.location.map_or(self.data.len() as u64, |v| v.length)
         )
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PicType {
     Other,
     FileIcon32x32,
@@ -84,6 +94,8 @@ pub enum PicType {
     Illustration,
     BandOrArtistLogotype,
     PublisherOrStudioLogoType,
+    /// a value that the specification does not define
+    Unknown(u8),
 }
 
 impl From<u8> for PicType {
@@ -110,7 +122,7 @@ impl From<u8> for PicType {
             0x12 => PicType::Illustration,
             0x13 => PicType::BandOrArtistLogotype,
             0x14 => PicType::PublisherOrStudioLogoType,
-            _ => panic!("Invalid value for conversion to PicType"),
+            v => PicType::Unknown(v),
         }
     }
 }
@@ -138,12 +150,13 @@ impl ToString for PicType {
             Self::Illustration => String::from("Illustration"),
             Self::BandOrArtistLogotype => String::from("BandOrArtistLogotype"),
             Self::PublisherOrStudioLogoType => String::from("PublisherOrStudioLogoType"),
+            Self::Unknown(v) => format!("Unknown{v}"),
         }
     }
 }
 impl Tape for APIC {
     fn message(&self) -> String {
-        self.description.clone()
+        or_null(&self.description)
     }
     fn identifier(&self) -> String {
         self.identifier.clone()
@@ -151,4 +164,22 @@ impl Tape for APIC {
     fn raw(&self) -> Vec<u8> {
         self.data.clone()
     }
+    fn location(&self) -> Option<PictureLocation> {
+        self.location
+    }
+    fn mime_type(&self) -> Option<String> {
+        optional(&self.MIME_type)
+    }
+    fn frame(&self) -> Frame {
+        let mut data = self.data.clone();
+        // the picture type
+        data.pop();
+        Frame::Picture {
+            mime_type: self.mime_type(),
+            picture_type: self.picture_type.clone(),
+            description: optional(&self.description),
+            data,
+            location: self.location,
+        }
+    }
 }
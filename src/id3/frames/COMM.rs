@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use super::common::{Encoding, Tape};
+use super::{
+    common::{Encoding, Tape},
+    frame::{language, optional, Frame},
+};
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -45,9 +48,19 @@ impl Tape for COMM {
         self.identifier.clone()
     }
     fn message(&self) -> String {
-        self.description.clone()
+        self.data.clone()
     }
     fn raw(&self) -> Vec<u8> {
         Vec::default()
     }
-}
\ No newline at end of file
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+    fn frame(&self) -> Frame {
+        Frame::Comment {
+            language: language(&self.language),
+            description: optional(&self.description),
+            text: self.data.clone(),
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use super::common::{Encoding, Tape};
+use super::{
+    common::{or_null, Encoding, Tape},
+    frame::{language, optional, Frame},
+};
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -12,6 +15,23 @@ pub struct SYLT {
     ctype: u8,
     description: String,
     data: Vec<u8>,
+    synced_text: Vec<SyncedText>,
+}
+
+/// One line of synchronised text.
+///
+/// The unit of `timestamp` depends on the timestamp format, see `TimestampFormat`.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncedText {
+    pub timestamp: u32,
+    pub text: String,
+}
+
+impl SyncedText {
+    pub fn new(timestamp: u32, text: String) -> Self {
+        SyncedText { timestamp, text }
+    }
 }
 
 impl SYLT {
@@ -22,6 +42,7 @@ impl SYLT {
         ctype: u8,
         description: String,
         data: Vec<u8>,
+        synced_text: Vec<SyncedText>,
     ) -> SYLT {
         SYLT {
             identifier: "SYLT".to_string(),
@@ -31,6 +52,7 @@ impl SYLT {
             ctype,
             description,
             data,
+            synced_text,
         }
     }
 }
@@ -63,9 +85,18 @@ impl Tape for SYLT {
         self.identifier.clone()
     }
     fn message(&self) -> String {
-        self.description.clone()
+        or_null(&self.description)
     }
     fn raw(&self) -> Vec<u8> {
         self.data.clone()
     }
-}
\ No newline at end of file
+    fn frame(&self) -> Frame {
+        Frame::SyncedLyrics {
+            language: language(&self.language),
+            timestamp_format: self.timestamp_format.into(),
+            content_type: self.ctype,
+            description: optional(&self.description),
+            lines: self.synced_text.clone(),
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use super::common::{Encoding, Tape};
+use super::{
+    common::{Encoding, Tape},
+    frame::{optional, Frame},
+};
 
 pub struct TXXX {
     encoding: Encoding,
@@ -41,4 +44,13 @@ impl Tape for TXXX {
     fn raw(&self) -> Vec<u8> {
         Vec::default()
     }
-}
\ No newline at end of file
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+    fn frame(&self) -> Frame {
+        Frame::UserText {
+            description: optional(&self.description),
+            text: self.data.clone(),
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use super::common::{Encoding, Tape};
+use super::{
+    common::{Encoding, Tape},
+    frame::{language, optional, Frame},
+};
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -50,4 +53,14 @@ impl Tape for USLT {
     fn raw(&self) -> Vec<u8> {
         Vec::default()
     }
-}
\ No newline at end of file
+    fn description(&self) -> Option<String> {
+        Some(self.descriptor.clone())
+    }
+    fn frame(&self) -> Frame {
+        Frame::Lyrics {
+            language: language(&self.language),
+            description: optional(&self.descriptor),
+            text: self.data.clone(),
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use super::common::{Encoding, Tape};
+use super::{
+    common::{Encoding, Tape},
+    frame::{optional, Frame},
+};
 
 pub struct WXXX {
     encoding: Encoding,
@@ -42,4 +45,13 @@ impl Tape for WXXX {
     fn raw(&self) -> Vec<u8> {
         Vec::default()
     }
-}
\ No newline at end of file
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+    fn frame(&self) -> Frame {
+        Frame::UserUrl {
+            description: optional(&self.description),
+            url: self.data.clone(),
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use crate::picture::PictureLocation;
+
+use super::frame::Frame;
+
 #[allow(non_camel_case_types)]
 #[derive(Debug)]
 pub enum Encoding {
@@ -8,9 +12,32 @@ pub enum Encoding {
     UTF8,
 }
 
-pub trait Tape {
+pub trait Tape: Send + Sync {
     // 有些frame不止出现一次
     fn identifier(&self) -> String;
     fn message(&self) -> String;
     fn raw(&self) -> Vec<u8>;
-}
\ No newline at end of file
+    /// The frame with its fields, see `Frame`.
+    fn frame(&self) -> Frame;
+    /// Position of the data left in the file, see `PictureLoading::Lazy`.
+    fn location(&self) -> Option<PictureLocation> {
+        None
+    }
+    /// Description of the frames that have one, such as TXXX and COMM.
+    fn description(&self) -> Option<String> {
+        None
+    }
+    /// MIME type of the frames that hold a picture, such as APIC.
+    fn mime_type(&self) -> Option<String> {
+        None
+    }
+}
+
+/// `get` reports an empty description as "null".
+pub fn or_null(text: &str) -> String {
+    if text.is_empty() {
+        "null".to_string()
+    } else {
+        text.to_string()
+    }
+}
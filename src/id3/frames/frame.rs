@@ -0,0 +1,116 @@
+use crate::picture::PictureLocation;
+
+use super::{APIC::PicType, SYLT::SyncedText};
+
+/// An ID3v2 frame with its fields, see `ID3Parser::get_frames`.
+///
+/// A field that is empty in the file is None, where `get` reports "null".
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Frame {
+    /// text information frames, TIT2, TPE1, etc.
+    Text {
+        identifier: String,
+        /// ID3v2.4 separates multiple values with 0x00
+        text: String,
+    },
+    /// TXXX
+    UserText {
+        description: Option<String>,
+        text: String,
+    },
+    /// URL link frames, WOAR, WCOP, etc.
+    Url { identifier: String, url: String },
+    /// WXXX
+    UserUrl {
+        description: Option<String>,
+        url: String,
+    },
+    /// COMM
+    Comment {
+        /// ISO-639-2 code, such as "eng"
+        language: Option<String>,
+        description: Option<String>,
+        text: String,
+    },
+    /// USLT
+    Lyrics {
+        language: Option<String>,
+        description: Option<String>,
+        text: String,
+    },
+    /// SYLT
+    SyncedLyrics {
+        language: Option<String>,
+        timestamp_format: TimestampFormat,
+        /// 0 = other, 1 = lyrics, 2 = text transcription, 3 = movement, 4 = events, 5 = chord, 6 = trivia, 7 = URLs of webpages, 8 = URLs of images
+        content_type: u8,
+        description: Option<String>,
+        lines: Vec<SyncedText>,
+    },
+    /// APIC
+    Picture {
+        /// "-->" if `data` is the URL of the image
+        mime_type: Option<String>,
+        picture_type: PicType,
+        description: Option<String>,
+        /// empty with `PictureLoading::Lazy`, see `location`
+        #[cfg_attr(feature = "serde", serde(with = "crate::dump::base64"))]
+        data: Vec<u8>,
+        location: Option<PictureLocation>,
+    },
+    /// frames without a variant of their own, or that could not be decoded, with their payload undecoded
+    Other {
+        identifier: String,
+        payload: Vec<u8>,
+    },
+}
+
+impl Frame {
+    pub fn identifier(&self) -> String {
+        match self {
+            Frame::Text { identifier, .. }
+            | Frame::Url { identifier, .. }
+            | Frame::Other { identifier, .. } => identifier.clone(),
+            Frame::UserText { .. } => "TXXX".to_string(),
+            Frame::UserUrl { .. } => "WXXX".to_string(),
+            Frame::Comment { .. } => "COMM".to_string(),
+            Frame::Lyrics { .. } => "USLT".to_string(),
+            Frame::SyncedLyrics { .. } => "SYLT".to_string(),
+            Frame::Picture { .. } => "APIC".to_string(),
+        }
+    }
+}
+
+/// Unit of the timestamps of SYLT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimestampFormat {
+    MpegFrames,
+    Milliseconds,
+    /// a value that the specification does not define
+    Unknown(u8),
+}
+
+impl From<u8> for TimestampFormat {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => TimestampFormat::MpegFrames,
+            0x02 => TimestampFormat::Milliseconds,
+            v => TimestampFormat::Unknown(v),
+        }
+    }
+}
+
+/// None for an empty field.
+pub fn optional(text: &str) -> Option<String> {
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// None for an empty language, or "XXX" which stands for an unknown one.
+pub fn language(code: &str) -> Option<String> {
+    match code.trim_end_matches('\0') {
+        "" | "XXX" | "xxx" => None,
+        v => Some(v.to_string()),
+    }
+}
@@ -18,11 +18,18 @@ impl From<Vec<u8>> for IDFactory {
         if value[0] == 0 {
             return IDFactory::PADDING;
         }
-        let id: String = String::from_utf8(value).expect("");
+        let id: String = String::from_utf8_lossy(&value).into_owned();
+        // T and W frames missing from the tables, such as iTunes TCMP or podcast WFED, are not implemented
         if id.starts_with('T') {
-            IDFactory::T(TextInformationFrameIdentifier::from(id))
+            TextInformationFrameIdentifier::try_from(id).map_or_else(
+                |id| IDFactory::R(RarelyUsedFrameIdentifier::UNIMPLEMENT(id)),
+                IDFactory::T,
+            )
         } else if id.starts_with('W') {
-            return IDFactory::W(URLLinkFrameIdentifier::from(id));
+            return URLLinkFrameIdentifier::try_from(id).map_or_else(
+                |id| IDFactory::R(RarelyUsedFrameIdentifier::UNIMPLEMENT(id)),
+                IDFactory::W,
+            );
         } else if id == "APIC" {
             return IDFactory::APIC;
         } else if id == "USLT" {
@@ -108,13 +115,16 @@ pub enum TextInformationFrameIdentifier {
     TXXX,
 }
 
-impl From<String> for TextInformationFrameIdentifier {
-    fn from(id: String) -> Self {
+impl TryFrom<String> for TextInformationFrameIdentifier {
+    type Error = String;
+
+    /// Return `id` back if it is not in the table.
+    fn try_from(id: String) -> Result<Self, Self::Error> {
         // if value == vec![0, 0, 0, 0] {
         //     return TextInformationFrameIdentifiers::PADDING;
         // }
         // let id = String::from_utf8(value).expect("");
-        match id.as_str() {
+        Ok(match id.as_str() {
             "TIT1" => TextInformationFrameIdentifier::TIT1,
             "TIT2" => TextInformationFrameIdentifier::TIT2,
             "TIT3" => TextInformationFrameIdentifier::TIT3,
@@ -167,8 +177,8 @@ impl From<String> for TextInformationFrameIdentifier {
             "TSIZ" => TextInformationFrameIdentifier::TSIZ,
             "TYER" => TextInformationFrameIdentifier::TYER,
             "TXXX" => TextInformationFrameIdentifier::TXXX,
-            _ => unreachable!(),
-        }
+            _ => return Err(id),
+        })
     }
 }
 
@@ -244,10 +254,13 @@ pub enum URLLinkFrameIdentifier {
     WXXX,
 }
 
-impl From<String> for URLLinkFrameIdentifier {
-    fn from(id: String) -> Self {
+impl TryFrom<String> for URLLinkFrameIdentifier {
+    type Error = String;
+
+    /// Return `id` back if it is not in the table.
+    fn try_from(id: String) -> Result<Self, Self::Error> {
         // let id = String::from_utf8(value).expect("");
-        match id.as_str() {
+        Ok(match id.as_str() {
             "WCOM" => URLLinkFrameIdentifier::WCOM,
             "WCOP" => URLLinkFrameIdentifier::WCOP,
             "WOAF" => URLLinkFrameIdentifier::WOAF,
@@ -257,10 +270,8 @@ impl From<String> for URLLinkFrameIdentifier {
             "WPAY" => URLLinkFrameIdentifier::WPAY,
             "WPUB" => URLLinkFrameIdentifier::WPUB,
             "WXXX" => URLLinkFrameIdentifier::WXXX,
-            _ => {
-                unreachable!()
-            }
-        }
+            _ => return Err(id),
+        })
     }
 }
 
@@ -11,6 +11,7 @@ pub mod USLT;
 #[allow(non_snake_case)]
 pub mod WXXX;
 pub mod common;
+pub mod frame;
 pub mod header;
 pub mod identifiers;
 pub mod rarely_used;
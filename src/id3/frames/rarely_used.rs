@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use super::common::Tape;
+use super::{common::Tape, frame::Frame};
 
 pub struct RarelyUsed {
     identifier: String,
@@ -40,4 +40,11 @@ impl Tape for RarelyUsed {
     fn raw(&self) -> Vec<u8> {
         self.payload.clone()
     }
-}
\ No newline at end of file
+
+    fn frame(&self) -> Frame {
+        Frame::Other {
+            identifier: self.identifier.clone(),
+            payload: self.payload.clone(),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use super::common::Tape;
+use super::{common::Tape, frame::Frame};
 
 pub struct TextInfomationFrame {
     identifier: String,
@@ -36,4 +36,10 @@ impl Tape for TextInfomationFrame {
     fn raw(&self) -> Vec<u8> {
         Vec::default()
     }
-}
\ No newline at end of file
+    fn frame(&self) -> Frame {
+        Frame::Text {
+            identifier: self.identifier.clone(),
+            text: self.data.clone(),
+        }
+    }
+}
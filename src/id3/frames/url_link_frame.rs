@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use super::common::Tape;
+use super::{common::Tape, frame::Frame};
 
 pub struct URLLinkFrame {
     identifier: String,
@@ -36,4 +36,10 @@ impl Tape for URLLinkFrame {
     fn raw(&self) -> Vec<u8> {
         Vec::default()
     }
+    fn frame(&self) -> Frame {
+        Frame::Url {
+            identifier: self.identifier.clone(),
+            url: self.data.clone(),
+        }
+    }
 }
@@ -1,20 +1,16 @@
-use std::{
-    fs::File,
-    io::{self, Read, Seek},
-    path::Path,
-};
+use std::io::{self, Read, Seek};
 
 use crate::util::Buffer;
 
-pub struct ID3BufferReader {
-    file: File,
+pub struct ID3BufferReader<'a, R: Read + Seek> {
+    file: &'a mut R,
 }
 
-impl ID3BufferReader {
-    pub fn new<T: AsRef<Path>>(f: T) -> io::Result<Self> {
-        Ok(ID3BufferReader {
-            file: File::open(f)?,
-        })
+impl<'a, R: Read + Seek> ID3BufferReader<'a, R> {
+    /// Start reading `f` from the beginning.
+    pub fn new(f: &'a mut R) -> io::Result<Self> {
+        f.rewind()?;
+        Ok(ID3BufferReader { file: f })
     }
 
     pub fn read_protocol_header_buffer(&mut self) -> io::Result<Buffer> {
@@ -41,6 +37,31 @@ impl ID3BufferReader {
         self.read_frame_payload_buffer(128)
     }
 
+    pub fn read_id3v1_enhanced_buffer(&mut self) -> io::Result<Buffer> {
+        self.read_frame_payload_buffer(227)
+    }
+
+    pub fn read_lyrics3_buffer(&mut self, length: u32) -> io::Result<Buffer> {
+        self.read_frame_payload_buffer(length)
+    }
+
+    pub fn read_ape_footer_buffer(&mut self) -> io::Result<Buffer> {
+        self.read_frame_payload_buffer(32)
+    }
+
+    pub fn read_ape_items_buffer(&mut self, length: u32) -> io::Result<Buffer> {
+        self.read_frame_payload_buffer(length)
+    }
+
+    pub fn read_audio_buffer(&mut self, length: u32) -> io::Result<Buffer> {
+        self.read_frame_payload_buffer(length)
+    }
+
+    /// The ID3v1 tag, possibly preceded by its "TAG+" block.
+    pub fn read_id3v1_tail_buffer(&mut self, length: u32) -> io::Result<Buffer> {
+        self.read_frame_payload_buffer(length)
+    }
+
     pub fn read_footer_buffer(&mut self) -> io::Result<Buffer> {
         self.read_protocol_header_buffer()
     }
@@ -51,7 +72,7 @@ impl ID3BufferReader {
     }
 
     /// absolute position from file start
-    pub fn seek_to(&mut self, location: u64) -> io::Result<u64>{
+    pub fn seek_to(&mut self, location: u64) -> io::Result<u64> {
         self.file.seek(io::SeekFrom::Start(location))
     }
 }
@@ -1,16 +1,52 @@
 use std::fmt::Display;
 
+use super::charset::Charset;
+
 #[derive(Debug)]
 #[allow(dead_code)]
-#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ID3v1 {
-    header: Vec<u8>,
-    title: Vec<u8>,
-    artist: Vec<u8>,
-    album: Vec<u8>,
-    year: Vec<u8>,
-    comment: Vec<u8>,
-    genre: u8,
+    pub header: Vec<u8>,
+    pub title: Vec<u8>,
+    pub artist: Vec<u8>,
+    pub album: Vec<u8>,
+    pub year: Vec<u8>,
+    pub comment: Vec<u8>,
+    pub genre: u8,
+    /// whether an Enhanced "TAG+" block was found and merged
+    pub enhanced: bool,
+    /// 0 = unset, 1 = slow, 2 = medium, 3 = fast, 4 = hardcore
+    pub speed: u8,
+    /// free-text genre from the Enhanced block
+    pub genre_text: Vec<u8>,
+    /// mmm:ss
+    pub start_time: Vec<u8>,
+    /// mmm:ss
+    pub end_time: Vec<u8>,
+    /// charset used by the `*_text` getters
+    pub charset: Charset,
+}
+
+/// An empty tag whose genre is unset (255), since 0 is Blues.
+impl Default for ID3v1 {
+    fn default() -> Self {
+        ID3v1 {
+            header: Vec::default(),
+            title: Vec::default(),
+            artist: Vec::default(),
+            album: Vec::default(),
+            year: Vec::default(),
+            comment: Vec::default(),
+            genre: 0xFF,
+            enhanced: false,
+            speed: 0,
+            genre_text: Vec::default(),
+            start_time: Vec::default(),
+            end_time: Vec::default(),
+            charset: Charset::default(),
+        }
+    }
 }
 
 impl ID3v1 {
@@ -31,15 +67,196 @@ impl ID3v1 {
             year,
             comment,
             genre,
+            ..Default::default()
+        }
+    }
+
+    /// Append the extended title/artist/album of a "TAG+" block to the 30-byte fields,
+    /// so that they can hold up to 90 bytes.
+    pub fn merge_enhanced(&mut self, enhanced: ID3v1Enhanced) {
+        self.title.extend(enhanced.title);
+        self.artist.extend(enhanced.artist);
+        self.album.extend(enhanced.album);
+        self.speed = enhanced.speed;
+        self.genre_text = enhanced.genre;
+        self.start_time = enhanced.start_time;
+        self.end_time = enhanced.end_time;
+        self.enhanced = true;
+    }
+
+    pub fn title_text(&self) -> String {
+        self.charset.decode(&self.title)
+    }
+
+    pub fn artist_text(&self) -> String {
+        self.charset.decode(&self.artist)
+    }
+
+    pub fn album_text(&self) -> String {
+        self.charset.decode(&self.album)
+    }
+
+    /// The track number of ID3v1.1 is excluded.
+    pub fn comment_text(&self) -> String {
+        match self.track() {
+            Some(_) => self.charset.decode(&self.comment[..28]),
+            None => self.charset.decode(&self.comment),
+        }
+    }
+
+    pub fn set_title(&mut self, title: &str, charset: &Charset) {
+        self.title = charset.encode(title, 90);
+    }
+
+    pub fn set_artist(&mut self, artist: &str, charset: &Charset) {
+        self.artist = charset.encode(artist, 90);
+    }
+
+    pub fn set_album(&mut self, album: &str, charset: &Charset) {
+        self.album = charset.encode(album, 90);
+    }
+
+    pub fn set_year(&mut self, year: &str) {
+        self.year = Charset::ISO_8859_1.encode(year, 4);
+    }
+
+    /// The comment is limited to 28 bytes if a track number is set (ID3v1.1).
+    pub fn set_comment(&mut self, comment: &str, charset: &Charset) {
+        let track = self.track();
+        let max_len = if track.is_some() { 28 } else { 30 };
+        self.comment = charset.encode(comment, max_len);
+        if let Some(track) = track {
+            self.set_track(track);
+        }
+    }
+
+    /// Set the ID3v1.1 track number, 0 removes it.
+    ///
+    /// The last byte of an ID3v1.0 comment is kept when removing a track number that is not set.
+    pub fn set_track(&mut self, track: u8) {
+        if track == 0 {
+            if self.track().is_some() {
+                self.comment[29] = 0;
+            }
+        } else {
+            self.comment.resize(30, 0);
+            self.comment.truncate(28);
+            self.comment.push(0);
+            self.comment.push(track);
         }
     }
+
+    /// Return the ID3v1.1 track number, if any.
+    pub fn track(&self) -> Option<u8> {
+        if self.comment.len() == 30 && self.comment[28] == 0 && self.comment[29] != 0 {
+            Some(self.comment[29])
+        } else {
+            None
+        }
+    }
+
+    /// Map a genre name to its index, case insensitive.
+    ///
+    /// Return false and set the genre to 255 (unset) if the name is unknown.
+    pub fn set_genre(&mut self, genre: &str) -> bool {
+        match GENRES.iter().position(|g| g.eq_ignore_ascii_case(genre)) {
+            Some(index) => {
+                self.genre = index as u8;
+                true
+            }
+            None => {
+                self.genre = 0xFF;
+                false
+            }
+        }
+    }
+
+    /// Return the genre name according to the genre index.
+    pub fn genre_name(&self) -> Option<&'static str> {
+        GENRES.get(self.genre as usize).copied()
+    }
+
+    /// Serialize into the 128-byte "TAG" block,
+    ///
+    /// preceded by the 227-byte "TAG+" block if title, artist or album exceed 30 bytes
+    ///
+    /// or if the tag was read with an Enhanced block.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf: Vec<u8> = Vec::with_capacity(128 + 227);
+        let enhanced = self.enhanced
+            || self.title.len() > 30
+            || self.artist.len() > 30
+            || self.album.len() > 30;
+        if enhanced {
+            buf.extend_from_slice(b"TAG+");
+            push_padded(&mut buf, self.title.get(30..).unwrap_or_default(), 60);
+            push_padded(&mut buf, self.artist.get(30..).unwrap_or_default(), 60);
+            push_padded(&mut buf, self.album.get(30..).unwrap_or_default(), 60);
+            buf.push(self.speed);
+            push_padded(&mut buf, &self.genre_text, 30);
+            push_padded(&mut buf, &self.start_time, 6);
+            push_padded(&mut buf, &self.end_time, 6);
+        }
+        buf.extend_from_slice(b"TAG");
+        push_padded(&mut buf, &self.title, 30);
+        push_padded(&mut buf, &self.artist, 30);
+        push_padded(&mut buf, &self.album, 30);
+        push_padded(&mut buf, &self.year, 4);
+        push_padded(&mut buf, &self.comment, 30);
+        buf.push(self.genre);
+        buf
+    }
 }
 
+/// Push exactly `len` bytes, truncating or padding with 0x00.
+fn push_padded(buf: &mut Vec<u8>, value: &[u8], len: usize) {
+    let n = value.len().min(len);
+    buf.extend_from_slice(&value[..n]);
+    buf.resize(buf.len() + len - n, 0);
+}
 
+/// Enhanced ID3v1 block, 227 bytes located right before the 128-byte "TAG".
+///
+/// https://en.wikipedia.org/wiki/ID3#Enhanced_tag
+#[derive(Debug)]
+#[derive(Default)]
+pub struct ID3v1Enhanced {
+    pub title: Vec<u8>,
+    pub artist: Vec<u8>,
+    pub album: Vec<u8>,
+    pub speed: u8,
+    pub genre: Vec<u8>,
+    pub start_time: Vec<u8>,
+    pub end_time: Vec<u8>,
+}
+
+impl ID3v1Enhanced {
+    pub fn new(
+        title: Vec<u8>,
+        artist: Vec<u8>,
+        album: Vec<u8>,
+        speed: u8,
+        genre: Vec<u8>,
+        start_time: Vec<u8>,
+        end_time: Vec<u8>,
+    ) -> Self {
+        ID3v1Enhanced {
+            title,
+            artist,
+            album,
+            speed,
+            genre,
+            start_time,
+            end_time,
+        }
+    }
+}
 
 impl Display for ID3v1 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "
+        write!(
+            f,
+            "
 ID3v1 {{
     header: {:X?},
     title: {:X?},
@@ -47,7 +264,53 @@ ID3v1 {{
     album: {:X?},
     year: {:X?},
     comment: {:X?},
-    genre: {}
-}}", self.header, self.title, self.artist, self.album, self.year, self.comment, self.genre)
+    genre: {}",
+            self.header, self.title, self.artist, self.album, self.year, self.comment, self.genre
+        )?;
+        if self.enhanced {
+            write!(
+                f,
+                ",
+    speed: {},
+    genre_text: {:X?},
+    start_time: {:X?},
+    end_time: {:X?}",
+                self.speed, self.genre_text, self.start_time, self.end_time
+            )?;
+        }
+        write!(
+            f,
+            "
+}}"
+        )
     }
-}
\ No newline at end of file
+}
+
+/// ID3v1 genres, including the Winamp extensions.
+#[rustfmt::skip]
+pub const GENRES: [&str; 192] = [
+    "Blues", "Classic Rock", "Country", "Dance", "Disco", "Funk", "Grunge", "Hip-Hop",
+    "Jazz", "Metal", "New Age", "Oldies", "Other", "Pop", "R&B", "Rap",
+    "Reggae", "Rock", "Techno", "Industrial", "Alternative", "Ska", "Death Metal", "Pranks",
+    "Soundtrack", "Euro-Techno", "Ambient", "Trip-Hop", "Vocal", "Jazz+Funk", "Fusion", "Trance",
+    "Classical", "Instrumental", "Acid", "House", "Game", "Sound Clip", "Gospel", "Noise",
+    "AlternRock", "Bass", "Soul", "Punk", "Space", "Meditative", "Instrumental Pop", "Instrumental Rock",
+    "Ethnic", "Gothic", "Darkwave", "Techno-Industrial", "Electronic", "Pop-Folk", "Eurodance", "Dream",
+    "Southern Rock", "Comedy", "Cult", "Gangsta", "Top 40", "Christian Rap", "Pop/Funk", "Jungle",
+    "Native American", "Cabaret", "New Wave", "Psychadelic", "Rave", "Showtunes", "Trailer", "Lo-Fi",
+    "Tribal", "Acid Punk", "Acid Jazz", "Polka", "Retro", "Musical", "Rock & Roll", "Hard Rock",
+    "Folk", "Folk-Rock", "National Folk", "Swing", "Fast Fusion", "Bebob", "Latin", "Revival",
+    "Celtic", "Bluegrass", "Avantgarde", "Gothic Rock", "Progressive Rock", "Psychedelic Rock", "Symphonic Rock", "Slow Rock",
+    "Big Band", "Chorus", "Easy Listening", "Acoustic", "Humour", "Speech", "Chanson", "Opera",
+    "Chamber Music", "Sonata", "Symphony", "Booty Bass", "Primus", "Porn Groove", "Satire", "Slow Jam",
+    "Club", "Tango", "Samba", "Folklore", "Ballad", "Power Ballad", "Rhythmic Soul", "Freestyle",
+    "Duet", "Punk Rock", "Drum Solo", "A capella", "Euro-House", "Dance Hall", "Goa", "Drum & Bass",
+    "Club-House", "Hardcore Techno", "Terror", "Indie", "BritPop", "Afro-Punk", "Polsk Punk", "Beat",
+    "Christian Gangsta Rap", "Heavy Metal", "Black Metal", "Crossover", "Contemporary Christian", "Christian Rock", "Merengue", "Salsa",
+    "Thrash Metal", "Anime", "Jpop", "Synthpop", "Abstract", "Art Rock", "Baroque", "Bhangra",
+    "Big Beat", "Breakbeat", "Chillout", "Downtempo", "Dub", "EBM", "Eclectic", "Electro",
+    "Electroclash", "Emo", "Experimental", "Garage", "Global", "IDM", "Illbient", "Industro-Goth",
+    "Jam Band", "Krautrock", "Leftfield", "Lounge", "Math Rock", "New Romantic", "Nu-Breakz", "Post-Punk",
+    "Post-Rock", "Psytrance", "Shoegaze", "Space Rock", "Trop Rock", "World Music", "Neoclassical", "Audiobook",
+    "Audio Theatre", "Neue Deutsche Welle", "Podcast", "Indie Rock", "G-Funk", "Dubstep", "Garage Rock", "Psybient",
+];
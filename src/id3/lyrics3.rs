@@ -0,0 +1,117 @@
+use std::fmt::Display;
+
+use super::frames::SYLT::SyncedText;
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Lyrics3Version {
+    V1,
+    V2,
+    #[default]
+    Default,
+}
+
+impl Display for Lyrics3Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ver = match self {
+            Lyrics3Version::V1 => 1,
+            Lyrics3Version::V2 => 2,
+            Lyrics3Version::Default => -1,
+        };
+        write!(f, "{ver}")
+    }
+}
+
+/// Lyrics3 block, located between the audio and the ID3v1 tag.
+///
+/// https://id3.org/Lyrics3v2
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lyrics3 {
+    pub version: Lyrics3Version,
+    /// absolute position of "LYRICSBEGIN"
+    pub position: u64,
+    /// from "LYRICSBEGIN" to "LYRICSEND" or "LYRICS200", both included
+    pub size: u32,
+    /// IND, e.g. "11" means lyrics present and timestamps in lyrics
+    pub indications: String,
+    /// LYR, the lyrics including timestamps
+    pub lyrics: String,
+    /// INF
+    pub information: String,
+    /// AUT
+    pub author: String,
+    /// EAL
+    pub album: String,
+    /// EAR
+    pub artist: String,
+    /// ETT
+    pub title: String,
+    /// IMG
+    pub images: String,
+    /// LYR converted from `[mm:ss]` timestamps, in milliseconds
+    pub synced_lyrics: Vec<SyncedText>,
+}
+
+impl Lyrics3 {
+    pub fn new(version: Lyrics3Version, position: u64, size: u32) -> Self {
+        Lyrics3 {
+            version,
+            position,
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Store a Lyrics3v2 field, unknown fields are ignored.
+    pub fn set_field(&mut self, id: &str, data: String) {
+        match id {
+            "IND" => self.indications = data,
+            "LYR" => self.lyrics = data,
+            "INF" => self.information = data,
+            "AUT" => self.author = data,
+            "EAL" => self.album = data,
+            "EAR" => self.artist = data,
+            "ETT" => self.title = data,
+            "IMG" => self.images = data,
+            _ => {}
+        }
+    }
+}
+
+impl Display for Lyrics3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "
+Lyrics3 {{
+    version: {},
+    position: {},
+    size: {} Bytes,
+    indications: {},
+    information: {},
+    author: {},
+    album: {},
+    artist: {},
+    title: {},
+    images: {},
+    lyrics: {} Lines
+}}",
+            self.version,
+            self.position,
+            self.size,
+            self.indications,
+            self.information,
+            self.author,
+            self.album,
+            self.artist,
+            self.title,
+            self.images,
+            self.lyrics.lines().count()
+        )
+    }
+}
@@ -7,4 +7,6 @@ pub mod id3v1_tag;
 pub mod core;
 pub mod common;
 pub mod id3_buffer_reader;
-pub mod error;
\ No newline at end of file
+pub mod error;
+pub mod charset;
+pub mod lyrics3;
\ No newline at end of file
@@ -4,6 +4,8 @@ use super::version::Version;
 
 #[derive(Debug)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProtocolHeader {
     pub identifier: String,
     pub major_version: Version,
@@ -12,6 +14,14 @@ pub struct ProtocolHeader {
     pub size: u32,
 }
 
+impl ProtocolHeader {
+    /// Length of the whole tag, including this header and the footer.
+    pub fn tag_length(&self) -> u64 {
+        let footer = if self.flags.Footer { 10 } else { 0 };
+        10 + self.size as u64 + footer
+    }
+}
+
 impl Display for ProtocolHeader {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -34,6 +44,8 @@ impl Display for ProtocolHeader {
 #[allow(non_snake_case)]
 #[allow(dead_code)]
 #[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Flag {
     Unsynchronisation: bool,
     pub ExtendedHeader: bool,
@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 #[derive(Debug)]
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Version {
     V3,
     V4,
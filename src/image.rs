@@ -0,0 +1,259 @@
+/// Format of the image bytes of a picture, recognized from their signature.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Gif,
+    WebP,
+    Bmp,
+}
+
+impl ImageFormat {
+    /// File extension without the dot, such as "jpg".
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Gif => "gif",
+            ImageFormat::WebP => "webp",
+            ImageFormat::Bmp => "bmp",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::WebP => "image/webp",
+            ImageFormat::Bmp => "image/bmp",
+        }
+    }
+
+    /// Whether `mime` names this format, case insensitive.
+    ///
+    /// "image/" may be omitted as ID3v2 allows, and the usual aliases such as "image/jpg" are accepted.
+    pub fn matches_mime(&self, mime: &str) -> bool {
+        let mime = mime.trim().to_lowercase();
+        let subtype = mime.strip_prefix("image/").unwrap_or(&mime);
+        match self {
+            ImageFormat::Jpeg => matches!(subtype, "jpeg" | "jpg" | "pjpeg"),
+            ImageFormat::Png => subtype == "png",
+            ImageFormat::Gif => subtype == "gif",
+            ImageFormat::WebP => subtype == "webp",
+            ImageFormat::Bmp => matches!(subtype, "bmp" | "x-bmp" | "x-ms-bmp"),
+        }
+    }
+}
+
+/// What the image bytes of a picture really are, see `ID3Parser::image_info`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageInfo {
+    pub format: ImageFormat,
+    /// pixels, 0 if the header is not found or truncated
+    pub width: u32,
+    pub height: u32,
+    /// bits per pixel, 0 if the header is not found or truncated
+    pub depth: u32,
+    /// number of colours of the palette, 0 if the image is not indexed
+    pub colors: u32,
+    /// the MIME type declared by the tag is the one of `format`
+    pub mime_matches: bool,
+}
+
+impl ImageInfo {
+    /// Recognize `data` and read its dimensions, `mime` is the MIME type declared by the tag.
+    ///
+    /// Return None if it is not a JPEG, PNG, GIF, WebP or BMP image.
+    pub fn detect(data: &[u8], mime: &str) -> Option<Self> {
+        let (format, (width, height, depth, colors)) = if data.starts_with(b"\xFF\xD8") {
+            (ImageFormat::Jpeg, jpeg_size(data))
+        } else if data.starts_with(b"\x89PNG\r\n\x1A\n") {
+            (ImageFormat::Png, png_size(data))
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            (ImageFormat::Gif, gif_size(data))
+        } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+            (ImageFormat::WebP, webp_size(data))
+        } else if data.starts_with(b"BM") {
+            (ImageFormat::Bmp, bmp_size(data))
+        } else {
+            return None;
+        };
+        Some(ImageInfo {
+            format,
+            width,
+            height,
+            depth,
+            colors,
+            mime_matches: format.matches_mime(mime),
+        })
+    }
+}
+
+/// width, height, bits per pixel and number of colours of the palette
+type Size = (u32, u32, u32, u32);
+
+fn be16(data: &[u8], at: usize) -> u32 {
+    data.get(at..at + 2)
+        .map_or(0, |v| u16::from_be_bytes([v[0], v[1]]) as u32)
+}
+
+fn le16(data: &[u8], at: usize) -> u32 {
+    data.get(at..at + 2)
+        .map_or(0, |v| u16::from_le_bytes([v[0], v[1]]) as u32)
+}
+
+fn be32(data: &[u8], at: usize) -> u32 {
+    data.get(at..at + 4)
+        .map_or(0, |v| u32::from_be_bytes([v[0], v[1], v[2], v[3]]))
+}
+
+fn le32(data: &[u8], at: usize) -> u32 {
+    data.get(at..at + 4)
+        .map_or(0, |v| u32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+}
+
+fn le24(data: &[u8], at: usize) -> u32 {
+    data.get(at..at + 3)
+        .map_or(0, |v| u32::from_le_bytes([v[0], v[1], v[2], 0]))
+}
+
+/// Walk the segments up to the first SOF (start of frame).
+fn jpeg_size(data: &[u8]) -> Size {
+    let mut position = 2;
+    while position + 4 <= data.len() {
+        if data[position] != 0xFF {
+            break;
+        }
+        let marker = data[position + 1];
+        match marker {
+            // fill byte
+            0xFF => {
+                position += 1;
+                continue;
+            }
+            // markers without length
+            0x01 | 0xD0..=0xD8 => {
+                position += 2;
+                continue;
+            }
+            // start of scan, the frame header is missing
+            0xDA | 0xD9 => break,
+            // DHT, JPG and DAC share the range of SOF
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                if position + 10 > data.len() {
+                    break;
+                }
+                let height = be16(data, position + 5);
+                let width = be16(data, position + 7);
+                let precision = data[position + 4] as u32;
+                let components = data[position + 9] as u32;
+                return (width, height, precision * components, 0);
+            }
+            _ => position += 2 + be16(data, position + 2) as usize,
+        }
+    }
+    (0, 0, 0, 0)
+}
+
+/// Read the IHDR chunk, which comes first, and the PLTE chunk of indexed images.
+fn png_size(data: &[u8]) -> Size {
+    if data.len() < 26 || data[12..16] != *b"IHDR" {
+        return (0, 0, 0, 0);
+    }
+    let bit_depth = data[24] as u32;
+    let channels = match data[25] {
+        // grey, palette
+        0 | 3 => 1,
+        // grey and alpha
+        4 => 2,
+        2 => 3,
+        6 => 4,
+        _ => 0,
+    };
+    let colors = match data[25] {
+        3 => png_palette_length(data),
+        _ => 0,
+    };
+    (be32(data, 16), be32(data, 20), bit_depth * channels, colors)
+}
+
+/// Number of entries of the PLTE chunk, which precedes the image data.
+fn png_palette_length(data: &[u8]) -> u32 {
+    let mut position = 8;
+    while let Some(kind) = data.get(position + 4..position + 8) {
+        let length = be32(data, position);
+        match kind {
+            b"PLTE" => return length / 3,
+            b"IDAT" => break,
+            _ => position += 12 + length as usize,
+        }
+    }
+    0
+}
+
+/// Read the logical screen descriptor, the depth is the size of the global colour table.
+fn gif_size(data: &[u8]) -> Size {
+    if data.len() < 11 {
+        return (0, 0, 0, 0);
+    }
+    let packed = data[10] as u32;
+    let depth = (packed & 0x07) + 1;
+    // whether there is a global colour table
+    let colors = if packed & 0x80 != 0 { 1 << depth } else { 0 };
+    (le16(data, 6), le16(data, 8), depth, colors)
+}
+
+/// Read the first chunk, VP8 (lossy), VP8L (lossless) or VP8X (extended).
+fn webp_size(data: &[u8]) -> Size {
+    match data.get(12..16) {
+        Some(b"VP8 ") if data.len() >= 30 && data[23..26] == *b"\x9D\x01\x2A" => {
+            (le16(data, 26) & 0x3FFF, le16(data, 28) & 0x3FFF, 24, 0)
+        }
+        Some(b"VP8L") if data.len() >= 25 && data[20] == 0x2F => {
+            let bits = le32(data, 21);
+            let alpha = (bits >> 28) & 1 == 1;
+            let depth = if alpha { 32 } else { 24 };
+            ((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1, depth, 0)
+        }
+        Some(b"VP8X") if data.len() >= 30 => {
+            let alpha = data[20] & 0x10 != 0;
+            let depth = if alpha { 32 } else { 24 };
+            (le24(data, 24) + 1, le24(data, 27) + 1, depth, 0)
+        }
+        _ => (0, 0, 0, 0),
+    }
+}
+
+/// Read the DIB header, the height is negative for top-down bitmaps.
+///
+/// Images of 8 bits per pixel or less are indexed.
+fn bmp_size(data: &[u8]) -> Size {
+    let palette = |depth: u32, used: u32| match depth {
+        1..=8 if used > 0 => used,
+        1..=8 => 1 << depth,
+        _ => 0,
+    };
+    match le32(data, 14) {
+        // BITMAPCOREHEADER
+        12 if data.len() >= 26 => {
+            let depth = le16(data, 24);
+            (le16(data, 18), le16(data, 20), depth, palette(depth, 0))
+        }
+        // BITMAPINFOHEADER and its successors
+        40.. if data.len() >= 50 => {
+            let height = le32(data, 22) as i32;
+            let depth = le16(data, 28);
+            let colors = palette(depth, le32(data, 46));
+            (le32(data, 18), height.unsigned_abs(), depth, colors)
+        }
+        _ => (0, 0, 0, 0),
+    }
+}
+
+/// Extension of the files written for the image bytes `data`, jpg if the format is not recognized.
+pub(crate) fn image_extension(data: &[u8]) -> &'static str {
+    ImageInfo::detect(data, "").map_or("jpg", |v| v.format.extension())
+}
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+
+use crate::flac::blocks::block_header::BlockType;
+
+/// Position and size of an element of a tag: an ID3v2 frame, a FLAC metadata block or a Vorbis comment.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// position of the first byte of the header,
+    ///
+    /// in the file, except for Ogg comments where it is in the comment packet
+    pub offset: u64,
+    pub header_size: u32,
+    pub payload_size: u32,
+}
+
+impl Span {
+    pub fn new(offset: u64, header_size: u32, payload_size: u32) -> Self {
+        Span {
+            offset,
+            header_size,
+            payload_size,
+        }
+    }
+
+    /// Position right after the payload.
+    pub fn end(&self) -> u64 {
+        self.offset + self.header_size as u64 + self.payload_size as u64
+    }
+}
+
+/// What a `Region` of a file holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegionKind {
+    /// header, extended header and frames
+    ID3v2Tag,
+    /// ID3v2 padding or a FLAC PADDING block
+    Padding,
+    ID3v2Footer,
+    /// "fLaC"
+    FlacMarker,
+    /// a FLAC metadata block other than PADDING
+    MetadataBlock(BlockType),
+    /// the Ogg pages of the header packets (identification, comment, setup)
+    OggHeaders,
+    Audio,
+    ApeTag,
+    Lyrics3,
+    /// including its "TAG+" block
+    ID3v1,
+    /// bytes between or before the MPEG frames that are not part of any frame
+    Junk,
+    /// bytes that belong to none of the above
+    Unknown,
+}
+
+/// A range of bytes of a file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Region {
+    pub kind: RegionKind,
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl Region {
+    pub fn new(kind: RegionKind, offset: u64, length: u64) -> Self {
+        Region {
+            kind,
+            offset,
+            length,
+        }
+    }
+}
+
+/// The regions of a file from start to end, see `ID3Parser::layout`.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layout {
+    pub file_size: u64,
+    /// sorted, without overlap, the bytes between known regions are `RegionKind::Unknown`
+    pub regions: Vec<Region>,
+}
+
+impl Layout {
+    /// Sort `regions` and fill the gaps between them, empty regions are dropped.
+    pub fn new(file_size: u64, mut regions: Vec<Region>) -> Self {
+        regions.retain(|v| v.length > 0);
+        regions.sort_by_key(|v| v.offset);
+        let mut layout = Layout {
+            file_size,
+            regions: Vec::new(),
+        };
+        let mut position: u64 = 0;
+        for region in regions {
+            if region.offset > position {
+                let gap = Region::new(RegionKind::Unknown, position, region.offset - position);
+                layout.regions.push(gap);
+            }
+            position = position.max(region.offset + region.length);
+            layout.regions.push(region);
+        }
+        if file_size > position {
+            let gap = Region::new(RegionKind::Unknown, position, file_size - position);
+            layout.regions.push(gap);
+        }
+        layout
+    }
+
+    /// Total length of the regions of `kind`.
+    pub fn length_of(&self, kind: RegionKind) -> u64 {
+        self.regions
+            .iter()
+            .filter(|v| v.kind == kind)
+            .map(|v| v.length)
+            .sum()
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Layout {{")?;
+        for region in self.regions.iter() {
+            writeln!(
+                f,
+                "    {:>10} {:>10} {:?}",
+                region.offset, region.length, region.kind
+            )?;
+        }
+        write!(f, "}} {} bytes", self.file_size)
+    }
+}
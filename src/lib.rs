@@ -1,59 +1,114 @@
 // 如果frame有description不定长字段，如果frame中为空，则设置为String::from("null")
+mod ape;
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "serde")]
+mod dump;
+mod error;
 mod flac;
+mod hash;
 mod id3;
+mod image;
+mod layout;
+mod mpeg;
 mod ogg;
+mod picture;
+mod probe;
+mod scan;
+mod tag;
 mod util;
 
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fs;
 use std::fs::File;
+use std::fs::OpenOptions;
 use std::io;
-use std::path::Path;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 
+use sha2::{Digest, Sha256};
+
+use ape::core::{parse_ape_footer, parse_ape_items};
+use error::tolerate;
 use flac::blocks::{
-    block_application::BlockApplication,
-    block_cue_sheet::BlockCueSheet,
-    block_header::{BlockHeader, BlockType},
-    block_picture::BlockPicture,
-    block_seektable::BlockSeekTable,
-    block_stream_info::BlockStreamInfo,
+    block_application::BlockApplication, block_cue_sheet::BlockCueSheet, block_header::BlockHeader,
+    block_seektable::BlockSeekTable, block_stream_info::BlockStreamInfo,
     block_vorbis_comment::BlockVorbisComment,
 };
 use flac::core::parse_block_cue_sheet;
+use flac::error::FlacError;
 use flac::flac_buffer_reader::FlacBufferReader;
+use hash::hash_range;
 use id3::{
+    common::get_size,
     core::{
-        parse_extended_header, parse_footer_buffer, parse_frame_header, parse_frame_payload,
-        parse_protocol_header,
+        parse_decimal, parse_extended_header, parse_footer_buffer, parse_frame_header,
+        parse_frame_payload, parse_lazy_APIC, parse_lyrics3_timestamps, parse_lyrics3v2_fields,
+        parse_protocol_header, APIC_head_length,
     },
-    frames::APIC::PicType,
+    frames::{header::FrameHeader, identifiers::IDFactory},
+    protocol_header::Flag,
+    version::Version,
 };
 use id3::{
     error::ID3Error, extended_header::ExtendedHeader, footer::Footer, frames::common::Tape,
-    id3_buffer_reader::ID3BufferReader, id3v1_tag::ID3v1, protocol_header::ProtocolHeader,
+    id3_buffer_reader::ID3BufferReader, id3v1_tag::ID3v1Enhanced, protocol_header::ProtocolHeader,
 };
+use image::image_extension;
 
+use mpeg::core::{find_first_frame, parse_vbri_header, parse_xing_header, scan_stream, SCAN_CHUNK};
 use ogg::{
+    core::{collect_comment_packet, parse_page_header},
     ogg_buffer_reader::OggBufferReader,
     ogg_vorbis_comment::{CommentBody, CommentHeader, HeaderType},
-    page::PageHeader,
 };
+use picture::{
+    linked_picture, no_picture, picture_reader, read_picture, LINK_MIME_TYPE, PICTURE_HEAD_LENGTH,
+};
+use probe::id3v2_length;
 use util::{parse_4_bytes_with_little_endian, Buffer};
 
+pub use ape::ape_tag::{ApeCoverArt, ApeFooter, ApeItem, ApeTag, ApeValue};
+#[cfg(feature = "serde")]
+pub use dump::{Dump, FlacDump, FrameDump, ID3Dump, OggDump, PictureData};
+pub use error::{Error, ParseMode, Warning};
+pub use flac::blocks::block_header::BlockType;
+pub use flac::blocks::block_picture::{BlockPicture, PicType as FlacPicType};
+pub use hash::AudioHash;
+pub use id3::charset::{Charset, CharsetDetection, TextDecoding};
+pub use id3::frames::frame::{Frame, TimestampFormat};
+pub use id3::frames::APIC::PicType;
+pub use id3::frames::SYLT::SyncedText;
+pub use id3::id3v1_tag::{ID3v1, GENRES};
+pub use id3::lyrics3::{Lyrics3, Lyrics3Version};
+pub use image::{ImageFormat, ImageInfo};
+pub use layout::{Layout, Region, RegionKind, Span};
+pub use mpeg::audio_properties::AudioProperties;
+pub use mpeg::frame_header::{ChannelMode, Emphasis, Layer, MpegFrameHeader, MpegVersion};
+pub use mpeg::stream_scan::{JunkRegion, StreamScan};
+pub use mpeg::vbr_header::{LameHeader, VbrMethod, VbriHeader, XingHeader};
+pub use picture::{PictureContent, PictureLoading, PictureLocation};
+pub use probe::{open, probe, probe_reader, AudioFile, Codec, Format, Probe};
+pub use scan::{Scan, ScanCancel, ScanEntry, ScanProgress, Scanner};
+pub use tag::{StandardKey, Tag};
+
 use flac::core::{
-    parse_block_application, parse_block_header, parse_block_picture, parse_block_seektable,
-    parse_flac_marker, parse_stream_info_block, parse_vorbis_comment,
+    block_header_bytes, block_picture_bytes, block_type_number, parse_block_application,
+    parse_block_header, parse_block_picture, parse_block_seektable, parse_flac_marker,
+    parse_lazy_block_picture, parse_stream_info_block, parse_vorbis_comment, picture_head_length,
+    vorbis_comment_bytes, DEFAULT_PADDING, MAX_BLOCK_LENGTH,
 };
 
-pub struct ID3Parser<T>
-where
-    T: AsRef<Path>,
-{
-    fp: T,
+pub struct ID3Parser<R = File> {
+    source: R,
+    /// path of the file, required by the methods that write to disk
+    path: Option<PathBuf>,
     hm: HashMap<String, usize>,
     /// Some frames appear more than once
     frames: Vec<Vec<Box<dyn Tape>>>,
+    /// every frame in file order, see `frames_in_order`
+    frame_order: Vec<(Span, FrameSlot)>,
     /// protocol header
     pub pheader: ProtocolHeader,
     /// extended header
@@ -64,31 +119,81 @@ where
     pub footer: Footer,
     /// ID3v1 tag
     pub id3v1: ID3v1,
+    /// Lyrics3 block
+    pub lyrics3: Lyrics3,
+    /// APEv2 tag
+    pub ape: ApeTag,
+    /// MPEG audio stream properties
+    pub audio_properties: AudioProperties,
+    /// result of `scan_audio_stream`
+    pub stream_scan: StreamScan,
+    /// charset guessed for the ISO-8859-1 text of ID3v2, only with `TextDecoding::Detect`
+    pub id3v2_charset: Option<CharsetDetection>,
+    /// charset guessed for ID3v1, only with `TextDecoding::Detect`
+    pub id3v1_charset: Option<CharsetDetection>,
+    text_decoding: TextDecoding,
+    /// problems skipped by `parse_id3v2`, see `set_parse_mode`
+    pub warnings: Vec<Warning>,
+    parse_mode: ParseMode,
+    picture_loading: PictureLoading,
     /// file size, for locating ID3v1
     file_size: u64,
 }
 
-impl<T> ID3Parser<T>
-where
-    T: AsRef<Path>,
-{
+impl ID3Parser<File> {
     /// Create a new parser.
     /// 传入一个列表，启动多个线程进行解析：怎么返回值？线程切换成本？
     /// 用户启动多个线程，每个线程一个ID3Parser
     /// 异步？对已经确定的Buffer
-    pub fn new(fp: T) -> io::Result<Self> {
-        let file_size = File::open(&fp)?.metadata()?.len();
-        Ok(ID3Parser {
-            fp,
+    pub fn new<T: AsRef<Path>>(fp: T) -> io::Result<Self> {
+        let mut parser = ID3Parser::from_reader(File::open(&fp)?)?;
+        parser.path = Some(fp.as_ref().to_owned());
+        Ok(parser)
+    }
+
+    /// As the method says.
+    ///
+    /// In addition, its own data will be cleared.
+    pub fn change_target<T: AsRef<Path>>(&mut self, new_fp: T) -> io::Result<()> {
+        self.change_source(File::open(&new_fp)?)?;
+        self.path = Some(new_fp.as_ref().to_owned());
+        Ok(())
+    }
+}
+
+impl<'a> ID3Parser<Cursor<&'a [u8]>> {
+    /// Create a new parser over an in-memory file, such as an upload.
+    pub fn from_bytes(bytes: &'a [u8]) -> io::Result<Self> {
+        ID3Parser::from_reader(Cursor::new(bytes))
+    }
+}
+
+impl<R> ID3Parser<R> {
+    /// Create a parser of `source` whose length is `file_size`, without reading anything.
+    fn with_source(source: R, file_size: u64) -> Self {
+        ID3Parser {
+            source,
+            path: None,
             hm: HashMap::default(),
             frames: Vec::default(),
+            frame_order: Vec::default(),
             pheader: ProtocolHeader::default(),
             eheader: ExtendedHeader::default(),
             padding_size: u32::default(),
             footer: Footer::default(),
             id3v1: ID3v1::default(),
+            lyrics3: Lyrics3::default(),
+            ape: ApeTag::default(),
+            audio_properties: AudioProperties::default(),
+            stream_scan: StreamScan::default(),
+            id3v2_charset: None,
+            id3v1_charset: None,
+            text_decoding: TextDecoding::default(),
+            warnings: Vec::default(),
+            parse_mode: ParseMode::default(),
+            picture_loading: PictureLoading::default(),
             file_size,
-        })
+        }
     }
 
     /// Return frame content that after decoding.
@@ -115,6 +220,8 @@ where
     ///
     /// SYLT may call the `get` method in the future.
     ///
+    /// With `PictureLoading::Lazy`, APIC only holds the picture type, see `load_picture`.
+    ///
     /// This method is case insensitive.
     pub fn get_raw(&self, query: &str) -> Option<Vec<Vec<u8>>> {
         let upper_query = query.to_uppercase();
@@ -129,6 +236,118 @@ where
         }
     }
 
+    /// Return the frames of `query` with their fields, see `Frame`.
+    ///
+    /// This method is case insensitive.
+    pub fn get_frames(&self, query: &str) -> Option<Vec<Frame>> {
+        self.hm
+            .get(&query.to_uppercase())
+            .map(|i| self.frames[*i].iter().map(|v| v.frame()).collect())
+    }
+
+    /// Return all the frames with their fields, frames with the same identifier are together.
+    pub fn all_frames(&self) -> Vec<Frame> {
+        self.frames.iter().flatten().map(|v| v.frame()).collect()
+    }
+
+    /// Return the `index`-th APIC frame.
+    fn apic(&self, index: usize) -> io::Result<&dyn Tape> {
+        self.hm
+            .get("APIC")
+            .and_then(|i| self.frames[*i].get(index))
+            .map(|v| v.as_ref())
+            .ok_or_else(|| no_picture(index))
+    }
+
+    /// Collect the frames under standard keys, see `Tag`,
+    ///
+    /// completed by ID3v1 if it was parsed.
+    pub fn tag(&self) -> Tag {
+        let mut tag = self.id3v2_tag();
+        tag.merge_id3v1(&self.id3v1);
+        tag
+    }
+
+    /// Same as `tag`, without the fields taken from ID3v1.
+    pub fn id3v2_tag(&self) -> Tag {
+        let mut tag = Tag::default();
+        let mut identifiers: Vec<(&String, &usize)> = self.hm.iter().collect();
+        identifiers.sort();
+        for (identifier, index) in identifiers {
+            for frame in self.frames[*index].iter() {
+                let message = frame.message();
+                let description = frame.description().unwrap_or_default();
+                let key = match identifier.as_str() {
+                    "TXXX" => StandardKey::from_txxx(&description),
+                    "COMM" if description.is_empty() => Some(StandardKey::Comment),
+                    "UFID" => {
+                        // owner identifier, then the id
+                        let raw = frame.raw();
+                        let mut fields = raw.splitn(2, |&b| b == 0);
+                        if fields.next() == Some(b"http://musicbrainz.org") {
+                            let id = String::from_utf8_lossy(fields.next().unwrap_or_default());
+                            tag.push(StandardKey::MusicBrainzTrackId, &id);
+                        }
+                        continue;
+                    }
+                    id => StandardKey::from_id3(id),
+                };
+                // ID3v2.4 separates multiple values with 0x00
+                for value in message.split('\0') {
+                    match key {
+                        Some(key) => tag.push(key, value),
+                        None if !description.is_empty() => {
+                            tag.push_native(&format!("{identifier}:{description}"), value)
+                        }
+                        None if identifier.starts_with(['T', 'W']) => {
+                            tag.push_native(identifier, value)
+                        }
+                        // pictures, synchronised lyrics and binary frames
+                        None => {}
+                    }
+                }
+            }
+        }
+        tag
+    }
+
+    /// Choose how text declared as ISO-8859-1 is decoded by the following parse calls.
+    ///
+    /// Many tags written by old Chinese, Japanese or Russian software
+    ///
+    /// declare ISO-8859-1 but actually contain GBK, Shift-JIS or CP1251.
+    pub fn set_text_decoding(&mut self, decoding: TextDecoding) {
+        self.text_decoding = decoding;
+    }
+
+    /// In lenient mode, `parse_id3v2` skips broken frames and records them in `self.warnings`.
+    ///
+    /// Unimplemented frames are always recorded.
+    pub fn set_parse_mode(&mut self, mode: ParseMode) {
+        self.parse_mode = mode;
+    }
+
+    /// With `PictureLoading::Lazy`, `parse_id3v2` skips the image bytes of APIC frames,
+    ///
+    /// read them with `load_picture` or `picture_reader`.
+    pub fn set_picture_loading(&mut self, loading: PictureLoading) {
+        self.picture_loading = loading;
+    }
+
+    /// Resolve the charset of ISO-8859-1 text according to `self.text_decoding`.
+    ///
+    /// `sample` is all the ISO-8859-1 text of a tag.
+    fn resolve_latin1(&self, sample: &[u8]) -> (Charset, Option<CharsetDetection>) {
+        match self.text_decoding {
+            TextDecoding::Latin1 => (Charset::ISO_8859_1, None),
+            TextDecoding::Force(charset) => (charset, None),
+            TextDecoding::Detect => {
+                let detection = Charset::detect(sample);
+                (detection.charset, Some(detection))
+            }
+        }
+    }
+
     /// Push a frame to self.frames.
     fn push(&mut self, v: Box<dyn Tape>) -> io::Result<()> {
         if let Some(index) = self.hm.get(&v.identifier()) {
@@ -142,18 +361,24 @@ where
         Ok(())
     }
 
-    /// Start parsing id3v1.
-    ///
-    /// It is not recommended to call this method,
+    /// Return the text values of an APEv2 item, such as REPLAYGAIN_TRACK_GAIN.
     ///
-    /// thinking that the ID3 protocol contains very little information,
+    /// This method is case insensitive.
+    pub fn get_ape(&self, query: &str) -> Option<Vec<String>> {
+        self.ape.get(query)?.text()
+    }
+
+    /// Parse the last 128 bytes of `tail` as ID3v1,
     ///
-    /// unless a very old song.
-    pub fn parse_id3v1(&mut self) -> io::Result<()> {
-        let position = self.file_size - 128;
-        let mut buffer_reader = ID3BufferReader::new(&self.fp)?;
-        buffer_reader.seek_to(position)?;
-        let buffer = buffer_reader.read_id3v1_buffer()?;
+    /// and the 227 bytes before them as its "TAG+" block if present.
+    fn parse_id3v1_tail(&mut self, tail: &[u8]) -> io::Result<()> {
+        if tail.len() < 128 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the file is shorter than an ID3v1 tag",
+            ));
+        }
+        let buffer = &tail[tail.len() - 128..];
         let mut start: usize = 0;
         let header: Vec<u8> = (buffer[start..start + 3]).to_vec();
         // update_start_end()
@@ -170,121 +395,680 @@ where
         start += 30;
         let genre: u8 = buffer[start];
         self.id3v1 = ID3v1::new(header, title, artist, album, year, comment, genre);
+        if self.id3v1.header == b"TAG" && tail.len() >= 128 + 227 {
+            let buffer = &tail[tail.len() - 128 - 227..];
+            if buffer[0..4] == *b"TAG+" {
+                let mut start: usize = 4;
+                let title: Vec<u8> = (buffer[start..start + 60]).to_vec();
+                start += 60;
+                let artist: Vec<u8> = (buffer[start..start + 60]).to_vec();
+                start += 60;
+                let album: Vec<u8> = (buffer[start..start + 60]).to_vec();
+                start += 60;
+                let speed: u8 = buffer[start];
+                start += 1;
+                let genre: Vec<u8> = (buffer[start..start + 30]).to_vec();
+                start += 30;
+                let start_time: Vec<u8> = (buffer[start..start + 6]).to_vec();
+                start += 6;
+                let end_time: Vec<u8> = (buffer[start..start + 6]).to_vec();
+                self.id3v1.merge_enhanced(ID3v1Enhanced::new(
+                    title, artist, album, speed, genre, start_time, end_time,
+                ));
+            }
+        }
+        let sample = [
+            &self.id3v1.title[..],
+            &self.id3v1.artist[..],
+            &self.id3v1.album[..],
+            &self.id3v1.comment[..],
+        ]
+        .join(&b' ');
+        (self.id3v1.charset, self.id3v1_charset) = self.resolve_latin1(&sample);
         Ok(())
     }
 
-    /// Start parsing id3v2.
-    pub fn parse_id3v2(&mut self) -> io::Result<()> {
-        let mut buffer_reader = ID3BufferReader::new(&self.fp)?;
+    /// Decode the frames read by `parse_id3v2`.
+    ///
+    /// Frames are decoded after the whole tag is read,
+    ///
+    /// so that the charset of ISO-8859-1 text can be detected from all of them.
+    fn decode_frames(&mut self, pending: Vec<(u64, FrameHeader, Buffer)>) -> Result<(), Error> {
+        let mut sample: Vec<u8> = Vec::new();
+        for (_, header, payload) in pending.iter() {
+            if payload.first() != Some(&0x00) {
+                continue;
+            }
+            match header.identifier {
+                IDFactory::T(_) => sample.extend_from_slice(&payload[1..]),
+                IDFactory::COMM | IDFactory::USLT if payload.len() > 4 => {
+                    sample.extend_from_slice(&payload[4..])
+                }
+                _ => continue,
+            }
+            sample.push(b' ');
+        }
+        let latin1;
+        (latin1, self.id3v2_charset) = self.resolve_latin1(&sample);
+        for (offset, header, payload) in pending.iter() {
+            // 优化为异步
+            let rst = match header.identifier {
+                IDFactory::APIC if self.picture_loading == PictureLoading::Lazy => {
+                    parse_lazy_APIC(payload, &latin1, offset + 10, header.size)
+                        .map(|v| Box::new(v) as Box<dyn Tape>)
+                }
+                _ => parse_frame_payload(payload, header, &latin1),
+            };
+            let span = Span::new(*offset, 10, header.size);
+            match rst {
+                Ok(frame) => {
+                    let identifier = frame.identifier();
+                    self.push(frame)?;
+                    let group = self.hm[&identifier];
+                    let slot = FrameSlot::Parsed(group, self.frames[group].len() - 1);
+                    self.frame_order.push((span, slot));
+                }
+                Err(e) => {
+                    let identifier = header.identifier.to_string();
+                    let e = e.at(*offset, &identifier);
+                    tolerate(self.parse_mode, &mut self.warnings, e)?;
+                    let slot = FrameSlot::Unparsed(identifier, payload.clone());
+                    self.frame_order.push((span, slot));
+                }
+            }
+        }
+        // skipped frames are pushed while reading
+        self.frame_order.sort_by_key(|(span, _)| span.offset);
+        Ok(())
+    }
 
-        let mut buffer: Buffer;
+    /// Return every frame in file order, with its position and size.
+    ///
+    /// Frames that are not implemented or cannot be decoded are `Frame::Other`, with an empty payload if they were skipped.
+    pub fn frames_in_order(&self) -> impl Iterator<Item = (Span, Frame)> + '_ {
+        self.frame_order.iter().map(|(span, slot)| {
+            let frame = match slot {
+                FrameSlot::Parsed(group, index) => self.frames[*group][*index].frame(),
+                FrameSlot::Unparsed(identifier, payload) => Frame::Other {
+                    identifier: identifier.clone(),
+                    payload: payload.clone(),
+                },
+            };
+            (*span, frame)
+        })
+    }
+}
 
-        buffer = buffer_reader.read_protocol_header_buffer()?;
-        let rst = parse_protocol_header(&buffer);
-        if rst.is_err() {
-            println!("not include ID3v2.3 or ID3v2.4");
+impl<R> ID3Parser<R>
+where
+    R: Read + Seek,
+{
+    /// Create a new parser over any seekable source, such as an object-store stream.
+    ///
+    /// `write_id3v1`, `remove_id3v1` and `write_image` need a path and fail on such a parser.
+    pub fn from_reader(mut source: R) -> io::Result<Self> {
+        let file_size = source.seek(SeekFrom::End(0))?;
+        Ok(ID3Parser::with_source(source, file_size))
+    }
+
+    /// Start parsing id3v1.
+    ///
+    /// It is not recommended to call this method,
+    ///
+    /// thinking that the ID3 protocol contains very little information,
+    ///
+    /// unless a very old song.
+    ///
+    /// If an Enhanced "TAG+" block precedes the tag, its title, artist and album
+    ///
+    /// are appended to the 30-byte fields, extending them to 90 bytes.
+    pub fn parse_id3v1(&mut self) -> io::Result<()> {
+        let length = self.file_size.min(128 + 227);
+        let mut buffer_reader = ID3BufferReader::new(&mut self.source)?;
+        buffer_reader.seek_to(self.file_size - length)?;
+        let tail = buffer_reader.read_id3v1_tail_buffer(length as u32)?;
+        self.parse_id3v1_tail(&tail)
+    }
+
+    /// Write `self.id3v1` to the end of the file,
+    ///
+    /// replacing the existing ID3v1 tag and "TAG+" block if any.
+    pub fn write_id3v1(&mut self) -> io::Result<()> {
+        let tag_start = self.locate_id3v1()?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(require_path(&self.path)?)?;
+        file.set_len(tag_start)?;
+        file.seek(SeekFrom::Start(tag_start))?;
+        let buffer = self.id3v1.to_bytes();
+        file.write_all(&buffer)?;
+        self.file_size = tag_start + buffer.len() as u64;
+        Ok(())
+    }
+
+    /// Strip the ID3v1 tag and "TAG+" block from the end of the file.
+    pub fn remove_id3v1(&mut self) -> io::Result<()> {
+        let tag_start = self.locate_id3v1()?;
+        if tag_start < self.file_size {
+            OpenOptions::new()
+                .write(true)
+                .open(require_path(&self.path)?)?
+                .set_len(tag_start)?;
+            self.file_size = tag_start;
+        }
+        self.id3v1 = ID3v1::default();
+        Ok(())
+    }
+
+    /// Return the position of the ID3v1 tag (or its "TAG+" block),
+    ///
+    /// or the file size if there is none.
+    fn locate_id3v1(&mut self) -> io::Result<u64> {
+        if self.file_size < 128 {
+            return Ok(self.file_size);
+        }
+        let mut buffer_reader = ID3BufferReader::new(&mut self.source)?;
+        buffer_reader.seek_to(self.file_size - 128)?;
+        if buffer_reader.read_id3v1_buffer()?[0..3] != *b"TAG" {
+            return Ok(self.file_size);
+        }
+        let mut position = self.file_size - 128;
+        if position >= 227 {
+            buffer_reader.seek_to(position - 227)?;
+            if buffer_reader.read_id3v1_enhanced_buffer()?[0..4] == *b"TAG+" {
+                position -= 227;
+            }
+        }
+        Ok(position)
+    }
+
+    /// Start parsing Lyrics3 v1 or v2, which is located right before the ID3v1 tag.
+    ///
+    /// The `[mm:ss]` timestamps in the lyrics are converted into `lyrics3.synced_lyrics`.
+    pub fn parse_lyrics3(&mut self) -> io::Result<()> {
+        let end = self.locate_id3v1()?;
+        if end < 11 + 15 {
+            return Ok(());
+        }
+        let mut buffer_reader = ID3BufferReader::new(&mut self.source)?;
+        buffer_reader.seek_to(end - 15)?;
+        let tail = buffer_reader.read_lyrics3_buffer(15)?;
+        if tail[6..] == *b"LYRICS200" {
+            // the size includes "LYRICSBEGIN", but not itself and "LYRICS200"
+            let size = match parse_decimal(&tail[0..6]) {
+                Some(size) if size as u64 + 15 <= end && size >= 11 => size,
+                _ => return Ok(()),
+            };
+            let position = end - 15 - size as u64;
+            buffer_reader.seek_to(position)?;
+            let buffer = buffer_reader.read_lyrics3_buffer(size)?;
+            if buffer[0..11] != *b"LYRICSBEGIN" {
+                return Ok(());
+            }
+            let mut lyrics3 = Lyrics3::new(Lyrics3Version::V2, position, size + 15);
+            parse_lyrics3v2_fields(&buffer[11..], &mut lyrics3)
+                .map_err(|e| e.at(position + 11, "Lyrics3v2"))?;
+            self.lyrics3 = lyrics3;
+        } else if tail[6..] == *b"LYRICSEND" {
+            // Lyrics3v1 holds up to 5100 bytes of lyrics
+            let length = (end - 9).min(11 + 5100);
+            let position = end - 9 - length;
+            buffer_reader.seek_to(position)?;
+            let buffer = buffer_reader.read_lyrics3_buffer(length as u32)?;
+            if let Some(index) = buffer.windows(11).rposition(|w| w == b"LYRICSBEGIN") {
+                let mut lyrics3 = Lyrics3::new(
+                    Lyrics3Version::V1,
+                    position + index as u64,
+                    (length as usize - index + 9) as u32,
+                );
+                lyrics3.lyrics = util::latin1_to_string(&buffer[index + 11..]);
+                lyrics3.synced_lyrics = parse_lyrics3_timestamps(&lyrics3.lyrics);
+                self.lyrics3 = lyrics3;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start parsing the APEv2 tag written by tools like mp3gain or foobar2000,
+    ///
+    /// which is located before the ID3v1 tag.
+    ///
+    /// Call `parse_lyrics3` first if the file may also carry a Lyrics3 block.
+    pub fn parse_ape(&mut self) -> io::Result<()> {
+        let mut end = self.locate_id3v1()?;
+        let mut buffer_reader = ID3BufferReader::new(&mut self.source)?;
+        let mut footer = None;
+        for _ in 0..2 {
+            if end >= 32 {
+                buffer_reader.seek_to(end - 32)?;
+                let buffer = buffer_reader.read_ape_footer_buffer()?;
+                if let Ok(v) = parse_ape_footer(&buffer) {
+                    footer = Some(v);
+                    break;
+                }
+            }
+            // the APEv2 tag may precede the Lyrics3 block
+            match self.lyrics3.version {
+                Lyrics3Version::Default => break,
+                _ if self.lyrics3.position + self.lyrics3.size as u64 == end => {
+                    end = self.lyrics3.position;
+                }
+                _ => break,
+            }
+        }
+        let Some(footer) = footer else {
+            return Ok(());
+        };
+        if footer.size as u64 > end {
             return Ok(());
         }
-        self.pheader = rst.unwrap();
+        let items_start = end - footer.size as u64;
+        buffer_reader.seek_to(items_start)?;
+        let buffer = buffer_reader.read_ape_items_buffer(footer.size - 32)?;
+        let items = parse_ape_items(&buffer, footer.item_count)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let position = if footer.has_header && items_start >= 32 {
+            items_start - 32
+        } else {
+            items_start
+        };
+        self.ape = ApeTag::new(position, footer, items);
+        Ok(())
+    }
+
+    /// Start parsing the first MPEG audio frame after the ID3v2 tag.
+    ///
+    /// The duration is exact if the frame carries a Xing, Info or VBRI header,
+    ///
+    /// otherwise it is calculated assuming CBR.
+    ///
+    /// Call `parse_ape` and `parse_lyrics3` first to exclude them from the audio size.
+    pub fn parse_audio_properties(&mut self) -> io::Result<()> {
+        let start = self.locate_audio_start()?;
+        let end = self.locate_audio_end()?;
+        if start >= end {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no MPEG audio frame found",
+            ));
+        }
+        let mut buffer_reader = ID3BufferReader::new(&mut self.source)?;
+        buffer_reader.seek_to(start)?;
+        // junk may precede the first frame
+        let length = (end - start).min(0x10000) as u32;
+        let buffer = buffer_reader.read_audio_buffer(length)?;
+        let (offset, header) = find_first_frame(&buffer).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no MPEG audio frame found: {:?}", e),
+            )
+        })?;
+        let first_frame_offset = start + offset as u64;
+        let frame = &buffer[offset..];
+        let xing = parse_xing_header(frame, &header);
+        let vbri = parse_vbri_header(frame);
+        self.audio_properties = AudioProperties::new(
+            header,
+            first_frame_offset,
+            end - first_frame_offset,
+            xing,
+            vbri,
+        );
+        Ok(())
+    }
+
+    /// Walk every MPEG frame after the ID3v2 tag, verifying the CRC-16 of protected frames
+    ///
+    /// and reporting lost sync, junk and a truncated last frame, see `StreamScan::is_ok`.
+    ///
+    /// The whole audio is read in chunks of 1 MiB, so this is much slower than `parse_audio_properties`.
+    ///
+    /// Only Layer III frames have their CRC checked, those of Layer I and II are counted as valid.
+    ///
+    /// Call `parse_ape` and `parse_lyrics3` first, otherwise they are reported as junk.
+    pub fn scan_audio_stream(&mut self) -> io::Result<()> {
+        let start = self.locate_audio_start()?;
+        let end = self.locate_audio_end()?;
+        self.source.seek(SeekFrom::Start(start))?;
+        let audio = (&mut self.source).take(end.saturating_sub(start));
+        self.stream_scan = scan_stream(audio, start, SCAN_CHUNK)?;
+        Ok(())
+    }
+
+    /// Hash the MPEG audio only, skipping ID3v2, APEv2, Lyrics3 and ID3v1,
+    ///
+    /// so that two copies of the same file with different tags have the same digest.
+    ///
+    /// The trailing tags are parsed by this method.
+    pub fn audio_hash(&mut self) -> io::Result<AudioHash> {
+        self.parse_lyrics3()?;
+        self.parse_ape()?;
+        let start = self.locate_audio_start()?;
+        let end = self.locate_audio_end()?;
+        hash_range(&mut self.source, start, end.max(start))
+    }
+
+    /// Map the regions of the file: ID3v2 tag, padding and footer, audio, APEv2, Lyrics3 and ID3v1.
+    ///
+    /// Call `parse_id3v2` first to separate the padding from the frames,
+    ///
+    /// and `parse_audio_properties` or `scan_audio_stream` to find the junk around the MPEG frames.
+    ///
+    /// The trailing tags are parsed by this method.
+    pub fn layout(&mut self) -> io::Result<Layout> {
+        self.parse_lyrics3()?;
+        self.parse_ape()?;
+        let mut regions: Vec<Region> = Vec::new();
+        let start = self.locate_audio_start()?;
+        if self.pheader.identifier == "ID3" {
+            let end = 10 + self.pheader.size as u64;
+            let padding = self.padding_size as u64;
+            regions.push(Region::new(RegionKind::ID3v2Tag, 0, end - padding));
+            if padding > 0 {
+                regions.push(Region::new(RegionKind::Padding, end - padding, padding));
+            }
+            if self.pheader.flags.Footer {
+                regions.push(Region::new(RegionKind::ID3v2Footer, end, 10));
+            }
+        } else if start > 0 {
+            // the tag is not parsed, only its header is read
+            regions.push(Region::new(RegionKind::ID3v2Tag, 0, start));
+        }
+        let end = self.locate_audio_end()?;
+        let mut position = start;
+        for (offset, length) in self.audio_junk(start, end) {
+            if offset > position {
+                regions.push(Region::new(RegionKind::Audio, position, offset - position));
+            }
+            regions.push(Region::new(RegionKind::Junk, offset, length));
+            position = offset + length;
+        }
+        if end > position {
+            regions.push(Region::new(RegionKind::Audio, position, end - position));
+        }
+        if let Lyrics3Version::V1 | Lyrics3Version::V2 = self.lyrics3.version {
+            let lyrics3 = &self.lyrics3;
+            regions.push(Region::new(
+                RegionKind::Lyrics3,
+                lyrics3.position,
+                lyrics3.size as u64,
+            ));
+        }
+        if self.ape.footer.size > 0 {
+            let ape = &self.ape;
+            regions.push(Region::new(
+                RegionKind::ApeTag,
+                ape.position,
+                ape.size() as u64,
+            ));
+        }
+        let id3v1 = self.locate_id3v1()?;
+        if id3v1 < self.file_size {
+            regions.push(Region::new(
+                RegionKind::ID3v1,
+                id3v1,
+                self.file_size - id3v1,
+            ));
+        }
+        Ok(Layout::new(self.file_size, regions))
+    }
+
+    /// Junk between `start` and `end` found by `scan_audio_stream`,
+    ///
+    /// or else before the first frame found by `parse_audio_properties`.
+    fn audio_junk(&self, start: u64, end: u64) -> Vec<(u64, u64)> {
+        let mut junk: Vec<(u64, u64)> = if self.stream_scan.frame_count > 0 {
+            let junk = self.stream_scan.junk.iter();
+            junk.map(|v| (v.offset, v.length)).collect()
+        } else if self.audio_properties.first_frame_offset > start {
+            vec![(start, self.audio_properties.first_frame_offset - start)]
+        } else {
+            Vec::new()
+        };
+        // the trailing tags may have been parsed after the scan
+        junk.retain(|&(offset, _)| offset >= start && offset < end);
+        for (offset, length) in junk.iter_mut() {
+            *length = (*length).min(end - *offset);
+        }
+        junk
+    }
+
+    /// Return the position right after the ID3v2 tag, including its footer.
+    fn locate_audio_start(&mut self) -> io::Result<u64> {
+        if self.pheader.identifier == "ID3" {
+            let footer = if self.pheader.flags.Footer { 10 } else { 0 };
+            return Ok(10 + self.pheader.size as u64 + footer);
+        }
+        if self.file_size < 10 {
+            return Ok(0);
+        }
+        let mut buffer_reader = ID3BufferReader::new(&mut self.source)?;
+        let buffer = buffer_reader.read_protocol_header_buffer()?;
+        if buffer[0..3] != *b"ID3" {
+            return Ok(0);
+        }
+        let footer = if Flag::new(buffer[5]).Footer { 10 } else { 0 };
+        Ok(10 + get_size(buffer[6..].to_vec(), &Version::V4) as u64 + footer)
+    }
+
+    /// Return the position of the first trailing tag (APEv2, Lyrics3 or ID3v1) that has been parsed.
+    fn locate_audio_end(&mut self) -> io::Result<u64> {
+        let mut end = self.locate_id3v1()?;
+        if let Lyrics3Version::V1 | Lyrics3Version::V2 = self.lyrics3.version {
+            if self.lyrics3.position + self.lyrics3.size as u64 == end {
+                end = self.lyrics3.position;
+            }
+        }
+        if self.ape.footer.size > 0 && self.ape.position + self.ape.size() as u64 == end {
+            end = self.ape.position;
+        }
+        Ok(end)
+    }
+
+    /// Start parsing id3v2.
+    ///
+    /// Return `Error::NotThisFormat` if the file does not start with an ID3v2 tag.
+    pub fn parse_id3v2(&mut self) -> Result<(), Error> {
+        let mut buffer_reader = ID3BufferReader::new(&mut self.source)?;
+
+        let mut buffer: Buffer;
+
+        buffer = buffer_reader
+            .read_protocol_header_buffer()
+            .map_err(|e| Error::from_io(e, 0, "ID3v2 header"))?;
+        self.pheader = parse_protocol_header(&buffer).map_err(|e| e.at(0, "ID3v2 header"))?;
         let mut start: u32 = 0;
         if self.pheader.flags.ExtendedHeader {
-            buffer = buffer_reader.read_extended_header_buffer()?;
+            buffer = buffer_reader
+                .read_extended_header_buffer()
+                .map_err(|e| Error::from_io(e, 10, "extended header"))?;
             let mut ext = parse_extended_header(&buffer, &self.pheader.major_version);
-            ext.payload = buffer_reader.skip(ext.len.into())?;
+            ext.payload = buffer_reader
+                .skip(ext.len.into())
+                .map_err(|e| Error::from_io(e, 10, "extended header"))?;
             self.eheader = ext;
             start += 10 + self.eheader.len as u32;
         }
 
+        let mut pending: Vec<(u64, FrameHeader, Buffer)> = Vec::new();
+        if let Err(e) = self.read_frames(start, &mut pending) {
+            // the following frames cannot be located
+            tolerate(self.parse_mode, &mut self.warnings, e)?;
+        }
+        self.decode_frames(pending)
+    }
+
+    /// Read the header and payload of each frame until the padding or the end of the tag.
+    fn read_frames(
+        &mut self,
+        mut start: u32,
+        pending: &mut Vec<(u64, FrameHeader, Buffer)>,
+    ) -> Result<(), Error> {
+        let mut buffer_reader = ID3BufferReader::new(&mut self.source)?;
+        let mut buffer: Buffer;
+        // the tag starts after the 10-byte protocol header
+        buffer_reader.seek_to(10 + start as u64)?;
         while start < self.pheader.size {
-            buffer = buffer_reader.read_frame_header_buffer()?;
-            match parse_frame_header(&buffer, &self.pheader.major_version) {
-                Ok(v) => {
+            let offset = 10 + start as u64;
+            buffer = buffer_reader
+                .read_frame_header_buffer()
+                .map_err(|e| Error::from_io(e, offset, "frame header"))?;
+            match plan_frame(&buffer, &self.pheader, start, self.picture_loading)? {
+                FrameRead::Payload(header, length) => {
+                    let context = header.identifier.to_string();
                     // 这里可以优化为异步
                     // 去解析而不等待返回值，接着获取下一个FrameHeader继续解析
-                    buffer = buffer_reader.read_frame_payload_buffer(v.size)?;
-                    // 优化为异步
-                    match parse_frame_payload(&buffer, &v) {
-                        Ok(v) => {
-                            self.push(v)?;
-                        }
-                        Err(e) => println!("{:?}", e),
-                    }
-                    start += 10 + v.size;
-                }
-                Err(e) => match e {
-                    ID3Error::IsPadding => {
-                        self.padding_size = self.pheader.size - start;
-                        if self.pheader.flags.Footer {
-                            // 将reader的指针定位到footer第一个字节
-                            buffer_reader.seek_to(10 + self.pheader.size as u64)?;
-                            buffer = buffer_reader.read_footer_buffer()?;
-                            self.footer = parse_footer_buffer(&buffer).unwrap();
-                        }
-                        return Ok(());
-                    }
-                    ID3Error::Unimplement(id, skip) => {
-                        let buf = buffer_reader.skip(skip)?;
-                        start += 10 + skip;
-                        println!(
-                            "unimplement: {{
-identifier: {},
-raw: {:?}",
-                            id, buf
+                    buffer = buffer_reader
+                        .read_frame_payload_buffer(length)
+                        .map_err(|e| Error::from_io(e, offset, &context))?;
+                    let rest = frame_rest(&buffer, &header, length, offset, self.file_size)?;
+                    if rest.read > 0 {
+                        buffer.append(
+                            &mut buffer_reader
+                                .read_frame_payload_buffer(rest.read)
+                                .map_err(|e| Error::from_io(e, offset, &context))?,
                         );
                     }
-                    ID3Error::UnknownError(s) => {
-                        println!("{s}");
-                        println!("The parser is stopped");
-                        return Ok(());
+                    if let Some(next) = rest.seek_to {
+                        buffer_reader.seek_to(next)?;
                     }
-                },
+                    start += 10 + header.size;
+                    pending.push((offset, header, buffer));
+                }
+                FrameRead::Skip(id, skip) => {
+                    buffer_reader
+                        .skip(skip)
+                        .map_err(|e| Error::from_io(e, offset, &id))?;
+                    start += 10 + skip;
+                    record_skipped_frame(
+                        &mut self.warnings,
+                        &mut self.frame_order,
+                        offset,
+                        id,
+                        skip,
+                    );
+                }
+                FrameRead::Padding(padding_size, footer) => {
+                    self.padding_size = padding_size;
+                    if let Some(offset) = footer {
+                        // 将reader的指针定位到footer第一个字节
+                        buffer_reader.seek_to(offset)?;
+                        buffer = buffer_reader
+                            .read_footer_buffer()
+                            .map_err(|e| Error::from_io(e, offset, "footer"))?;
+                        self.footer =
+                            parse_footer_buffer(&buffer).map_err(|e| e.at(offset, "footer"))?;
+                    }
+                    break;
+                }
             }
         }
         Ok(())
     }
 
-    /// As the method says.
+    /// Read the image bytes of the `index`-th APIC frame,
     ///
-    /// In addition, its own data will be cleared.
-    pub fn change_target(&mut self, new_fp: T) {
-        self.fp = new_fp;
-        self.hm.clear();
-        self.frames.clear()
+    /// from the file if it was parsed with `PictureLoading::Lazy`.
+    ///
+    /// Return an error if it is a link, see `picture_content`.
+    pub fn load_picture(&mut self, index: usize) -> io::Result<Vec<u8>> {
+        match self.picture_content(index)? {
+            PictureContent::Image(data) => Ok(data),
+            PictureContent::Url(_) => Err(linked_picture(index)),
+        }
+    }
+
+    /// Same as `load_picture`, but the image bytes are read as they are consumed.
+    pub fn picture_reader(&mut self, index: usize) -> io::Result<Box<dyn Read + '_>> {
+        let frame = self.apic(index)?;
+        if frame.mime_type().as_deref() == Some(LINK_MIME_TYPE) {
+            return Err(linked_picture(index));
+        }
+        let (location, raw) = (frame.location(), apic_data(frame));
+        picture_reader(&mut self.source, location, raw)
+    }
+
+    /// Read the `index`-th APIC frame, which holds the image bytes or the URL of the image.
+    pub fn picture_content(&mut self, index: usize) -> io::Result<PictureContent> {
+        let frame = self.apic(index)?;
+        let mime = frame.mime_type().unwrap_or_default();
+        let (location, raw) = (frame.location(), apic_data(frame));
+        let data = read_picture(&mut self.source, location, raw)?;
+        Ok(PictureContent::new(&mime, data))
+    }
+
+    /// Recognize the image bytes of the `index`-th APIC frame, see `ImageInfo`.
+    ///
+    /// Return None if they are not a known image format or the frame is a link.
+    pub fn image_info(&mut self, index: usize) -> io::Result<Option<ImageInfo>> {
+        let mime = self.apic(index)?.mime_type().unwrap_or_default();
+        match self.picture_content(index)? {
+            PictureContent::Image(data) => Ok(ImageInfo::detect(&data, &mime)),
+            PictureContent::Url(_) => Ok(None),
+        }
     }
 
     /// Write APIC frame's raw to the current directory named with filename.jpg like 云烟成雨.jpg if there is only one APIC frame.
     ///
     /// Unless, add a underline followd by a number after the filename start with the second one, like 云烟成雨_1.jpg.
-    pub fn write_image(&self) -> io::Result<()> {
-        let mut t = self.fp.as_ref().to_owned();
+    ///
+    /// The extension is the one of the image format, jpg if it is not recognized. Links are skipped.
+    pub fn write_image(&mut self) -> io::Result<()> {
+        let mut t = require_path(&self.path)?.to_owned();
         t.set_extension("");
-        if let Some(index) = self.hm.get("APIC") {
-            for (index, d) in self.frames[*index].iter().enumerate() {
-                let pic_type = PicType::from(d.raw().pop().unwrap()).to_string();
-                let mut fname: OsString = OsString::from(&t);
-                fname.push("_mp3_");
-                fname.push(pic_type);
-                if index > 0 {
-                    fname.push("_");
-                    fname.push(index.to_string());
-                }
-                fname.push(".jpg");
-                fs::write(fname, d.raw())?
+        let count = self.hm.get("APIC").map_or(0, |i| self.frames[*i].len());
+        for index in 0..count {
+            let PictureContent::Image(data) = self.picture_content(index)? else {
+                continue;
+            };
+            // the last byte of raw is the picture type
+            let raw_pic_type = self.apic(index)?.raw().pop().unwrap_or_default();
+            let mut fname: OsString = OsString::from(&t);
+            fname.push("_mp3_");
+            fname.push(PicType::from(raw_pic_type).to_string());
+            if index > 0 {
+                fname.push("_");
+                fname.push(index.to_string());
             }
-        } else {
-            println!("NO APIC");
+            fname.push(".");
+            fname.push(image_extension(&data));
+            fs::write(fname, data)?
         }
         Ok(())
     }
+
+    /// Switch to another source, which has no path.
+    ///
+    /// In addition, its own data will be cleared.
+    pub fn change_source(&mut self, mut new_source: R) -> io::Result<()> {
+        self.file_size = new_source.seek(SeekFrom::End(0))?;
+        self.source = new_source;
+        self.path = None;
+        self.hm.clear();
+        self.frames.clear();
+        self.frame_order.clear();
+        self.pheader = ProtocolHeader::default();
+        self.eheader = ExtendedHeader::default();
+        self.padding_size = u32::default();
+        self.footer = Footer::default();
+        self.id3v1 = ID3v1::default();
+        self.lyrics3 = Lyrics3::default();
+        self.ape = ApeTag::default();
+        self.audio_properties = AudioProperties::default();
+        self.stream_scan = StreamScan::default();
+        self.id3v2_charset = None;
+        self.id3v1_charset = None;
+        self.warnings.clear();
+        Ok(())
+    }
 }
 
 // https://xiph.org/flac/format.html#metadata_block_vorbis_comment
 #[derive(Debug)]
-pub struct FlacParser<T>
-where
-    T: AsRef<Path>,
-{
-    fp: T,
+pub struct FlacParser<R = File> {
+    source: R,
+    /// path of the file, required by `write_image`
+    path: Option<PathBuf>,
     pub stream_info: BlockStreamInfo,
     pub application: BlockApplication,
     pub seek_table: BlockSeekTable,
@@ -292,72 +1076,401 @@ where
     pub picture: Vec<BlockPicture>,
     pub cue_sheet: BlockCueSheet,
     pub padding_length: u32,
+    /// position of "fLaC", after the ID3v2 tag that some files start with
+    pub marker_offset: u64,
+    /// position of the first audio frame, right after the last metadata block
+    pub audio_offset: u64,
+    /// every metadata block in file order, see `blocks_in_order`
+    block_order: Vec<(Span, BlockType)>,
+    /// see `comments_in_order`
+    comment_order: Vec<(Span, String, String)>,
+    /// problems skipped by `parse`, see `set_parse_mode`
+    pub warnings: Vec<Warning>,
+    parse_mode: ParseMode,
+    picture_loading: PictureLoading,
 }
 
-#[allow(dead_code)]
-#[allow(unused_assignments)]
-#[allow(unused_variables)]
-impl<T> FlacParser<T>
-where
-    T: AsRef<Path>,
-{
+impl FlacParser<File> {
     /// Create a new FlacParser
-    pub fn new(fp: T) -> io::Result<Self> {
-        Ok(FlacParser {
-            fp,
-            stream_info: BlockStreamInfo::default(),
-            application: BlockApplication::default(),
-            seek_table: BlockSeekTable::default(),
-            vorbis_comment: BlockVorbisComment::default(),
-            picture: Vec::default(),
-            cue_sheet: BlockCueSheet::default(),
-            padding_length: u32::default(),
-        })
+    pub fn new<T: AsRef<Path>>(fp: T) -> io::Result<Self> {
+        let mut parser = FlacParser::from_reader(File::open(&fp)?)?;
+        parser.path = Some(fp.as_ref().to_owned());
+        Ok(parser)
     }
 
-    /// Start parsing flac.
-    pub fn parse(&mut self) -> io::Result<()> {
-        let mut buffer_reader = FlacBufferReader::new(&self.fp)?;
-        let mut buffer: Buffer;
-        buffer = buffer_reader.read_block_header()?;
-        if parse_flac_marker(buffer).is_err() {
-            println!("not include flac header");
-            return Ok(());
+    /// As the method says.
+    ///
+    /// In addition, its own data will be cleared.
+    pub fn change_target<T: AsRef<Path>>(&mut self, new_fp: T) -> io::Result<()> {
+        self.change_source(File::open(&new_fp)?);
+        self.path = Some(new_fp.as_ref().to_owned());
+        Ok(())
+    }
+
+    /// Write the Vorbis comments and `self.picture` back to the file, then parse it again.
+    ///
+    /// Call `parse` first. The other blocks are copied as they are, comments skipped by `parse` are lost.
+    ///
+    /// The PADDING blocks absorb the change in size so that the audio frames do not move.
+    ///
+    /// If they are too small, the file is written again with 4096 bytes of padding to "<file>.tmp",
+    ///
+    /// which then replaces the original.
+    pub fn save(&mut self) -> Result<(), Error> {
+        let path = require_path(&self.path)?.to_owned();
+        if self.audio_offset == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the file must be parsed before it is saved",
+            )
+            .into());
         }
-        let mut block_header: BlockHeader = BlockHeader::default();
-        while !block_header.is_last {
-            buffer = buffer_reader.read_block_header()?;
-            block_header = parse_block_header(buffer)?;
-            buffer = buffer_reader.read_block_data_buffer(block_header.length)?;
-            match block_header.block_type {
-                BlockType::STREAMINFO => {
-                    self.stream_info = parse_stream_info_block(buffer)?;
-                }
-                BlockType::PADDING => {
-                    self.padding_length = block_header.length;
-                    println!("here is padding, is last = {}", block_header.is_last);
-                }
-                BlockType::APPLICATION => {
-                    self.application = parse_block_application(buffer)?;
-                }
-                BlockType::SEEKTABLE => {
-                    self.seek_table = parse_block_seektable(buffer)?;
+        let blocks = self.metadata_blocks()?;
+        let start = self.marker_offset + 4;
+        let used: u64 = blocks.iter().map(|(_, data)| 4 + data.len() as u64).sum();
+        match (self.audio_offset - start).checked_sub(used) {
+            Some(0) => self.write_metadata(&path, &serialize_blocks(&blocks, None)?)?,
+            Some(room) if (4..=4 + MAX_BLOCK_LENGTH as u64).contains(&room) => {
+                let metadata = serialize_blocks(&blocks, Some(room as u32 - 4))?;
+                self.write_metadata(&path, &metadata)?;
+            }
+            _ => {
+                let metadata = serialize_blocks(&blocks, Some(DEFAULT_PADDING))?;
+                self.rewrite(&path, &metadata)?;
+            }
+        }
+        self.change_target(&path)?;
+        self.parse()
+    }
+
+    /// The metadata blocks to write by `save`, as (type number, data), without PADDING.
+    ///
+    /// The pictures take the place of the first PICTURE block, or follow the other blocks.
+    fn metadata_blocks(&mut self) -> io::Result<Vec<(u8, Buffer)>> {
+        let mut picture_blocks = Some(self.picture_blocks()?);
+        let comments = self
+            .comment_order
+            .iter()
+            .map(|(_, key, value)| (key.as_str(), value.as_str()));
+        let mut comment_block = Some(vorbis_comment_bytes(&self.vorbis_comment.encoder, comments));
+        let vorbis_comment = block_type_number(BlockType::VORBISCOMMENT);
+        let mut blocks: Vec<(u8, Buffer)> = Vec::new();
+        for (span, block_type) in self.block_order.clone() {
+            match block_type {
+                BlockType::PADDING => continue,
+                BlockType::PICTURE => {
+                    if let Some(mut pictures) = picture_blocks.take() {
+                        blocks.append(&mut pictures);
+                    }
                 }
+                // `parse` keeps the comments of the last one only
                 BlockType::VORBISCOMMENT => {
-                    self.vorbis_comment = parse_vorbis_comment(buffer)?;
+                    if let Some(data) = comment_block.take() {
+                        blocks.push((vorbis_comment, data));
+                    }
                 }
-                BlockType::CUESHEET => {
-                    self.cue_sheet = parse_block_cue_sheet(buffer)?;
+                _ => {
+                    let length = span.header_size as usize + span.payload_size as usize;
+                    let mut buffer = vec![0; length];
+                    self.source.seek(SeekFrom::Start(span.offset))?;
+                    self.source.read_exact(&mut buffer)?;
+                    let data = buffer.split_off(4);
+                    blocks.push((buffer[0] & 0x7F, data));
                 }
-                BlockType::PICTURE => {
-                    self.picture.push(parse_block_picture(buffer)?);
+            }
+        }
+        if let Some(mut pictures) = picture_blocks {
+            blocks.append(&mut pictures);
+        }
+        if let Some(data) = comment_block.filter(|_| !self.comment_order.is_empty()) {
+            // right after STREAMINFO
+            blocks.insert(blocks.len().min(1), (vorbis_comment, data));
+        }
+        Ok(blocks)
+    }
+
+    /// Serialize `self.picture`, reading the image bytes of lazy pictures from the file.
+    fn picture_blocks(&mut self) -> io::Result<Vec<(u8, Buffer)>> {
+        let number = block_type_number(BlockType::PICTURE);
+        let mut blocks: Vec<(u8, Buffer)> = Vec::new();
+        for picture in self.picture.iter() {
+            let data = read_picture(&mut self.source, picture.location, picture.data.clone())?;
+            blocks.push((number, block_picture_bytes(picture, &data)));
+        }
+        Ok(blocks)
+    }
+
+    /// Overwrite the metadata blocks with `metadata`, which has the same length.
+    fn write_metadata(&mut self, path: &Path, metadata: &[u8]) -> io::Result<()> {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.seek(SeekFrom::Start(self.marker_offset + 4))?;
+        file.write_all(metadata)?;
+        file.sync_all()
+    }
+
+    /// Write the file again with `metadata` in place of the metadata blocks,
+    ///
+    /// to a temporary file that replaces `path` once it is complete.
+    fn rewrite(&mut self, path: &Path, metadata: &[u8]) -> io::Result<()> {
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        let rst = self.copy_with_metadata(path, &temp, metadata);
+        if rst.is_err() {
+            let _ = fs::remove_file(&temp);
+            return rst;
+        }
+        fs::rename(&temp, path)
+    }
+
+    fn copy_with_metadata(&mut self, path: &Path, temp: &Path, metadata: &[u8]) -> io::Result<()> {
+        let mut file = File::create(temp)?;
+        // the ID3v2 tag if any, and "fLaC"
+        self.source.seek(SeekFrom::Start(0))?;
+        io::copy(
+            &mut (&mut self.source).take(self.marker_offset + 4),
+            &mut file,
+        )?;
+        file.write_all(metadata)?;
+        self.source.seek(SeekFrom::Start(self.audio_offset))?;
+        io::copy(&mut self.source, &mut file)?;
+        file.set_permissions(fs::metadata(path)?.permissions())?;
+        file.sync_all()
+    }
+}
+
+impl<'a> FlacParser<Cursor<&'a [u8]>> {
+    /// Create a new FlacParser over an in-memory file, such as an upload.
+    pub fn from_bytes(bytes: &'a [u8]) -> io::Result<Self> {
+        FlacParser::from_reader(Cursor::new(bytes))
+    }
+}
+
+impl<R> FlacParser<R> {
+    /// Create a FlacParser of `source` without reading anything.
+    fn with_source(source: R) -> Self {
+        FlacParser {
+            source,
+            path: None,
+            stream_info: BlockStreamInfo::default(),
+            application: BlockApplication::default(),
+            seek_table: BlockSeekTable::default(),
+            vorbis_comment: BlockVorbisComment::default(),
+            picture: Vec::default(),
+            cue_sheet: BlockCueSheet::default(),
+            padding_length: u32::default(),
+            marker_offset: u64::default(),
+            audio_offset: u64::default(),
+            block_order: Vec::default(),
+            comment_order: Vec::default(),
+            warnings: Vec::default(),
+            parse_mode: ParseMode::default(),
+            picture_loading: PictureLoading::default(),
+        }
+    }
+
+    /// In lenient mode, `parse` skips broken blocks and records them in `self.warnings`.
+    pub fn set_parse_mode(&mut self, mode: ParseMode) {
+        self.parse_mode = mode;
+    }
+
+    /// With `PictureLoading::Lazy`, `parse` skips the image bytes of PICTURE blocks,
+    ///
+    /// read them with `load_picture` or `picture_reader`.
+    pub fn set_picture_loading(&mut self, loading: PictureLoading) {
+        self.picture_loading = loading;
+    }
+
+    /// Parse the data of a metadata block located at `position`.
+    fn parse_block(
+        &mut self,
+        block_header: &BlockHeader,
+        buffer: Buffer,
+        position: u64,
+    ) -> Result<(), Error> {
+        let context = format!("{:?}", block_header.block_type);
+        let span = Span::new(position, 4, block_header.length);
+        self.block_order.push((span, block_header.block_type));
+        let rst = match block_header.block_type {
+            BlockType::STREAMINFO => parse_stream_info_block(buffer).map(|v| self.stream_info = v),
+            BlockType::PADDING => {
+                self.padding_length = block_header.length;
+                Ok(())
+            }
+            BlockType::APPLICATION => parse_block_application(buffer).map(|v| self.application = v),
+            BlockType::SEEKTABLE => parse_block_seektable(buffer).map(|v| self.seek_table = v),
+            BlockType::VORBISCOMMENT => {
+                let mut invalid: Vec<FlacError> = Vec::new();
+                self.comment_order.clear();
+                let order = &mut self.comment_order;
+                let rst = parse_vorbis_comment(buffer, &mut invalid, order, position + 4)
+                    .map(|v| self.vorbis_comment = v);
+                for e in invalid {
+                    tolerate(
+                        self.parse_mode,
+                        &mut self.warnings,
+                        e.at(position, &context),
+                    )?;
                 }
-                BlockType::INVALID => todo!(),
+                rst
+            }
+            BlockType::CUESHEET => parse_block_cue_sheet(buffer).map(|v| self.cue_sheet = v),
+            BlockType::PICTURE if self.picture_loading == PictureLoading::Lazy => {
+                parse_lazy_block_picture(&buffer, position + 4, block_header.length)
+                    .map(|v| self.picture.push(v))
+            }
+            BlockType::PICTURE => parse_block_picture(buffer).map(|v| self.picture.push(v)),
+            BlockType::RESERVED => Ok(()),
+            BlockType::INVALID => Err(FlacError::InvalidBlock(
+                "block type 127 is invalid".to_string(),
+            )),
+        };
+        if let Err(e) = rst {
+            tolerate(
+                self.parse_mode,
+                &mut self.warnings,
+                e.at(position, &context),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Return every metadata block in file order, with its position and size.
+    pub fn blocks_in_order(&self) -> impl Iterator<Item = (Span, BlockType)> + '_ {
+        self.block_order.iter().copied()
+    }
+
+    /// Return the Vorbis comments in file order as (key, value), with their position and size.
+    ///
+    /// The key keeps its case, comments skipped by `parse` are not included.
+    ///
+    /// Comments changed by `set_comment` or `add_comment` have an empty span until `save`.
+    pub fn comments_in_order(&self) -> impl Iterator<Item = (Span, &str, &str)> + '_ {
+        self.comment_order
+            .iter()
+            .map(|(span, key, value)| (*span, key.as_str(), value.as_str()))
+    }
+
+    /// Replace the values of `key` with `value`, or add it after the other comments.
+    ///
+    /// The key is case insensitive, the first comment of `key` keeps its place and its case.
+    ///
+    /// Call `save` to write the change.
+    pub fn set_comment(&mut self, key: &str, value: &str) -> io::Result<()> {
+        check_comment_key(key)?;
+        let upper_key = key.to_uppercase();
+        let mut found = false;
+        self.comment_order.retain_mut(|(span, k, v)| {
+            if k.to_uppercase() != upper_key {
+                return true;
+            }
+            if found {
+                return false;
             }
+            found = true;
+            *span = Span::default();
+            *v = value.to_string();
+            true
+        });
+        if !found {
+            let comment = (Span::default(), key.to_string(), value.to_string());
+            self.comment_order.push(comment);
         }
+        self.index_comments();
+        Ok(())
+    }
+
+    /// Add a value to `key` after the other comments, keeping the existing values.
+    ///
+    /// Call `save` to write the change.
+    pub fn add_comment(&mut self, key: &str, value: &str) -> io::Result<()> {
+        check_comment_key(key)?;
+        let comment = (Span::default(), key.to_string(), value.to_string());
+        self.comment_order.push(comment);
+        self.index_comments();
+        Ok(())
+    }
+
+    /// Remove every value of `key`, case insensitive.
+    ///
+    /// Return whether there was any. Call `save` to write the change.
+    pub fn remove_comment(&mut self, key: &str) -> bool {
+        let upper_key = key.to_uppercase();
+        let count = self.comment_order.len();
+        self.comment_order
+            .retain(|(_, k, _)| k.to_uppercase() != upper_key);
+        self.index_comments();
+        count != self.comment_order.len()
+    }
+
+    /// Add a picture of the image bytes `data` after the other pictures,
+    ///
+    /// its MIME type, dimensions, depth and number of colours are read from `data`.
+    ///
+    /// Call `save` to write the change.
+    pub fn add_picture(
+        &mut self,
+        pic_type: FlacPicType,
+        description: &str,
+        data: Vec<u8>,
+    ) -> io::Result<()> {
+        let picture = BlockPicture::from_image(pic_type, description, data)?;
+        self.picture.push(picture);
+        Ok(())
+    }
+
+    /// Same as `add_picture`, with the content of the image file `path`.
+    pub fn add_picture_file<T: AsRef<Path>>(
+        &mut self,
+        pic_type: FlacPicType,
+        description: &str,
+        path: T,
+    ) -> io::Result<()> {
+        self.add_picture(pic_type, description, fs::read(path)?)
+    }
+
+    /// Replace the front covers with a picture of the image bytes `data`, at the place of the first one.
+    ///
+    /// Call `save` to write the change.
+    pub fn set_front_cover(&mut self, data: Vec<u8>) -> io::Result<()> {
+        let picture = BlockPicture::from_image(FlacPicType::FrontCover, "", data)?;
+        let index = self
+            .picture
+            .iter()
+            .position(|v| v.pic_type == FlacPicType::FrontCover)
+            .unwrap_or(self.picture.len());
+        self.remove_pictures(&FlacPicType::FrontCover);
+        self.picture.insert(index, picture);
         Ok(())
     }
 
+    /// Remove the pictures of `pic_type`, return how many there were.
+    ///
+    /// Call `save` to write the change.
+    pub fn remove_pictures(&mut self, pic_type: &FlacPicType) -> usize {
+        let count = self.picture.len();
+        self.picture.retain(|v| v.pic_type != *pic_type);
+        count - self.picture.len()
+    }
+
+    /// Rebuild `self.vorbis_comment` from `self.comment_order` after a change.
+    fn index_comments(&mut self) {
+        let vorbis_comment = &mut self.vorbis_comment;
+        vorbis_comment.hm.clear();
+        vorbis_comment.comment.clear();
+        for (_, key, value) in self.comment_order.iter() {
+            let upper_key = key.to_uppercase();
+            if let Some(index) = vorbis_comment.hm.get(&upper_key) {
+                vorbis_comment.comment[*index].push(value.clone());
+            } else {
+                vorbis_comment
+                    .hm
+                    .insert(upper_key, vorbis_comment.comment.len());
+                vorbis_comment.comment.push(vec![value.clone()]);
+            }
+        }
+    }
+
     /// Get vorbis comment according to query.
     ///
     /// Return a Vec<String> wrapped in an Option.
@@ -384,33 +1497,17 @@ where
         Ok((key_vec, value_vec))
     }
 
-    /// Write image(s) to disk.
-    pub fn write_image(&mut self) -> io::Result<()> {
-        let mut t = self.fp.as_ref().to_owned();
-        t.set_extension("");
-        let mut index = 0;
-        while index < self.picture.len() {
-            let mut raw_data = self.picture[index].data.clone();
-            let pic_type = PicType::from(raw_data.pop().unwrap()).to_string();
-            let mut fname: OsString = OsString::from(&t);
-            fname.push("_flac_");
-            fname.push(pic_type);
-            if index > 0 {
-                fname.push("_");
-                fname.push(index.to_string());
-            }
-            fname.push(".jpg");
-            fs::write(fname, raw_data)?;
-            index += 1;
-        }
-        Ok(())
+    /// Collect the Vorbis comments under standard keys, see `Tag`.
+    pub fn tag(&self) -> Tag {
+        Tag::from_vorbis_comments(&self.vorbis_comment.hm, &self.vorbis_comment.comment)
     }
 
-    /// As the method says.
+    /// Switch to another source, which has no path.
     ///
     /// In addition, its own data will be cleared.
-    pub fn change_target(&mut self, new_fp: T) {
-        self.fp = new_fp;
+    pub fn change_source(&mut self, new_source: R) {
+        self.source = new_source;
+        self.path = None;
         self.application = BlockApplication::default();
         self.stream_info = BlockStreamInfo::default();
         self.seek_table = BlockSeekTable::default();
@@ -418,128 +1515,371 @@ where
         self.picture.clear();
         self.cue_sheet = BlockCueSheet::default();
         self.padding_length = u32::default();
+        self.marker_offset = u64::default();
+        self.audio_offset = u64::default();
+        self.block_order.clear();
+        self.comment_order.clear();
+        self.warnings.clear();
     }
 }
 
-pub struct OggParser<T>
+#[allow(dead_code)]
+#[allow(unused_assignments)]
+#[allow(unused_variables)]
+impl<R> FlacParser<R>
 where
-    T: AsRef<Path>,
+    R: Read + Seek,
 {
-    fp: T,
+    /// Create a new FlacParser over any seekable source, such as an object-store stream.
+    ///
+    /// `write_image` needs a path and fails on such a parser.
+    pub fn from_reader(source: R) -> io::Result<Self> {
+        Ok(FlacParser::with_source(source))
+    }
+
+    /// Start parsing flac.
+    ///
+    /// Return `Error::NotThisFormat` if the file does not start with "fLaC",
+    ///
+    /// or with an ID3v2 tag followed by "fLaC".
+    pub fn parse(&mut self) -> Result<(), Error> {
+        self.marker_offset = 0;
+        self.block_order.clear();
+        let mut buffer_reader = FlacBufferReader::new(&mut self.source)?;
+        let mut buffer: Buffer;
+        buffer = buffer_reader
+            .read_block_header()
+            .map_err(|e| Error::from_io(e, 0, "flac header"))?;
+        // some taggers put an ID3v2 tag before "fLaC"
+        if buffer.starts_with(b"ID3") {
+            if let Ok(mut rest) = buffer_reader.read_block_data_buffer(6) {
+                buffer.append(&mut rest);
+            }
+            if let Some(length) = id3v2_length(&buffer) {
+                self.marker_offset = length;
+                buffer_reader.seek_to(length)?;
+                buffer = buffer_reader
+                    .read_block_header()
+                    .map_err(|e| Error::from_io(e, length, "flac header"))?;
+            }
+        }
+        parse_flac_marker(buffer).map_err(|e| e.at(self.marker_offset, "flac header"))?;
+        let mut block_header: BlockHeader = BlockHeader::default();
+        // "fLaC"
+        let mut position: u64 = self.marker_offset + 4;
+        while !block_header.is_last {
+            let mut buffer_reader = FlacBufferReader::new(&mut self.source)?;
+            buffer_reader.seek_to(position)?;
+            match buffer_reader.read_block_header() {
+                Ok(v) => buffer = v,
+                Err(e) => {
+                    let e = Error::from_io(e, position, "block header");
+                    // the following blocks cannot be located
+                    return tolerate(self.parse_mode, &mut self.warnings, e);
+                }
+            }
+            block_header =
+                parse_block_header(buffer).map_err(|e| e.at(position, "block header"))?;
+            let length = block_read_length(&block_header, self.picture_loading);
+            let mut rst = buffer_reader.read_block_data_buffer(length);
+            if let Ok(v) = &mut rst {
+                // the description is longer than the head
+                if length < block_header.length
+                    && !matches!(picture_head_length(v), Some(n) if n <= v.len())
+                {
+                    match buffer_reader.read_block_data_buffer(block_header.length - length) {
+                        Ok(mut rest) => v.append(&mut rest),
+                        Err(e) => rst = Err(e),
+                    }
+                }
+            }
+            match rst {
+                Ok(v) => buffer = v,
+                Err(e) => {
+                    let context = format!("{:?}", block_header.block_type);
+                    let e = Error::from_io(e, position, &context);
+                    return tolerate(self.parse_mode, &mut self.warnings, e);
+                }
+            }
+            self.parse_block(&block_header, buffer, position)?;
+            position += 4 + block_header.length as u64;
+        }
+        self.audio_offset = position;
+        Ok(())
+    }
+
+    /// Hash the audio frames only, skipping the metadata blocks and a trailing ID3v1 tag,
+    ///
+    /// so that two copies of the same file with different tags have the same digest.
+    pub fn audio_hash(&mut self) -> io::Result<AudioHash> {
+        if self.audio_offset == 0 {
+            self.parse()?;
+        }
+        if self.audio_offset == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not include flac header",
+            ));
+        }
+        let end = self.locate_audio_end()?;
+        hash_range(
+            &mut self.source,
+            self.audio_offset,
+            end.max(self.audio_offset),
+        )
+    }
+
+    /// Return the position of a trailing ID3v1 tag, or the file size if there is none.
+    fn locate_audio_end(&mut self) -> io::Result<u64> {
+        let mut end = self.source.seek(SeekFrom::End(0))?;
+        if end >= self.audio_offset + 128 {
+            let mut tag = [0u8; 3];
+            self.source.seek(SeekFrom::Start(end - 128))?;
+            self.source.read_exact(&mut tag)?;
+            if tag == *b"TAG" {
+                end -= 128;
+            }
+        }
+        Ok(end)
+    }
+
+    /// Map the regions of the file: ID3v2 tag, "fLaC", metadata blocks, padding, audio and ID3v1.
+    ///
+    /// The file is parsed first if it was not.
+    pub fn layout(&mut self) -> io::Result<Layout> {
+        if self.audio_offset == 0 {
+            self.parse()?;
+        }
+        let file_size = self.source.seek(SeekFrom::End(0))?;
+        let mut regions: Vec<Region> = Vec::new();
+        if self.marker_offset > 0 {
+            regions.push(Region::new(RegionKind::ID3v2Tag, 0, self.marker_offset));
+        }
+        regions.push(Region::new(RegionKind::FlacMarker, self.marker_offset, 4));
+        for (span, block_type) in self.block_order.iter() {
+            let kind = match block_type {
+                BlockType::PADDING => RegionKind::Padding,
+                v => RegionKind::MetadataBlock(*v),
+            };
+            let length = span.end() - span.offset;
+            regions.push(Region::new(kind, span.offset, length));
+        }
+        let end = self.locate_audio_end()?;
+        let audio_length = end.saturating_sub(self.audio_offset);
+        regions.push(Region::new(
+            RegionKind::Audio,
+            self.audio_offset,
+            audio_length,
+        ));
+        if end < file_size {
+            regions.push(Region::new(RegionKind::ID3v1, end, file_size - end));
+        }
+        Ok(Layout::new(file_size, regions))
+    }
+
+    /// Read the image bytes of the `index`-th picture,
+    ///
+    /// from the file if it was parsed with `PictureLoading::Lazy`.
+    ///
+    /// Return an error if it is a link, see `picture_content`.
+    pub fn load_picture(&mut self, index: usize) -> io::Result<Vec<u8>> {
+        match self.picture_content(index)? {
+            PictureContent::Image(data) => Ok(data),
+            PictureContent::Url(_) => Err(linked_picture(index)),
+        }
+    }
+
+    /// Same as `load_picture`, but the image bytes are read as they are consumed.
+    pub fn picture_reader(&mut self, index: usize) -> io::Result<Box<dyn Read + '_>> {
+        let picture = self.picture.get(index).ok_or_else(|| no_picture(index))?;
+        if picture.mime == LINK_MIME_TYPE {
+            return Err(linked_picture(index));
+        }
+        picture_reader(&mut self.source, picture.location, picture.data.clone())
+    }
+
+    /// Read the `index`-th picture, which holds the image bytes or the URL of the image.
+    pub fn picture_content(&mut self, index: usize) -> io::Result<PictureContent> {
+        let picture = self.picture.get(index).ok_or_else(|| no_picture(index))?;
+        let mime = picture.mime.clone();
+        let data = read_picture(&mut self.source, picture.location, picture.data.clone())?;
+        Ok(PictureContent::new(&mime, data))
+    }
+
+    /// Recognize the image bytes of the `index`-th picture, see `ImageInfo`.
+    ///
+    /// Return None if they are not a known image format or the picture is a link.
+    pub fn image_info(&mut self, index: usize) -> io::Result<Option<ImageInfo>> {
+        match self.picture_content(index)? {
+            PictureContent::Image(data) => Ok(ImageInfo::detect(&data, &self.picture[index].mime)),
+            PictureContent::Url(_) => Ok(None),
+        }
+    }
+
+    /// Write image(s) to disk.
+    ///
+    /// The extension is the one of the image format, jpg if it is not recognized. Links are skipped.
+    pub fn write_image(&mut self) -> io::Result<()> {
+        let mut t = require_path(&self.path)?.to_owned();
+        t.set_extension("");
+        for index in 0..self.picture.len() {
+            let PictureContent::Image(data) = self.picture_content(index)? else {
+                continue;
+            };
+            let mut fname: OsString = OsString::from(&t);
+            fname.push("_flac_");
+            fname.push(self.picture[index].pic_type.to_string());
+            if index > 0 {
+                fname.push("_");
+                fname.push(index.to_string());
+            }
+            fname.push(".");
+            fname.push(image_extension(&data));
+            fs::write(fname, data)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct OggParser<R = File> {
+    source: R,
     pub audio_channels: u8,
     pub audio_sample_rate: u32,
     pub vorbis_comment: CommentBody,
     // pub vorbis_comment: Box<dyn FlagTrait>,
     comment_buffer: Vec<u8>,
+    /// see `comments_in_order`
+    comment_order: Vec<(Span, String, String)>,
+    /// problems skipped by `parse`, see `set_parse_mode`
+    pub warnings: Vec<Warning>,
+    parse_mode: ParseMode,
 }
-impl<T> OggParser<T>
-where
-    T: AsRef<Path>,
-{
-    pub fn new(fp: T) -> Self {
+impl OggParser<File> {
+    pub fn new<T: AsRef<Path>>(fp: T) -> io::Result<Self> {
+        Ok(OggParser::from_reader(File::open(fp)?))
+    }
+
+    /// As the method says.
+    ///
+    /// In addition, its own data will be cleared.
+    pub fn change_target<T: AsRef<Path>>(&mut self, new_fp: T) -> io::Result<()> {
+        self.change_source(File::open(new_fp)?);
+        Ok(())
+    }
+}
+
+impl<'a> OggParser<Cursor<&'a [u8]>> {
+    /// Create a new OggParser over an in-memory file, such as an upload.
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        OggParser::from_reader(Cursor::new(bytes))
+    }
+}
+
+impl<R> OggParser<R> {
+    /// Create an OggParser of `source` without reading anything.
+    fn with_source(source: R) -> Self {
         OggParser {
-            fp,
+            source,
             audio_channels: u8::default(),
             audio_sample_rate: u32::default(),
             // vorbis_comment: Box::new(OggVorbisComment::default()),
             vorbis_comment: CommentBody::default(),
             comment_buffer: Vec::default(),
+            comment_order: Vec::default(),
+            warnings: Vec::default(),
+            parse_mode: ParseMode::default(),
         }
     }
-    pub fn parse(&mut self) -> io::Result<()> {
-        let mut buffer_reader = OggBufferReader::new(&self.fp)?;
-        let mut comment_header = CommentHeader::default();
-        // comment缓存
-        // let mut comment_buffer: Vec<u8> = Vec::default();
-        while !comment_header.end {
-            let mut page_header = PageHeader::default();
-            page_header.capture_pattern = String::from_utf8(buffer_reader.read_buffer(4)?).unwrap();
-            page_header.structure_version = buffer_reader.read_one()?;
-            let header_type_flag = buffer_reader.read_one()?;
-            page_header.new_packet = (header_type_flag & 0x01) == 0;
-            page_header.bos = (header_type_flag & 0x02) == 2;
-            page_header.eos = (header_type_flag & 0x04) == 4;
-            page_header.granule_position = buffer_reader.read_buffer(8)?;
-            page_header.serial_number = buffer_reader.read_buffer(4)?;
-            page_header.page_sequence_number = buffer_reader.read_buffer(4)?;
-            page_header.crc_checksum = buffer_reader.read_buffer(4)?;
-            page_header.number_page_segments = buffer_reader.read_one()?;
-            page_header.segment_table =
-                buffer_reader.read_buffer(page_header.number_page_segments as u32)?;
-            let temp = buffer_reader.read_one().unwrap();
-            match temp {
-                0x01 | 0x05 => {
-                    let skip_len: u64 = page_header
-                        .segment_table
-                        .clone()
-                        .into_iter()
-                        .map(|x| x as u64)
-                        .sum();
-                    buffer_reader.skip(skip_len - 1)?;
-                    continue;
-                }
-                _ => {}
-            }
-            let mut packets: Vec<u32> = Vec::new();
-            packets.push(0);
-            // 此页有几个包，包的长度是多少
-            for (i, ele) in page_header.segment_table.into_iter().enumerate() {
-                // 非0xFF
-                if ele != 0xFF {
-                    *packets.last_mut().unwrap() += ele as u32;
-                    if i != page_header.number_page_segments as usize - 1 {
-                        packets.push(0);
-                    }
-                } else {
-                    *packets.last_mut().unwrap() += 0xFF;
-                }
-            }
-            // 第一个包长度-1
-            packets[0] -= 0x01;
-            // 是一个新包，并且有区段，并且第一个字节是0x03
-            if page_header.new_packet && page_header.number_page_segments > 0 && temp == 0x03 {
-                // 将所有数据读入
-                for len in packets {
-                    self.comment_buffer
-                        .append(buffer_reader.read_buffer(len)?.as_mut());
-                }
-                // 进入下一页
-            } else {
-                // 是继承包
-                self.comment_buffer.push(temp);
-                for len in packets {
-                    self.comment_buffer
-                        .append(buffer_reader.read_buffer(len)?.as_mut());
-                    while *self.comment_buffer.last().unwrap() == 0x00 {
-                        self.comment_buffer.pop();
-                    }
-                    if *self.comment_buffer.last().unwrap() == 0x01 {
-                        self.comment_buffer.pop();
-                        comment_header.end = true;
-                        break;
-                    }
-                }
-            }
-        }
+
+    /// In lenient mode, `parse` skips broken comments and records them in `self.warnings`.
+    pub fn set_parse_mode(&mut self, mode: ParseMode) {
+        self.parse_mode = mode;
+    }
+
+    /// Switch to another source.
+    ///
+    /// In addition, its own data will be cleared.
+    pub fn change_source(&mut self, new_source: R) {
+        self.source = new_source;
+        self.audio_channels = u8::default();
+        self.audio_sample_rate = u32::default();
+        self.vorbis_comment = CommentBody::default();
+        self.comment_buffer.clear();
+        self.comment_order.clear();
+        self.warnings.clear();
+    }
+
+    /// Parse the comment packet collected in `self.comment_buffer`.
+    fn parse_comment_packet(
+        &mut self,
+        mut comment_header: CommentHeader,
+        comment_offset: u64,
+    ) -> Result<(), Error> {
+        let invalid_encoding = |_| Error::InvalidEncoding {
+            offset: comment_offset,
+            context: "comment header".to_string(),
+        };
         comment_header.header_type = HeaderType::CommentHeader;
-        comment_header.packet_pattern = String::from_utf8(self.vec_reader(0, 6).to_vec()).unwrap();
-        let company_info_length: usize =
-            parse_4_bytes_with_little_endian(self.vec_reader(6, 4)) as usize;
-        comment_header.company_info =
-            String::from_utf8(self.vec_reader(10, company_info_length).to_vec()).unwrap();
+        self.comment_order.clear();
+        let rst = self
+            .vec_reader(0, 6, comment_offset)
+            .and_then(|v| String::from_utf8(v.to_vec()).map_err(invalid_encoding));
+        comment_header.packet_pattern = match rst {
+            Ok(v) => v,
+            Err(e) => return tolerate(self.parse_mode, &mut self.warnings, e),
+        };
+        let rst = self.vec_reader(6, 4, comment_offset);
+        let company_info_length: usize = match rst {
+            Ok(v) => parse_4_bytes_with_little_endian(v) as usize,
+            Err(e) => return tolerate(self.parse_mode, &mut self.warnings, e),
+        };
+        let rst = self
+            .vec_reader(10, company_info_length, comment_offset)
+            .and_then(|v| String::from_utf8(v.to_vec()).map_err(invalid_encoding));
+        comment_header.company_info = match rst {
+            Ok(v) => v,
+            Err(e) => return tolerate(self.parse_mode, &mut self.warnings, e),
+        };
         let mut start: usize = 10 + company_info_length + 4;
-        while self.comment_buffer.len() - start > 1 {
-            let comment_i_length: usize =
-                parse_4_bytes_with_little_endian(self.vec_reader(start, 4)) as usize;
+        while start + 1 < self.comment_buffer.len() {
+            let rst = self.vec_reader(start, 4, comment_offset);
+            let comment_i_length: usize = match rst {
+                Ok(v) => parse_4_bytes_with_little_endian(v) as usize,
+                Err(e) => return tolerate(self.parse_mode, &mut self.warnings, e),
+            };
+            // the packet type 0x03 is not in `comment_buffer`
+            let span = Span::new(start as u64 + 1, 4, comment_i_length as u32);
             start += 4;
-            let comment_i_content =
-                String::from_utf8(self.vec_reader(start, comment_i_length).to_vec()).unwrap();
+            let rst = self
+                .vec_reader(start, comment_i_length, comment_offset)
+                .map(|v| v.to_vec());
+            let comment_i_raw = match rst {
+                Ok(v) => v,
+                // the following comments cannot be located
+                Err(e) => return tolerate(self.parse_mode, &mut self.warnings, e),
+            };
             start += comment_i_length;
+            let comment_i_content = match String::from_utf8(comment_i_raw) {
+                Ok(v) => v,
+                Err(e) => {
+                    tolerate(self.parse_mode, &mut self.warnings, invalid_encoding(e))?;
+                    continue;
+                }
+            };
             // store(&mut self.vorbis_comment, comment_i_content);
             // self.vorbis_comment.store(comment_i_content);
-            let kv: Vec<&str> = comment_i_content.split('=').collect();
-            let comment_key = kv[0];
-            let comment_value: String = kv[1].to_owned();
+            let Some((comment_key, comment_value)) = comment_i_content.split_once('=') else {
+                let e = Error::InvalidFrame {
+                    offset: comment_offset,
+                    context: "comment header".to_string(),
+                    reason: format!("comment {comment_i_content:?} has no '='"),
+                };
+                tolerate(self.parse_mode, &mut self.warnings, e)?;
+                continue;
+            };
+            let comment_value: String = comment_value.to_owned();
+            self.comment_order
+                .push((span, comment_key.to_owned(), comment_value.clone()));
             if let Some(index) = self.vorbis_comment.hm.get(comment_key) {
                 self.vorbis_comment.comment[*index].push(comment_value);
             } else {
@@ -553,9 +1893,15 @@ where
         }
         Ok(())
     }
+
     // return buffer
-    fn vec_reader(&self, start: usize, length: usize) -> &[u8] {
-        &self.comment_buffer[start..start + length]
+    fn vec_reader(&self, start: usize, length: usize, offset: u64) -> Result<&[u8], Error> {
+        self.comment_buffer
+            .get(start..start + length)
+            .ok_or_else(|| Error::Truncated {
+                offset,
+                context: "comment header".to_string(),
+            })
     }
     pub fn get(&mut self, query: &str) -> Option<Vec<String>> {
         let upper_query = query.to_uppercase();
@@ -573,4 +1919,328 @@ where
         }
         Ok((key_vec, value_vec))
     }
+
+    /// Return the Vorbis comments in file order as (key, value), with their position and size.
+    ///
+    /// The positions are in the comment packet, which may span several pages.
+    pub fn comments_in_order(&self) -> impl Iterator<Item = (Span, &str, &str)> + '_ {
+        self.comment_order
+            .iter()
+            .map(|(span, key, value)| (*span, key.as_str(), value.as_str()))
+    }
+
+    /// Collect the Vorbis comments under standard keys, see `Tag`.
+    pub fn tag(&self) -> Tag {
+        Tag::from_vorbis_comments(&self.vorbis_comment.hm, &self.vorbis_comment.comment)
+    }
+}
+
+impl<R> OggParser<R>
+where
+    R: Read + Seek,
+{
+    /// Create a new OggParser over any seekable source, such as an object-store stream.
+    pub fn from_reader(source: R) -> Self {
+        OggParser::with_source(source)
+    }
+
+    /// Start parsing the comment header of Ogg Vorbis.
+    ///
+    /// Return `Error::NotThisFormat` if the file does not start with "OggS".
+    pub fn parse(&mut self) -> Result<(), Error> {
+        let mut comment_header = CommentHeader::default();
+        let comment_offset = match self.read_comment_packet(&mut comment_header) {
+            Ok(offset) => offset,
+            // the comments cannot be located
+            Err(e) => return tolerate(self.parse_mode, &mut self.warnings, e),
+        };
+        self.parse_comment_packet(comment_header, comment_offset)
+    }
+
+    /// Read the pages until the comment packet is complete in `self.comment_buffer`.
+    ///
+    /// Return the position of the page where the comment packet starts.
+    fn read_comment_packet(&mut self, comment_header: &mut CommentHeader) -> Result<u64, Error> {
+        let mut buffer_reader = OggBufferReader::new(&mut self.source)?;
+        // where the comment packet starts, for the errors
+        let mut comment_offset: u64 = 0;
+        while !comment_header.end {
+            let offset = buffer_reader.position()?;
+            let truncated = |e: io::Error| Error::from_io(e, offset, "page");
+            let mut buffer = buffer_reader.read_buffer(4).map_err(truncated)?;
+            check_capture_pattern(&buffer, offset)?;
+            buffer.append(&mut buffer_reader.read_buffer(23).map_err(truncated)?);
+            let segment_table = buffer_reader
+                .read_buffer(buffer[26] as u32)
+                .map_err(truncated)?;
+            let body_length: u32 = segment_table.iter().map(|&x| x as u32).sum();
+            let body = buffer_reader.read_buffer(body_length).map_err(truncated)?;
+            let page_header = parse_page_header(&buffer, segment_table);
+            if collect_comment_packet(page_header, &body, &mut self.comment_buffer, comment_header)
+            {
+                comment_offset = offset;
+            }
+        }
+        Ok(comment_offset)
+    }
+
+    /// Hash the audio packets only, skipping the header packets (identification, comment, setup)
+    ///
+    /// and all page headers, since their sequence numbers and CRCs change when the comments are resized.
+    ///
+    /// The returned range spans the audio pages, including the page headers that are not hashed.
+    pub fn audio_hash(&mut self) -> io::Result<AudioHash> {
+        let mut sha = Sha256::default();
+        let (start, end) = self.walk_pages(|body| sha.update(body))?;
+        Ok(AudioHash::new(sha.finalize().into(), start, end))
+    }
+
+    /// Map the regions of the file: the pages of the header packets and the audio pages.
+    pub fn layout(&mut self) -> io::Result<Layout> {
+        let file_size = self.source.seek(SeekFrom::End(0))?;
+        let (start, end) = self.walk_pages(|_| ())?;
+        let regions = vec![
+            Region::new(RegionKind::OggHeaders, 0, start),
+            Region::new(RegionKind::Audio, start, end - start),
+        ];
+        Ok(Layout::new(file_size, regions))
+    }
+
+    /// Read every page, passing the bodies of the audio pages to `audio`.
+    ///
+    /// Return the position of the first audio page and the position right after the last page.
+    fn walk_pages(&mut self, mut audio: impl FnMut(&[u8])) -> io::Result<(u64, u64)> {
+        let file_size = self.source.seek(SeekFrom::End(0))?;
+        let mut buffer_reader = OggBufferReader::new(&mut self.source)?;
+        let mut position: u64 = 0;
+        let mut start: Option<u64> = None;
+        let mut header_packets: u32 = 0;
+        let mut packets: u32 = 0;
+        while position + 27 <= file_size {
+            let header = buffer_reader.read_buffer(27)?;
+            if header[0..4] != *b"OggS" {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("not include OggS at {}", position),
+                ));
+            }
+            let segment_table = buffer_reader.read_buffer(header[26] as u32)?;
+            let body_length: u32 = segment_table.iter().map(|&x| x as u32).sum();
+            let body = buffer_reader.read_buffer(body_length)?;
+            position += 27 + segment_table.len() as u64 + body_length as u64;
+            if start.is_some() {
+                audio(&body);
+                continue;
+            }
+            if position == 27 + segment_table.len() as u64 + body_length as u64 {
+                header_packets = count_header_packets(&body);
+            }
+            // a packet ends with a segment shorter than 255
+            packets += segment_table.iter().filter(|&&x| x < 0xFF).count() as u32;
+            if packets >= header_packets {
+                start = Some(position);
+            }
+        }
+        Ok((start.unwrap_or(position), position))
+    }
+}
+
+/// Where a frame of `ID3Parser::frames_in_order` is kept.
+enum FrameSlot {
+    /// position in `frames`
+    Parsed(usize, usize),
+    /// identifier and payload of a frame that is not implemented or cannot be decoded
+    Unparsed(String, Buffer),
+}
+
+/// What to read after a frame header, see `plan_frame`.
+enum FrameRead {
+    /// read the first bytes of the payload, fewer than `size` for a lazy APIC frame
+    Payload(FrameHeader, u32),
+    /// skip a frame that is not implemented
+    Skip(String, u32),
+    /// the padding starts here, its size and the position of the footer if there is one
+    Padding(u32, Option<u64>),
+}
+
+/// Decide how to read the frame whose header is `buffer`, located at `start` of the tag.
+fn plan_frame(
+    buffer: &Buffer,
+    pheader: &ProtocolHeader,
+    start: u32,
+    picture_loading: PictureLoading,
+) -> Result<FrameRead, Error> {
+    let offset = 10 + start as u64;
+    match parse_frame_header(buffer, &pheader.major_version) {
+        Ok(v) => {
+            if start as u64 + 10 + v.size as u64 > pheader.size as u64 {
+                let context = v.identifier.to_string();
+                return Err(Error::Truncated { offset, context });
+            }
+            let length = match v.identifier {
+                IDFactory::APIC if picture_loading == PictureLoading::Lazy => {
+                    v.size.min(PICTURE_HEAD_LENGTH)
+                }
+                _ => v.size,
+            };
+            Ok(FrameRead::Payload(v, length))
+        }
+        Err(ID3Error::IsPadding) => {
+            let footer = pheader.flags.Footer.then_some(10 + pheader.size as u64);
+            Ok(FrameRead::Padding(pheader.size - start, footer))
+        }
+        Err(ID3Error::Unimplement(id, skip)) => Ok(FrameRead::Skip(id, skip)),
+        Err(e) => Err(e.at(offset, "frame header")),
+    }
+}
+
+/// Record a frame skipped while reading the tag because it is not implemented.
+fn record_skipped_frame(
+    warnings: &mut Vec<Warning>,
+    frame_order: &mut Vec<(Span, FrameSlot)>,
+    offset: u64,
+    id: String,
+    skip: u32,
+) {
+    warnings.push(Warning::new(offset, &id, "not implemented, skipped"));
+    let span = Span::new(offset, 10, skip);
+    frame_order.push((span, FrameSlot::Unparsed(id, Vec::new())));
+}
+
+/// What is left to read of a frame after the first bytes of its payload, see `frame_rest`.
+struct FrameRest {
+    /// bytes to append to the payload
+    read: u32,
+    /// where the next frame header is, if the reader is not there yet
+    seek_to: Option<u64>,
+}
+
+/// Decide what is left to read of the frame at `offset` once its first `length` bytes are in `payload`.
+///
+/// A lazy APIC frame is read whole if its description is longer than the head.
+fn frame_rest(
+    payload: &Buffer,
+    header: &FrameHeader,
+    length: u32,
+    offset: u64,
+    file_size: u64,
+) -> Result<FrameRest, Error> {
+    if length >= header.size {
+        return Ok(FrameRest {
+            read: 0,
+            seek_to: None,
+        });
+    }
+    let next = offset + 10 + header.size as u64;
+    if next > file_size {
+        let context = header.identifier.to_string();
+        return Err(Error::Truncated { offset, context });
+    }
+    Ok(match APIC_head_length(payload) {
+        Some(_) => FrameRest {
+            read: 0,
+            seek_to: Some(next),
+        },
+        // the description is longer than the head
+        None => FrameRest {
+            read: header.size - length,
+            seek_to: None,
+        },
+    })
+}
+
+/// Number of bytes to read first from the data of a block, see `FlacParser::set_picture_loading`.
+fn block_read_length(block_header: &BlockHeader, picture_loading: PictureLoading) -> u32 {
+    match block_header.block_type {
+        BlockType::PICTURE if picture_loading == PictureLoading::Lazy => {
+            block_header.length.min(PICTURE_HEAD_LENGTH)
+        }
+        _ => block_header.length,
+    }
+}
+
+/// Serialize the metadata blocks written by `FlacParser::save`, followed by `padding` bytes of PADDING.
+///
+/// The last block has its `is_last` flag set.
+fn serialize_blocks(blocks: &[(u8, Buffer)], padding: Option<u32>) -> io::Result<Buffer> {
+    let mut buffer: Buffer = Vec::new();
+    for (index, (number, data)) in blocks.iter().enumerate() {
+        if data.len() > MAX_BLOCK_LENGTH as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("a block of type {number} is longer than {MAX_BLOCK_LENGTH} bytes"),
+            ));
+        }
+        let is_last = padding.is_none() && index + 1 == blocks.len();
+        buffer.extend_from_slice(&block_header_bytes(is_last, *number, data.len() as u32));
+        buffer.extend_from_slice(data);
+    }
+    if let Some(length) = padding {
+        let number = block_type_number(BlockType::PADDING);
+        buffer.extend_from_slice(&block_header_bytes(true, number, length));
+        buffer.resize(buffer.len() + length as usize, 0);
+    }
+    Ok(buffer)
+}
+
+/// The image bytes of an APIC frame, `raw` ends with the picture type.
+fn apic_data(frame: &dyn Tape) -> Buffer {
+    let mut raw = frame.raw();
+    raw.pop();
+    raw
+}
+
+/// Return an error if `key` cannot be the name of a Vorbis comment:
+///
+/// it must not be empty and is made of ASCII 0x20 to 0x7D except '='.
+fn check_comment_key(key: &str) -> io::Result<()> {
+    let valid = |v: &u8| (0x20..=0x7D).contains(v) && *v != b'=';
+    if !key.is_empty() && key.bytes().all(|v| valid(&v)) {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{key:?} is not a valid Vorbis comment name"),
+    ))
+}
+
+/// Return an error if a page does not start with "OggS".
+fn check_capture_pattern(capture_pattern: &[u8], offset: u64) -> Result<(), Error> {
+    if capture_pattern == b"OggS" {
+        return Ok(());
+    }
+    if offset == 0 {
+        return Err(Error::NotThisFormat { expected: "Ogg" });
+    }
+    Err(Error::InvalidFrame {
+        offset,
+        context: "page".to_string(),
+        reason: "not include OggS".to_string(),
+    })
+}
+
+/// Return the path of a parser created from a file,
+///
+/// or an error if it was created from a reader or bytes.
+fn require_path(path: &Option<PathBuf>) -> io::Result<&Path> {
+    path.as_deref().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the parser was not created from a file path",
+        )
+    })
+}
+
+/// Number of header packets according to the first packet of the logical stream.
+fn count_header_packets(first_packet: &[u8]) -> u32 {
+    if first_packet.starts_with(b"OpusHead") {
+        2
+    } else if first_packet.starts_with(b"\x7FFLAC") && first_packet.len() >= 9 {
+        1 + first_packet[7] as u32 * 0x100 + first_packet[8] as u32
+    } else if first_packet.starts_with(b"Speex   ") && first_packet.len() >= 72 {
+        2 + parse_4_bytes_with_little_endian(&first_packet[68..72])
+    } else {
+        // Vorbis
+        3
+    }
 }
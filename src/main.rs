@@ -0,0 +1,457 @@
+//! Inspect, extract and edit the tags of mp3, flac and ogg files.
+//!
+//! Built with the `cli` feature.
+
+use std::error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use music_metadata::{
+    open, probe, AudioFile, ID3Parser, ID3v1, ImageInfo, PictureContent, PictureData, StandardKey,
+    Tag,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
+
+const USAGE: &str = "\
+usage: music-metadata <command> [options] <file or glob>...
+
+commands:
+    dump [--json] [--pictures]      print the tag, or everything that is parsed as JSON
+    get <key>                       print the values of a field
+    set <key>=<value>               replace a field
+    remove <key>                    remove a field
+    pictures extract [--out <dir>]  write the embedded pictures next to the file, or to <dir>
+    pictures add <image>            embed a front cover
+    pictures remove                 remove the embedded pictures
+    lyrics export [--out <dir>]     write the lyrics next to the file, or to <dir>
+    verify                          parse everything and check the MPEG audio frames
+    layout                          print where the tags, padding and audio are in the file
+
+<key> is a Vorbis comment name such as title or tracknumber, an ID3v2 frame such as TIT2,
+or a field without standard key such as TXXX:MOOD.
+
+set and remove write the Vorbis comments of flac files, and the ID3v1 tag of mp3 files,
+they fail if the ID3v2 tag of the mp3 file holds the field since it cannot be written.
+Only the pictures of flac files can be written, pictures add replaces their front cover.
+
+The exit status is 1 if a file cannot be parsed or changed, and 2 on a usage error.";
+
+enum Command {
+    Dump { json: bool, pictures: PictureData },
+    Get(String),
+    Set(String, String),
+    Remove(String),
+    PicturesExtract(Option<PathBuf>),
+    PicturesAdd(PathBuf),
+    PicturesRemove,
+    LyricsExport(Option<PathBuf>),
+    Verify,
+    Layout,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|v| v == "-h" || v == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let (command, patterns) = match parse_args(&args) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut ok = true;
+    let files = expand(&patterns, &mut ok);
+    // with several files, each line starts with the path
+    let prefix = files.len() > 1;
+    let mut dumps: Vec<serde_json::Value> = Vec::new();
+    for path in files.iter() {
+        let rst = match &command {
+            Command::Dump {
+                json: true,
+                pictures,
+            } => dump_json(path, *pictures, &mut dumps),
+            Command::Dump { json: false, .. } => dump(path),
+            Command::Get(key) => get(path, key, prefix),
+            Command::Set(key, value) => edit(path, key, Some(value)),
+            Command::Remove(key) => edit(path, key, None),
+            Command::PicturesExtract(out) => extract_pictures(path, out.as_deref()),
+            Command::PicturesAdd(image) => edit_pictures(path, Some(image)),
+            Command::PicturesRemove => edit_pictures(path, None),
+            Command::LyricsExport(out) => export_lyrics(path, out.as_deref()),
+            Command::Verify => verify(path),
+            Command::Layout => layout(path),
+        };
+        if let Err(e) = rst {
+            eprintln!("{}: {e}", path.display());
+            ok = false;
+        }
+    }
+    if let Command::Dump { json: true, .. } = command {
+        match serde_json::to_string_pretty(&dumps) {
+            Ok(v) => println!("{v}"),
+            Err(e) => {
+                eprintln!("{e}");
+                ok = false;
+            }
+        }
+    }
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Split the arguments into the command and the files.
+fn parse_args(args: &[String]) -> std::result::Result<(Command, Vec<String>), String> {
+    let mut json = false;
+    let mut pictures = PictureData::Omit;
+    let mut out: Option<PathBuf> = None;
+    let mut words: Vec<String> = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--pictures" => pictures = PictureData::Base64,
+            "--out" => match iter.next() {
+                Some(v) => out = Some(PathBuf::from(v)),
+                None => return Err("--out needs a directory".to_string()),
+            },
+            v if v.starts_with("--") => return Err(format!("unknown option {v}")),
+            v => words.push(v.to_string()),
+        }
+    }
+    let mut words = words.into_iter();
+    let mut next = |what: &str| words.next().ok_or_else(|| format!("missing {what}"));
+    let command = match next("command")?.as_str() {
+        "dump" => Command::Dump { json, pictures },
+        "get" => Command::Get(next("key")?),
+        "set" => {
+            let pair = next("key=value")?;
+            match pair.split_once('=') {
+                Some((key, value)) => Command::Set(key.to_string(), value.to_string()),
+                None => return Err(format!("{pair} is not key=value")),
+            }
+        }
+        "remove" => Command::Remove(next("key")?),
+        "pictures" => match next("pictures command")?.as_str() {
+            "extract" => Command::PicturesExtract(out),
+            "add" => Command::PicturesAdd(PathBuf::from(next("image")?)),
+            "remove" => Command::PicturesRemove,
+            v => return Err(format!("unknown pictures command {v}")),
+        },
+        "lyrics" => match next("lyrics command")?.as_str() {
+            "export" => Command::LyricsExport(out),
+            v => return Err(format!("unknown lyrics command {v}")),
+        },
+        "verify" => Command::Verify,
+        "layout" => Command::Layout,
+        v => return Err(format!("unknown command {v}")),
+    };
+    let patterns: Vec<String> = words.collect();
+    if patterns.is_empty() {
+        return Err("no file given".to_string());
+    }
+    Ok((command, patterns))
+}
+
+/// Expand the glob patterns, for shells that do not.
+///
+/// A pattern that matches nothing is an error.
+fn expand(patterns: &[String], ok: &mut bool) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for pattern in patterns {
+        if !pattern.contains(['*', '?', '[']) {
+            files.push(PathBuf::from(pattern));
+            continue;
+        }
+        let matched: Vec<PathBuf> = match glob::glob(pattern) {
+            Ok(paths) => paths
+                .filter_map(|v| v.ok())
+                .filter(|v| v.is_file())
+                .collect(),
+            Err(e) => {
+                eprintln!("{pattern}: {e}");
+                *ok = false;
+                continue;
+            }
+        };
+        if matched.is_empty() {
+            eprintln!("{pattern}: no such file");
+            *ok = false;
+        }
+        files.extend(matched);
+    }
+    files
+}
+
+fn dump(path: &Path) -> Result<()> {
+    let probe = probe(path)?;
+    let file = open(path)?;
+    println!("{}", path.display());
+    if let Some(probe) = probe {
+        println!("{:?}, {:?}", probe.format, probe.codec);
+    }
+    println!("{}", file.tag());
+    for (index, (content, image)) in pictures(file)?.iter().enumerate() {
+        match (content, image) {
+            (PictureContent::Url(url), _) => println!("picture {index}: link to {url}"),
+            (_, Some(v)) => println!(
+                "picture {index}: {:?} {}x{}, {} bits per pixel{}",
+                v.format,
+                v.width,
+                v.height,
+                v.depth,
+                if v.mime_matches {
+                    ""
+                } else {
+                    ", wrong MIME type"
+                }
+            ),
+            (_, None) => println!("picture {index}: unknown format"),
+        }
+    }
+    Ok(())
+}
+
+/// Read the pictures of `file` and recognize their image bytes.
+fn pictures(file: AudioFile) -> Result<Vec<(PictureContent, Option<ImageInfo>)>> {
+    let mut pictures = Vec::new();
+    match file {
+        AudioFile::Mp3(mut parser) => {
+            let count = parser.get_raw("APIC").map_or(0, |v| v.len());
+            for index in 0..count {
+                pictures.push((parser.picture_content(index)?, parser.image_info(index)?));
+            }
+        }
+        AudioFile::Flac(mut parser) => {
+            for index in 0..parser.picture.len() {
+                pictures.push((parser.picture_content(index)?, parser.image_info(index)?));
+            }
+        }
+        AudioFile::Ogg(_) => {}
+    }
+    Ok(pictures)
+}
+
+fn dump_json(path: &Path, pictures: PictureData, dumps: &mut Vec<serde_json::Value>) -> Result<()> {
+    let dump = open(path)?.dump(pictures);
+    dumps.push(serde_json::json!({ "path": path, "dump": dump }));
+    Ok(())
+}
+
+/// Look up `key` as a standard key first, then as a native field.
+fn lookup<'a>(tag: &'a Tag, key: &str) -> Option<&'a [String]> {
+    match standard_key(key) {
+        Some(key) => tag.get(key),
+        None => tag.get_native(key),
+    }
+}
+
+fn standard_key(key: &str) -> Option<StandardKey> {
+    StandardKey::from_vorbis(key).or_else(|| StandardKey::from_id3(&key.to_uppercase()))
+}
+
+fn get(path: &Path, key: &str, prefix: bool) -> Result<()> {
+    let tag = open(path)?.tag();
+    for value in lookup(&tag, key).unwrap_or_default() {
+        if prefix {
+            println!("{}: {value}", path.display());
+        } else {
+            println!("{value}");
+        }
+    }
+    Ok(())
+}
+
+/// Set `key` to `value`, or remove it if `value` is None.
+fn edit(path: &Path, key: &str, value: Option<&str>) -> Result<()> {
+    match open(path)? {
+        AudioFile::Mp3(mut parser) => edit_id3v1(&mut parser, key, value.unwrap_or_default()),
+        AudioFile::Flac(mut parser) => {
+            match value {
+                Some(value) => parser.set_comment(key, value)?,
+                None if !parser.remove_comment(key) => return Ok(()),
+                None => {}
+            }
+            parser.save()?;
+            Ok(())
+        }
+        file => Err(format!("writing {:?} files is not supported", file.format()).into()),
+    }
+}
+
+/// Write `value` to the ID3v1 field of `key`, an empty value clears it.
+fn edit_id3v1(parser: &mut ID3Parser, key: &str, value: &str) -> Result<()> {
+    let unsupported = || {
+        format!("{key} cannot be written, only title, artist, album, date, comment, tracknumber and genre")
+    };
+    let name = key;
+    let key = standard_key(key).ok_or_else(unsupported)?;
+    // ID3v1 only fills the fields that ID3v2 left empty, the change would not be seen
+    if parser.id3v2_tag().get(key).is_some() {
+        return Err(format!("{name} is held by the ID3v2 tag, which cannot be written").into());
+    }
+    let id3v1 = &mut parser.id3v1;
+    if id3v1.header != b"TAG" {
+        *id3v1 = ID3v1::default();
+    }
+    let charset = id3v1.charset;
+    match key {
+        StandardKey::Title => id3v1.set_title(value, &charset),
+        StandardKey::Artist => id3v1.set_artist(value, &charset),
+        StandardKey::Album => id3v1.set_album(value, &charset),
+        StandardKey::Date => id3v1.set_year(value),
+        StandardKey::Comment => id3v1.set_comment(value, &charset),
+        StandardKey::TrackNumber => {
+            let number = value.split('/').next().unwrap_or_default();
+            match number {
+                "" => id3v1.set_track(0),
+                v => id3v1.set_track(
+                    v.parse()
+                        .map_err(|_| format!("{v} is not a track number"))?,
+                ),
+            }
+        }
+        StandardKey::Genre if value.is_empty() => id3v1.genre = 0xFF,
+        StandardKey::Genre => {
+            if !id3v1.set_genre(value) {
+                return Err(format!("{value} is not an ID3v1 genre").into());
+            }
+        }
+        _ => return Err(unsupported().into()),
+    }
+    parser.write_id3v1()?;
+    Ok(())
+}
+
+/// Return the directory of the files written for `path`, and their name without extension.
+fn output(path: &Path, out: Option<&Path>) -> (PathBuf, String) {
+    let dir = match out {
+        Some(v) => v.to_owned(),
+        None => path.parent().unwrap_or(Path::new("")).to_owned(),
+    };
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    (dir, stem)
+}
+
+fn extract_pictures(path: &Path, out: Option<&Path>) -> Result<()> {
+    let file = open(path)?;
+    // pictures of song.mp3 and song.flac do not overwrite each other
+    let format = format!("{:?}", file.format()).to_lowercase();
+    let (dir, stem) = output(path, out);
+    for (index, (content, image)) in pictures(file)?.into_iter().enumerate() {
+        let picture = match content {
+            PictureContent::Image(v) => v,
+            PictureContent::Url(url) => {
+                println!("{}: picture {index} is a link to {url}", path.display());
+                continue;
+            }
+        };
+        let extension = image.map_or("jpg", |v| v.format.extension());
+        let file = dir.join(format!("{stem}_{format}_{index}.{extension}"));
+        fs::write(&file, picture)?;
+        println!("{}", file.display());
+    }
+    Ok(())
+}
+
+/// Embed `image` as the front cover, or remove the pictures if it is None.
+fn edit_pictures(path: &Path, image: Option<&PathBuf>) -> Result<()> {
+    let data = match image {
+        Some(image) => Some(fs::read(image).map_err(|e| format!("{}: {e}", image.display()))?),
+        None => None,
+    };
+    let mut parser = match open(path)? {
+        AudioFile::Flac(parser) => parser,
+        file => {
+            return Err(format!(
+                "writing pictures to {:?} files is not supported",
+                file.format()
+            )
+            .into())
+        }
+    };
+    match data {
+        Some(data) => parser.set_front_cover(data)?,
+        None if parser.picture.is_empty() => return Ok(()),
+        None => parser.picture.clear(),
+    }
+    parser.save()?;
+    Ok(())
+}
+
+fn export_lyrics(path: &Path, out: Option<&Path>) -> Result<()> {
+    let mut lyrics: Vec<(&str, String)> = Vec::new();
+    let file = open(path)?;
+    let tag = file.tag();
+    for v in tag.get(StandardKey::Lyrics).unwrap_or_default() {
+        lyrics.push(("txt", v.clone()));
+    }
+    if let AudioFile::Mp3(mut parser) = file {
+        parser.parse_lyrics3()?;
+        // Lyrics3 keeps the [mm:ss] timestamps
+        let extension = match parser.lyrics3.synced_lyrics.is_empty() {
+            true => "txt",
+            false => "lrc",
+        };
+        if !parser.lyrics3.lyrics.is_empty() {
+            lyrics.push((extension, parser.lyrics3.lyrics.clone()));
+        }
+    }
+    if lyrics.is_empty() {
+        println!("{}: no lyrics", path.display());
+        return Ok(());
+    }
+    let (dir, stem) = output(path, out);
+    for (index, (extension, text)) in lyrics.iter().enumerate() {
+        let name = match index {
+            0 => format!("{stem}.{extension}"),
+            i => format!("{stem}_{i}.{extension}"),
+        };
+        let file = dir.join(name);
+        fs::write(&file, text)?;
+        println!("{}", file.display());
+    }
+    Ok(())
+}
+
+/// Parse every tag strictly, and walk the MPEG frames of mp3 files.
+fn verify(path: &Path) -> Result<()> {
+    let file = open(path)?;
+    if let AudioFile::Mp3(mut parser) = file {
+        parser.parse_lyrics3()?;
+        parser.parse_ape()?;
+        parser.scan_audio_stream()?;
+        let scan = &parser.stream_scan;
+        if !scan.is_ok() {
+            return Err(format!(
+                "{} frames, {} CRC errors, {} lost syncs, {} junk regions, truncated: {}",
+                scan.frame_count,
+                scan.crc_errors.len(),
+                scan.lost_sync.len(),
+                scan.junk.len(),
+                scan.truncated_frame.is_some()
+            )
+            .into());
+        }
+    }
+    println!("{}: ok", path.display());
+    Ok(())
+}
+
+/// Print the regions of the file, from start to end.
+fn layout(path: &Path) -> Result<()> {
+    let mut file = open(path)?;
+    println!("{}: {}", path.display(), file.layout()?);
+    Ok(())
+}
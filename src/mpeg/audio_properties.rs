@@ -0,0 +1,152 @@
+use std::{fmt::Display, time::Duration};
+
+use super::{
+    frame_header::MpegFrameHeader,
+    vbr_header::{VbriHeader, XingHeader},
+};
+
+/// Properties of the MPEG audio stream, taken from the first frame.
+///
+/// If the first frame carries a Xing, Info or VBRI header, the frame count is exact,
+///
+/// otherwise it is estimated assuming CBR.
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioProperties {
+    pub header: MpegFrameHeader,
+    /// absolute position of the first frame
+    pub first_frame_offset: u64,
+    /// bytes from the first frame to the trailing tags
+    pub audio_size: u64,
+    /// kbps
+    pub bitrate: u32,
+    pub sample_rate: u32,
+    pub channels: u8,
+    pub frame_count: u64,
+    pub duration: Duration,
+    /// a Xing or VBRI header is present, "Info" means CBR
+    pub is_vbr: bool,
+    /// samples per channel after removing the encoder delay and padding, if known
+    pub total_samples: Option<u64>,
+    pub xing: Option<XingHeader>,
+    pub vbri: Option<VbriHeader>,
+}
+
+impl AudioProperties {
+    pub fn new(
+        header: MpegFrameHeader,
+        first_frame_offset: u64,
+        audio_size: u64,
+        xing: Option<XingHeader>,
+        vbri: Option<VbriHeader>,
+    ) -> Self {
+        let sample_rate = header.sample_rate;
+        let channels = header.channels();
+        let samples_per_frame = header.samples_per_frame() as u64;
+        let mut bitrate = header.bitrate;
+        let mut frame_count = match header.frame_length() {
+            0 => 0,
+            length => audio_size / length as u64,
+        };
+        let mut duration = if bitrate == 0 {
+            Duration::default()
+        } else {
+            // bits / kbps = ms
+            Duration::from_millis(audio_size * 8 / bitrate as u64)
+        };
+        let mut is_vbr = false;
+        let mut total_samples = None;
+        let (frames, bytes, delay_padding) = match (&xing, &vbri) {
+            (Some(x), _) => {
+                is_vbr = !x.is_info;
+                let delay_padding = x
+                    .lame
+                    .as_ref()
+                    .map(|l| l.encoder_delay as u64 + l.encoder_padding as u64);
+                (x.frames, x.bytes, delay_padding)
+            }
+            (None, Some(v)) => {
+                is_vbr = true;
+                (Some(v.frames), Some(v.bytes), Some(v.delay as u64))
+            }
+            (None, None) => (None, None, None),
+        };
+        if let Some(frames) = frames.filter(|_| sample_rate > 0) {
+            frame_count = frames as u64;
+            let samples = frame_count * samples_per_frame;
+            let samples = match delay_padding {
+                Some(v) if v < samples => {
+                    total_samples = Some(samples - v);
+                    samples - v
+                }
+                _ => samples,
+            };
+            duration = Duration::from_secs_f64(samples as f64 / sample_rate as f64);
+            let bytes = bytes.map(|b| b as u64).unwrap_or(audio_size);
+            if !duration.is_zero() {
+                bitrate = (bytes as f64 * 8.0 / duration.as_secs_f64() / 1000.0).round() as u32;
+            }
+        }
+        AudioProperties {
+            header,
+            first_frame_offset,
+            audio_size,
+            bitrate,
+            sample_rate,
+            channels,
+            frame_count,
+            duration,
+            is_vbr,
+            total_samples,
+            xing,
+            vbri,
+        }
+    }
+}
+
+impl Display for AudioProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "
+AudioProperties {{
+    version: {},
+    layer: {},
+    bitrate: {} kbps,
+    sample_rate: {} Hz,
+    channel_mode: {:?},
+    channels: {},
+    emphasis: {:?},
+    copyright: {},
+    original: {},
+    first_frame_offset: {},
+    audio_size: {} Bytes,
+    frame_count: {},
+    duration: {:?},
+    is_vbr: {},
+    total_samples: {:?}
+}}",
+            self.header.version,
+            self.header.layer,
+            self.bitrate,
+            self.sample_rate,
+            self.header.channel_mode,
+            self.channels,
+            self.header.emphasis,
+            self.header.copyright,
+            self.header.original,
+            self.first_frame_offset,
+            self.audio_size,
+            self.frame_count,
+            self.duration,
+            self.is_vbr,
+            self.total_samples
+        )?;
+        if let Some(xing) = &self.xing {
+            write!(f, "{xing}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,471 @@
+use std::io::{self, Read};
+use std::time::Duration;
+
+use crate::util::parse_4_bytes_with_big_endian;
+
+use super::{
+    error::MpegError,
+    frame_header::{ChannelMode, Emphasis, Layer, MpegFrameHeader, MpegVersion},
+    stream_scan::{JunkRegion, StreamScan},
+    vbr_header::{LameHeader, VbriHeader, XingHeader},
+};
+
+/// kbps, indexed by the 4-bit bitrate index, 0 means free format
+const BITRATE_V1_L1: [u32; 15] = [
+    0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+];
+const BITRATE_V1_L2: [u32; 15] = [
+    0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+];
+const BITRATE_V1_L3: [u32; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const BITRATE_V2_L1: [u32; 15] = [
+    0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+];
+const BITRATE_V2_L2_L3: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+pub fn parse_mpeg_frame_header(buffer: &[u8]) -> Result<MpegFrameHeader, MpegError> {
+    if buffer.len() < 4 || buffer[0] != 0xFF || buffer[1] & 0xE0 != 0xE0 {
+        return Err(MpegError::NoSync);
+    }
+    let version = match (buffer[1] & 0x18) >> 3 {
+        0b00 => MpegVersion::Mpeg25,
+        0b10 => MpegVersion::Mpeg2,
+        0b11 => MpegVersion::Mpeg1,
+        _ => return Err(MpegError::InvalidHeader),
+    };
+    let layer = match (buffer[1] & 0x06) >> 1 {
+        0b01 => Layer::Layer3,
+        0b10 => Layer::Layer2,
+        0b11 => Layer::Layer1,
+        _ => return Err(MpegError::InvalidHeader),
+    };
+    let protected = buffer[1] & 0x01 == 0;
+    let bitrate_index = ((buffer[2] & 0xF0) >> 4) as usize;
+    if bitrate_index == 0x0F {
+        return Err(MpegError::InvalidHeader);
+    }
+    let bitrate = match (&version, &layer) {
+        (MpegVersion::Mpeg1, Layer::Layer1) => BITRATE_V1_L1[bitrate_index],
+        (MpegVersion::Mpeg1, Layer::Layer2) => BITRATE_V1_L2[bitrate_index],
+        (MpegVersion::Mpeg1, Layer::Layer3) => BITRATE_V1_L3[bitrate_index],
+        (_, Layer::Layer1) => BITRATE_V2_L1[bitrate_index],
+        (_, _) => BITRATE_V2_L2_L3[bitrate_index],
+    };
+    let sample_rate_index = (buffer[2] & 0x0C) >> 2;
+    if sample_rate_index == 0b11 {
+        return Err(MpegError::InvalidHeader);
+    }
+    let sample_rate = [44100, 48000, 32000][sample_rate_index as usize]
+        / match version {
+            MpegVersion::Mpeg1 => 1,
+            MpegVersion::Mpeg2 => 2,
+            MpegVersion::Mpeg25 => 4,
+        };
+    let channel_mode = match (buffer[3] & 0xC0) >> 6 {
+        0b00 => ChannelMode::Stereo,
+        0b01 => ChannelMode::JointStereo,
+        0b10 => ChannelMode::DualChannel,
+        _ => ChannelMode::Mono,
+    };
+    let emphasis = match buffer[3] & 0x03 {
+        0b00 => Emphasis::None,
+        0b01 => Emphasis::Ms50_15,
+        0b10 => Emphasis::Reserved,
+        _ => Emphasis::CcittJ17,
+    };
+    Ok(MpegFrameHeader {
+        version,
+        layer,
+        protected,
+        bitrate,
+        sample_rate,
+        padding: buffer[2] & 0x02 == 0x02,
+        private: buffer[2] & 0x01 == 0x01,
+        channel_mode,
+        mode_extension: (buffer[3] & 0x30) >> 4,
+        copyright: buffer[3] & 0x08 == 0x08,
+        original: buffer[3] & 0x04 == 0x04,
+        emphasis,
+    })
+}
+
+/// Search for the first frame header in `buffer`.
+///
+/// To avoid false syncs inside junk data, the header must be followed by another valid header
+///
+/// unless the next frame is beyond the buffer.
+pub fn find_first_frame(buffer: &[u8]) -> Result<(usize, MpegFrameHeader), MpegError> {
+    let mut cursor: usize = 0;
+    while cursor + 4 <= buffer.len() {
+        if let Some(header) = confirm_frame(buffer, cursor) {
+            return Ok((cursor, header));
+        }
+        cursor += 1;
+    }
+    Err(MpegError::NoSync)
+}
+
+fn confirm_frame(buffer: &[u8], cursor: usize) -> Option<MpegFrameHeader> {
+    let header = parse_mpeg_frame_header(&buffer[cursor..]).ok()?;
+    let length = header.frame_length() as usize;
+    // free format is not supported
+    if length <= 4 {
+        return None;
+    }
+    let next = cursor + length;
+    if next + 4 > buffer.len() || parse_mpeg_frame_header(&buffer[next..]).is_ok() {
+        Some(header)
+    } else {
+        None
+    }
+}
+
+/// Bytes of audio read at once by `scan_stream`.
+pub const SCAN_CHUNK: usize = 1 << 20;
+
+/// Longer than any frame followed by the next header: MPEG-2.5 Layer II at 160 kbit/s and 8000 Hz
+///
+/// is 2881 bytes, free format is not supported.
+const LOOKAHEAD: usize = 4096;
+
+/// Walk every frame of the audio read from `reader`, which starts at the absolute position `base`.
+///
+/// The audio is read `chunk` bytes at a time, a frame cut by a chunk is completed by the next one.
+pub fn scan_stream<T: Read>(mut reader: T, base: u64, chunk: usize) -> io::Result<StreamScan> {
+    let mut scanner = FrameScanner::default();
+    let mut buffer: Vec<u8> = Vec::with_capacity(chunk + LOOKAHEAD);
+    let mut position = base;
+    loop {
+        let read = reader
+            .by_ref()
+            .take(chunk as u64)
+            .read_to_end(&mut buffer)?;
+        let is_end = read < chunk;
+        let consumed = scanner.feed(&buffer, position, is_end);
+        if is_end {
+            return Ok(scanner.finish(position + buffer.len() as u64));
+        }
+        buffer.drain(..consumed);
+        position += consumed as u64;
+    }
+}
+
+/// State of a frame walk over consecutive buffers.
+#[derive(Default)]
+struct FrameScanner {
+    scan: StreamScan,
+    samples_per_second: f64,
+    in_sync: bool,
+}
+
+impl FrameScanner {
+    /// Walk the frames of `buffer`, which holds the audio starting at the absolute position `base`.
+    ///
+    /// Unless `is_end`, the last `LOOKAHEAD` bytes are left for the next call
+    ///
+    /// and the number of bytes walked is returned.
+    fn feed(&mut self, buffer: &[u8], base: u64, is_end: bool) -> usize {
+        let limit = if is_end {
+            buffer.len()
+        } else {
+            buffer.len().saturating_sub(LOOKAHEAD)
+        };
+        let scan = &mut self.scan;
+        let mut cursor: usize = 0;
+        while cursor < limit {
+            let header = if self.in_sync {
+                parse_mpeg_frame_header(&buffer[cursor..])
+                    .ok()
+                    .filter(|h| h.frame_length() > 4)
+            } else {
+                confirm_frame(buffer, cursor)
+            };
+            let Some(header) = header else {
+                if self.in_sync {
+                    scan.lost_sync.push(base + cursor as u64);
+                    self.in_sync = false;
+                }
+                let skip = match find_first_frame(&buffer[cursor..]) {
+                    Ok((skip, _)) => skip.max(1),
+                    Err(_) => limit - cursor,
+                };
+                let offset = base + cursor as u64;
+                match scan.junk.last_mut() {
+                    // junk cut by the end of the previous buffer
+                    Some(last) if last.offset + last.length == offset => last.length += skip as u64,
+                    _ => scan.junk.push(JunkRegion::new(offset, skip as u64)),
+                }
+                cursor += skip;
+                continue;
+            };
+            let length = header.frame_length() as usize;
+            if cursor + length > buffer.len() {
+                scan.truncated_frame = Some(base + cursor as u64);
+                return buffer.len();
+            }
+            if scan.frame_count == 0 {
+                scan.first_frame_offset = base + cursor as u64;
+            }
+            if let (true, Layer::Layer3) = (header.protected, &header.layer) {
+                let frame = &buffer[cursor..cursor + length];
+                if let Some(side_info) = frame.get(6..6 + side_info_length(&header)) {
+                    scan.crc_checked += 1;
+                    let crc = crc16(side_info, crc16(&frame[2..4], 0xFFFF));
+                    if crc != (frame[4] as u16) * 0x100 + frame[5] as u16 {
+                        scan.crc_errors.push(base + cursor as u64);
+                    }
+                }
+            }
+            self.in_sync = true;
+            scan.frame_count += 1;
+            scan.total_samples += header.samples_per_frame() as u64;
+            self.samples_per_second = header.sample_rate as f64;
+            cursor += length;
+        }
+        cursor
+    }
+
+    /// `audio_end` is the absolute position right after the last buffer.
+    fn finish(mut self, audio_end: u64) -> StreamScan {
+        self.scan.audio_end = audio_end;
+        if self.samples_per_second > 0.0 {
+            self.scan.duration =
+                Duration::from_secs_f64(self.scan.total_samples as f64 / self.samples_per_second);
+        }
+        self.scan
+    }
+}
+
+/// CRC-16 with the polynomial 0x8005, as used by the MPEG audio frame CRC.
+fn crc16(data: &[u8], mut crc: u16) -> u16 {
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Size of the Layer III side information, which follows the header and the optional CRC.
+fn side_info_length(header: &MpegFrameHeader) -> usize {
+    match (&header.version, &header.channel_mode) {
+        (MpegVersion::Mpeg1, ChannelMode::Mono) => 17,
+        (MpegVersion::Mpeg1, _) => 32,
+        (_, ChannelMode::Mono) => 9,
+        (_, _) => 17,
+    }
+}
+
+/// Offset of the Xing header from the start of the frame, which follows the side information.
+fn xing_offset(header: &MpegFrameHeader) -> usize {
+    4 + side_info_length(header)
+}
+
+fn parse_2_bytes_with_big_endian(buffer: &[u8]) -> u16 {
+    buffer[0] as u16 * 0x100 + buffer[1] as u16
+}
+
+/// `frame` starts with the frame header of the first frame.
+pub fn parse_xing_header(frame: &[u8], header: &MpegFrameHeader) -> Option<XingHeader> {
+    let mut cursor = xing_offset(header);
+    let id = frame.get(cursor..cursor + 8)?;
+    let is_info = match &id[0..4] {
+        b"Xing" => false,
+        b"Info" => true,
+        _ => return None,
+    };
+    let flags = parse_4_bytes_with_big_endian(&id[4..8]);
+    cursor += 8;
+    let mut xing = XingHeader {
+        is_info,
+        ..Default::default()
+    };
+    if flags & 0x01 != 0 {
+        xing.frames = Some(parse_4_bytes_with_big_endian(
+            frame.get(cursor..cursor + 4)?,
+        ));
+        cursor += 4;
+    }
+    if flags & 0x02 != 0 {
+        xing.bytes = Some(parse_4_bytes_with_big_endian(
+            frame.get(cursor..cursor + 4)?,
+        ));
+        cursor += 4;
+    }
+    if flags & 0x04 != 0 {
+        xing.toc = Some(frame.get(cursor..cursor + 100)?.to_vec());
+        cursor += 100;
+    }
+    if flags & 0x08 != 0 {
+        xing.quality = Some(parse_4_bytes_with_big_endian(
+            frame.get(cursor..cursor + 4)?,
+        ));
+        cursor += 4;
+    }
+    xing.lame = frame.get(cursor..).and_then(parse_lame_header);
+    Some(xing)
+}
+
+/// `buffer` starts with the encoder version, like "LAME3.100".
+pub fn parse_lame_header(buffer: &[u8]) -> Option<LameHeader> {
+    let buffer = buffer.get(..36)?;
+    if &buffer[0..4] != b"LAME" && &buffer[0..4] != b"Lavf" && &buffer[0..4] != b"Lavc" {
+        return None;
+    }
+    let encoder = String::from_utf8_lossy(&buffer[0..9])
+        .trim_end_matches(['\0', ' '])
+        .to_string();
+    let track_peak = parse_4_bytes_with_big_endian(&buffer[11..15]) as f32 / (1 << 23) as f32;
+    Some(LameHeader {
+        encoder,
+        revision: buffer[9] >> 4,
+        vbr_method: (buffer[9] & 0x0F).into(),
+        lowpass: buffer[10] as u32 * 100,
+        track_peak,
+        track_gain: parse_replay_gain(parse_2_bytes_with_big_endian(&buffer[15..17]), 1),
+        album_gain: parse_replay_gain(parse_2_bytes_with_big_endian(&buffer[17..19]), 2),
+        encoding_flags: buffer[19] >> 4,
+        ath_type: buffer[19] & 0x0F,
+        bitrate: buffer[20],
+        encoder_delay: ((buffer[21] as u16) << 4) + (buffer[22] >> 4) as u16,
+        encoder_padding: (((buffer[22] & 0x0F) as u16) << 8) + buffer[23] as u16,
+        misc: buffer[24],
+        mp3_gain: buffer[25] as i8,
+        preset: parse_2_bytes_with_big_endian(&buffer[26..28]) & 0x07FF,
+        music_length: parse_4_bytes_with_big_endian(&buffer[28..32]),
+        music_crc: parse_2_bytes_with_big_endian(&buffer[32..34]),
+        tag_crc: parse_2_bytes_with_big_endian(&buffer[34..36]),
+    })
+}
+
+/// 3 bits name code (1 = track, 2 = album), 3 bits originator, 1 bit sign, 9 bits gain * 10.
+fn parse_replay_gain(value: u16, name: u16) -> Option<f32> {
+    if value >> 13 != name || (value >> 10) & 0x07 == 0 {
+        return None;
+    }
+    let gain = (value & 0x01FF) as f32 / 10.0;
+    if value & 0x0200 != 0 {
+        Some(-gain)
+    } else {
+        Some(gain)
+    }
+}
+
+/// `frame` starts with the frame header of the first frame.
+pub fn parse_vbri_header(frame: &[u8]) -> Option<VbriHeader> {
+    let buffer = frame.get(36..36 + 26)?;
+    if &buffer[0..4] != b"VBRI" {
+        return None;
+    }
+    let toc_entries = parse_2_bytes_with_big_endian(&buffer[18..20]) as usize;
+    let toc_scale = parse_2_bytes_with_big_endian(&buffer[20..22]);
+    let toc_entry_size = parse_2_bytes_with_big_endian(&buffer[22..24]);
+    let toc_frames_per_entry = parse_2_bytes_with_big_endian(&buffer[24..26]);
+    let mut toc: Vec<u32> = Vec::with_capacity(toc_entries);
+    let size = toc_entry_size as usize;
+    if (1..=4).contains(&size) {
+        if let Some(raw) = frame.get(36 + 26..36 + 26 + toc_entries * size) {
+            for entry in raw.chunks(size) {
+                let value = entry.iter().fold(0u32, |acc, &b| (acc << 8) + b as u32);
+                toc.push(value * toc_scale as u32);
+            }
+        }
+    }
+    Some(VbriHeader {
+        version: parse_2_bytes_with_big_endian(&buffer[4..6]),
+        delay: parse_2_bytes_with_big_endian(&buffer[6..8]),
+        quality: parse_2_bytes_with_big_endian(&buffer[8..10]),
+        bytes: parse_4_bytes_with_big_endian(&buffer[10..14]),
+        frames: parse_4_bytes_with_big_endian(&buffer[14..18]),
+        toc_scale,
+        toc_entry_size,
+        toc_frames_per_entry,
+        toc,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MPEG-1 Layer III, 128 kbit/s, 44100 Hz, joint stereo: 417 bytes.
+    fn frame(protected: bool) -> Vec<u8> {
+        let mut frame = vec![0u8; 417];
+        let flag = if protected { 0xFA } else { 0xFB };
+        frame[..4].copy_from_slice(&[0xFF, flag, 0x90, 0x40]);
+        for (i, byte) in frame.iter_mut().enumerate().skip(6) {
+            *byte = (i % 7) as u8;
+        }
+        if protected {
+            let crc = crc16(&frame[6..38], crc16(&frame[2..4], 0xFFFF));
+            frame[4..6].copy_from_slice(&crc.to_be_bytes());
+        }
+        frame
+    }
+
+    fn scan(audio: &[u8], chunk: usize) -> StreamScan {
+        scan_stream(audio, 100, chunk).unwrap()
+    }
+
+    #[test]
+    fn clean_stream() {
+        let audio: Vec<u8> = (0..40).flat_map(|_| frame(false)).collect();
+        let scan = scan(&audio, SCAN_CHUNK);
+        assert!(scan.is_ok());
+        assert_eq!(scan.frame_count, 40);
+        assert_eq!(scan.first_frame_offset, 100);
+        assert_eq!(scan.audio_end, 100 + 40 * 417);
+        assert_eq!(scan.total_samples, 40 * 1152);
+    }
+
+    #[test]
+    fn chunks_give_the_same_result() {
+        let mut audio: Vec<u8> = (0..30).flat_map(|_| frame(false)).collect();
+        audio.extend_from_slice(&[0x55; 300]);
+        audio.extend((0..30).flat_map(|_| frame(false)));
+        audio.extend_from_slice(&frame(false)[..200]);
+        let whole = scan(&audio, audio.len() + 1);
+        assert_eq!(whole.frame_count, 60);
+        assert_eq!(whole.audio_end, 100 + audio.len() as u64);
+        assert_eq!(whole.lost_sync, [100 + 30 * 417]);
+        assert_eq!(whole.junk.len(), 1);
+        assert_eq!(
+            (whole.junk[0].offset, whole.junk[0].length),
+            (100 + 30 * 417, 300)
+        );
+        assert_eq!(whole.truncated_frame, Some(100 + 60 * 417 + 300));
+        for chunk in [1, 417, 4096, 5000, 8191] {
+            assert_eq!(
+                scan(&audio, chunk).to_string(),
+                whole.to_string(),
+                "{chunk}"
+            );
+        }
+    }
+
+    #[test]
+    fn crc_of_layer3_frames() {
+        let mut audio: Vec<u8> = (0..3).flat_map(|_| frame(true)).collect();
+        audio[417 + 10] ^= 0xFF;
+        let scan = scan(&audio, SCAN_CHUNK);
+        assert_eq!(scan.crc_checked, 3);
+        assert_eq!(scan.crc_errors, [100 + 417]);
+    }
+
+    #[test]
+    fn no_frame() {
+        let audio = vec![0xFF; 10000];
+        let scan = scan(&audio, 4097);
+        assert!(!scan.is_ok());
+        assert_eq!(scan.frame_count, 0);
+        let junk: Vec<(u64, u64)> = scan.junk.iter().map(|v| (v.offset, v.length)).collect();
+        assert_eq!(junk, [(100, 10000)]);
+    }
+}
@@ -0,0 +1,7 @@
+#[derive(Debug)]
+pub enum MpegError {
+    /// the 11-bit frame sync is not found
+    NoSync,
+    /// reserved version, layer, bitrate or sample rate
+    InvalidHeader,
+}
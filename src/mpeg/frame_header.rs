@@ -0,0 +1,165 @@
+use std::fmt::Display;
+
+/// 4-byte MPEG audio frame header.
+///
+/// http://www.mp3-tech.org/programmer/frame_header.html
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MpegFrameHeader {
+    pub version: MpegVersion,
+    pub layer: Layer,
+    /// the frame is followed by a 16-bit CRC
+    pub protected: bool,
+    /// kbps, 0 means free format
+    pub bitrate: u32,
+    /// Hz
+    pub sample_rate: u32,
+    pub padding: bool,
+    pub private: bool,
+    pub channel_mode: ChannelMode,
+    pub mode_extension: u8,
+    pub copyright: bool,
+    pub original: bool,
+    pub emphasis: Emphasis,
+}
+
+impl MpegFrameHeader {
+    /// Number of samples per channel in one frame.
+    pub fn samples_per_frame(&self) -> u32 {
+        match (&self.layer, &self.version) {
+            (Layer::Layer1, _) => 384,
+            (Layer::Layer2, _) => 1152,
+            (Layer::Layer3, MpegVersion::Mpeg1) => 1152,
+            (Layer::Layer3, _) => 576,
+        }
+    }
+
+    /// Frame length in bytes, including the header.
+    pub fn frame_length(&self) -> u32 {
+        if self.sample_rate == 0 {
+            return 0;
+        }
+        match self.layer {
+            Layer::Layer1 => {
+                (12 * self.bitrate * 1000 / self.sample_rate + self.padding as u32) * 4
+            }
+            _ => {
+                self.samples_per_frame() / 8 * self.bitrate * 1000 / self.sample_rate
+                    + self.padding as u32
+            }
+        }
+    }
+
+    pub fn channels(&self) -> u8 {
+        match self.channel_mode {
+            ChannelMode::Mono => 1,
+            _ => 2,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MpegVersion {
+    #[default]
+    Mpeg1,
+    Mpeg2,
+    Mpeg25,
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Layer {
+    Layer1,
+    Layer2,
+    #[default]
+    Layer3,
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelMode {
+    #[default]
+    Stereo,
+    JointStereo,
+    DualChannel,
+    Mono,
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Emphasis {
+    #[default]
+    None,
+    /// 50/15 ms
+    Ms50_15,
+    Reserved,
+    /// CCITT J.17
+    CcittJ17,
+}
+
+impl Display for MpegVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ver = match self {
+            MpegVersion::Mpeg1 => "1",
+            MpegVersion::Mpeg2 => "2",
+            MpegVersion::Mpeg25 => "2.5",
+        };
+        write!(f, "MPEG-{ver}")
+    }
+}
+
+impl Display for Layer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let layer = match self {
+            Layer::Layer1 => "I",
+            Layer::Layer2 => "II",
+            Layer::Layer3 => "III",
+        };
+        write!(f, "Layer {layer}")
+    }
+}
+
+impl Display for MpegFrameHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MpegFrameHeader {{
+    version: {},
+    layer: {},
+    protected: {},
+    bitrate: {} kbps,
+    sample_rate: {} Hz,
+    padding: {},
+    private: {},
+    channel_mode: {:?},
+    mode_extension: {},
+    copyright: {},
+    original: {},
+    emphasis: {:?}
+}}",
+            self.version,
+            self.layer,
+            self.protected,
+            self.bitrate,
+            self.sample_rate,
+            self.padding,
+            self.private,
+            self.channel_mode,
+            self.mode_extension,
+            self.copyright,
+            self.original,
+            self.emphasis
+        )
+    }
+}
@@ -0,0 +1,6 @@
+pub mod audio_properties;
+pub mod core;
+pub mod error;
+pub mod frame_header;
+pub mod stream_scan;
+pub mod vbr_header;
@@ -0,0 +1,88 @@
+use std::{fmt::Display, time::Duration};
+
+/// Result of walking every MPEG frame between the ID3v2 tag and the trailing tags.
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamScan {
+    /// absolute position of the first valid frame
+    pub first_frame_offset: u64,
+    /// absolute position right after the audio
+    pub audio_end: u64,
+    pub frame_count: u64,
+    /// samples per channel of all frames
+    pub total_samples: u64,
+    pub duration: Duration,
+    /// frames whose CRC-16 has been verified (Layer III only)
+    pub crc_checked: u64,
+    /// absolute positions of frames whose CRC-16 does not match
+    pub crc_errors: Vec<u64>,
+    /// absolute positions where a frame header was expected after a valid frame
+    pub lost_sync: Vec<u64>,
+    /// data that is not part of any frame, including junk before the first frame
+    pub junk: Vec<JunkRegion>,
+    /// absolute position of the last frame if it is cut off
+    pub truncated_frame: Option<u64>,
+}
+
+impl StreamScan {
+    /// No CRC error, lost sync, junk or truncated frame is found.
+    pub fn is_ok(&self) -> bool {
+        self.frame_count > 0
+            && self.crc_errors.is_empty()
+            && self.lost_sync.is_empty()
+            && self.junk.is_empty()
+            && self.truncated_frame.is_none()
+    }
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JunkRegion {
+    /// absolute position
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl JunkRegion {
+    pub fn new(offset: u64, length: u64) -> Self {
+        JunkRegion { offset, length }
+    }
+}
+
+impl Display for StreamScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "
+StreamScan {{
+    first_frame_offset: {},
+    audio_end: {},
+    frame_count: {},
+    total_samples: {},
+    duration: {:?},
+    crc_checked: {},
+    crc_errors: {:?},
+    lost_sync: {:?},
+    junk: {:?},
+    truncated_frame: {:?}
+}}",
+            self.first_frame_offset,
+            self.audio_end,
+            self.frame_count,
+            self.total_samples,
+            self.duration,
+            self.crc_checked,
+            self.crc_errors,
+            self.lost_sync,
+            self.junk
+                .iter()
+                .map(|j| (j.offset, j.length))
+                .collect::<Vec<(u64, u64)>>(),
+            self.truncated_frame
+        )
+    }
+}
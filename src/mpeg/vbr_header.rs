@@ -0,0 +1,173 @@
+use std::fmt::Display;
+
+/// Xing header, or "Info" for CBR, stored in the first frame instead of audio data.
+///
+/// http://gabriel.mp3-tech.org/mp3infotag.html
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XingHeader {
+    /// "Info" instead of "Xing", written by LAME for CBR files
+    pub is_info: bool,
+    /// number of frames, excluding this one
+    pub frames: Option<u32>,
+    /// number of bytes, including this frame
+    pub bytes: Option<u32>,
+    /// 100 entries, each one is the position of n% of the duration, scaled to 256
+    pub toc: Option<Vec<u8>>,
+    /// 0 (best) to 100 (worst)
+    pub quality: Option<u32>,
+    pub lame: Option<LameHeader>,
+}
+
+/// Fraunhofer VBRI header, always located 32 bytes after the frame header.
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VbriHeader {
+    pub version: u16,
+    pub delay: u16,
+    pub quality: u16,
+    pub bytes: u32,
+    pub frames: u32,
+    pub toc_scale: u16,
+    pub toc_entry_size: u16,
+    pub toc_frames_per_entry: u16,
+    /// size of each entry in bytes, already multiplied by `toc_scale`
+    pub toc: Vec<u32>,
+}
+
+/// LAME extension, following the Xing header.
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LameHeader {
+    /// e.g. "LAME3.100"
+    pub encoder: String,
+    pub revision: u8,
+    pub vbr_method: VbrMethod,
+    /// Hz
+    pub lowpass: u32,
+    /// 1.0 is the maximal amplitude, 0.0 means unknown
+    pub track_peak: f32,
+    /// dB
+    pub track_gain: Option<f32>,
+    /// dB
+    pub album_gain: Option<f32>,
+    pub encoding_flags: u8,
+    pub ath_type: u8,
+    /// kbps, the ABR target or the minimal bitrate for VBR
+    pub bitrate: u8,
+    /// samples added at the start by the encoder
+    pub encoder_delay: u16,
+    /// samples added at the end by the encoder
+    pub encoder_padding: u16,
+    pub misc: u8,
+    pub mp3_gain: i8,
+    pub preset: u16,
+    /// bytes from the first frame (this one) to the last frame
+    pub music_length: u32,
+    pub music_crc: u16,
+    pub tag_crc: u16,
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VbrMethod {
+    #[default]
+    Unknown,
+    Cbr,
+    Abr,
+    /// VBR old, rh
+    Vbr1,
+    /// VBR mtrh
+    Vbr2,
+    /// VBR rh
+    Vbr3,
+    Vbr4,
+    Cbr2Pass,
+    Abr2Pass,
+    Reserved(u8),
+}
+
+impl From<u8> for VbrMethod {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => VbrMethod::Unknown,
+            1 => VbrMethod::Cbr,
+            2 => VbrMethod::Abr,
+            3 => VbrMethod::Vbr1,
+            4 => VbrMethod::Vbr2,
+            5 => VbrMethod::Vbr3,
+            6 => VbrMethod::Vbr4,
+            8 => VbrMethod::Cbr2Pass,
+            9 => VbrMethod::Abr2Pass,
+            v => VbrMethod::Reserved(v),
+        }
+    }
+}
+
+impl Display for XingHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "
+XingHeader {{
+    is_info: {},
+    frames: {:?},
+    bytes: {:?},
+    toc: {},
+    quality: {:?}
+}}",
+            self.is_info,
+            self.frames,
+            self.bytes,
+            self.toc.is_some(),
+            self.quality
+        )?;
+        if let Some(lame) = &self.lame {
+            write!(f, "{lame}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for LameHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "
+LameHeader {{
+    encoder: {},
+    revision: {},
+    vbr_method: {:?},
+    lowpass: {} Hz,
+    track_peak: {},
+    track_gain: {:?} dB,
+    album_gain: {:?} dB,
+    bitrate: {} kbps,
+    encoder_delay: {},
+    encoder_padding: {},
+    music_length: {} Bytes,
+    music_crc: {:04X}
+}}",
+            self.encoder,
+            self.revision,
+            self.vbr_method,
+            self.lowpass,
+            self.track_peak,
+            self.track_gain,
+            self.album_gain,
+            self.bitrate,
+            self.encoder_delay,
+            self.encoder_padding,
+            self.music_length,
+            self.music_crc
+        )
+    }
+}
@@ -0,0 +1,92 @@
+use crate::util::Buffer;
+
+use super::{ogg_vorbis_comment::CommentHeader, page::PageHeader};
+
+/// Parse the 27 bytes of a page header, `segment_table` is read right after them.
+pub fn parse_page_header(buffer: &[u8], segment_table: Buffer) -> PageHeader {
+    let header_type_flag = buffer[5];
+    PageHeader {
+        capture_pattern: String::from_utf8_lossy(&buffer[0..4]).into_owned(),
+        structure_version: buffer[4],
+        new_packet: (header_type_flag & 0x01) == 0,
+        bos: (header_type_flag & 0x02) == 2,
+        eos: (header_type_flag & 0x04) == 4,
+        granule_position: buffer[6..14].to_vec(),
+        serial_number: buffer[14..18].to_vec(),
+        page_sequence_number: buffer[18..22].to_vec(),
+        crc_checksum: buffer[22..26].to_vec(),
+        number_page_segments: buffer[26],
+        segment_table,
+    }
+}
+
+/// Append the part of the comment packet carried by a page to `comment_buffer`,
+///
+/// setting `comment_header.end` once the packet is complete.
+///
+/// Return true if the page starts with the comment packet.
+pub fn collect_comment_packet(
+    page_header: PageHeader,
+    body: &[u8],
+    comment_buffer: &mut Buffer,
+    comment_header: &mut CommentHeader,
+) -> bool {
+    let Some(&temp) = body.first() else {
+        return false;
+    };
+    if let 0x01 | 0x05 = temp {
+        return false;
+    }
+    // a segment shorter than 0xFF ends the first packet in this page
+    let first_packet_ends = page_header.segment_table.iter().any(|&v| v != 0xFF);
+    let mut packets: Vec<u32> = vec![0];
+    // 此页有几个包，包的长度是多少
+    for (i, ele) in page_header.segment_table.into_iter().enumerate() {
+        let last = packets.len() - 1;
+        // 非0xFF
+        if ele != 0xFF {
+            packets[last] += ele as u32;
+            if i != page_header.number_page_segments as usize - 1 {
+                packets.push(0);
+            }
+        } else {
+            packets[last] += 0xFF;
+        }
+    }
+    // 第一个包长度-1, the page starts with an empty packet if 0
+    packets[0] = packets[0].saturating_sub(0x01);
+    // 是一个新包，并且有区段，并且第一个字节是0x03
+    if page_header.new_packet && page_header.number_page_segments > 0 && temp == 0x03 {
+        if first_packet_ends {
+            // the setup packet may follow in the same page
+            let end = (1 + packets[0] as usize).min(body.len());
+            comment_buffer.extend_from_slice(&body[1..end]);
+            if comment_buffer.last() == Some(&0x01) {
+                comment_buffer.pop();
+            }
+            comment_header.end = true;
+        } else {
+            // 将所有数据读入
+            comment_buffer.extend_from_slice(&body[1..]);
+            // 进入下一页
+        }
+    } else {
+        // 是继承包
+        comment_buffer.push(temp);
+        let mut start: usize = 1;
+        for len in packets {
+            let end = (start + len as usize).min(body.len());
+            comment_buffer.extend_from_slice(&body[start..end]);
+            start = end;
+            while comment_buffer.last() == Some(&0x00) {
+                comment_buffer.pop();
+            }
+            if comment_buffer.last() == Some(&0x01) {
+                comment_buffer.pop();
+                comment_header.end = true;
+                break;
+            }
+        }
+    }
+    temp == 0x03
+}
@@ -1,3 +1,4 @@
 pub mod page;
 pub mod ogg_vorbis_comment;
 pub mod ogg_buffer_reader;
+pub mod core;
\ No newline at end of file
@@ -1,30 +1,26 @@
-use std::{fs::File, io::{self, Read, Seek}, path::Path};
+use std::io::{self, Read, Seek};
 
 use crate::util::Buffer;
 
-pub struct OggBufferReader {
-    file: File,
+pub struct OggBufferReader<'a, R: Read + Seek> {
+    file: &'a mut R,
 }
-impl OggBufferReader {
-    pub fn new<T: AsRef<Path>>(f: T) -> io::Result<Self> {
-        Ok(OggBufferReader {
-            file: File::open(f)?,
-        })
+impl<'a, R: Read + Seek> OggBufferReader<'a, R> {
+    /// Start reading `f` from the beginning.
+    pub fn new(f: &'a mut R) -> io::Result<Self> {
+        f.rewind()?;
+        Ok(OggBufferReader { file: f })
     }
     pub fn read_buffer(&mut self, length: u32) -> io::Result<Buffer> {
         let mut buf = vec![0; length as usize];
         self.file.read_exact(&mut buf)?;
         Ok(buf)
     }
-    pub fn read_one(&mut self) -> io::Result<u8> {
-        Ok(self.read_buffer(1).unwrap()[0])
-    }
     /// absolute position from file start
+    pub fn position(&mut self) -> io::Result<u64> {
+        self.file.stream_position()
+    }
     // pub fn seek_to(&mut self, location: u64) -> io::Result<u64>{
     //     self.file.seek(io::SeekFrom::Start(location))
     // }
-    pub fn skip(&mut self, length: u64) -> io::Result<u64> {
-        self.file.seek(io::SeekFrom::Current(length as i64))
-        // Ok(())
-    }
 }
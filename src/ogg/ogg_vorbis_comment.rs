@@ -27,6 +27,8 @@ impl Default for CommentHeader {
     }
 }
 #[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CommentBody {
     pub hm: HashMap<String, usize>,
     pub comment: Vec<Vec<String>>,
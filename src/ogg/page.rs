@@ -1,3 +1,4 @@
+#[allow(dead_code)]
 pub struct PageHeader {
     pub capture_pattern: String,
     pub structure_version: u8,
@@ -0,0 +1,105 @@
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+/// Whether the parsers read the image bytes of pictures (APIC frames, FLAC PICTURE blocks).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PictureLoading {
+    /// read the image bytes with the rest of the tag
+    #[default]
+    Eager,
+    /// record where the image bytes are, and read them with `load_picture` or `picture_reader`
+    Lazy,
+}
+
+/// Position of the image bytes of a picture in the file.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PictureLocation {
+    /// absolute position of the first byte
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl PictureLocation {
+    pub fn new(offset: u64, length: u64) -> Self {
+        PictureLocation { offset, length }
+    }
+}
+
+/// MIME type of the pictures whose data is the URL of the image instead of the image bytes.
+pub const LINK_MIME_TYPE: &str = "-->";
+
+/// What a picture holds, see `ID3Parser::picture_content`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PictureContent {
+    /// the image bytes
+    Image(#[cfg_attr(feature = "serde", serde(with = "crate::dump::base64"))] Vec<u8>),
+    /// the URL of the image, when the MIME type is "-->"
+    Url(String),
+}
+
+impl PictureContent {
+    /// Wrap the data read for a picture of MIME type `mime`.
+    pub fn new(mime: &str, data: Vec<u8>) -> Self {
+        if mime == LINK_MIME_TYPE {
+            // ID3v2.3 does not say whether the URL is terminated
+            let url = String::from_utf8_lossy(&data);
+            PictureContent::Url(url.trim_end_matches('\0').to_string())
+        } else {
+            PictureContent::Image(data)
+        }
+    }
+}
+
+/// How many bytes are read first from a picture in lazy mode,
+///
+/// enough for the MIME type and description in most files.
+pub const PICTURE_HEAD_LENGTH: u32 = 1024;
+
+/// Read the image bytes at `location`, or return `data` which holds them if there is no location.
+pub fn read_picture<R: Read + Seek>(
+    source: &mut R,
+    location: Option<PictureLocation>,
+    data: Vec<u8>,
+) -> io::Result<Vec<u8>> {
+    match location {
+        Some(location) => {
+            let mut buf = vec![0; location.length as usize];
+            source.seek(SeekFrom::Start(location.offset))?;
+            source.read_exact(&mut buf)?;
+            Ok(buf)
+        }
+        None => Ok(data),
+    }
+}
+
+/// Same as `read_picture`, without reading everything at once.
+pub fn picture_reader<R: Read + Seek>(
+    source: &mut R,
+    location: Option<PictureLocation>,
+    data: Vec<u8>,
+) -> io::Result<Box<dyn Read + '_>> {
+    match location {
+        Some(location) => {
+            source.seek(SeekFrom::Start(location.offset))?;
+            Ok(Box::new(source.take(location.length)))
+        }
+        None => Ok(Box::new(Cursor::new(data))),
+    }
+}
+
+/// Error of `load_picture` and `picture_reader` when there is no picture at `index`.
+pub fn no_picture(index: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no picture at index {index}"),
+    )
+}
+
+/// Error of `load_picture` and `picture_reader` when the picture at `index` is a link, see `PictureContent::Url`.
+pub fn linked_picture(index: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("picture at index {index} is a link, not image bytes"),
+    )
+}
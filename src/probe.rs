@@ -0,0 +1,233 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::id3::core::parse_protocol_header;
+use crate::mpeg::core::find_first_frame;
+use crate::mpeg::frame_header::Layer;
+use crate::{Error, FlacParser, ID3Parser, Layout, OggParser, ParseMode, PictureLoading, Tag};
+
+/// Container of an audio file, see `probe`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Format {
+    /// MPEG audio frames, with or without ID3 tags
+    Mp3,
+    Flac,
+    Ogg,
+}
+
+/// Codec of the audio stream, see `probe`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Codec {
+    MpegLayer1,
+    MpegLayer2,
+    MpegLayer3,
+    Flac,
+    Vorbis,
+    Opus,
+    Speex,
+    /// FLAC in Ogg
+    OggFlac,
+    /// no frame is found after an ID3v2 tag, or the first Ogg packet is not known
+    Unknown,
+}
+
+/// Result of `probe`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Probe {
+    pub format: Format,
+    pub codec: Codec,
+    /// length of the ID3v2 tag before the audio, 0 if there is none
+    pub id3v2_length: u64,
+}
+
+impl Probe {
+    pub fn new(format: Format, codec: Codec, id3v2_length: u64) -> Self {
+        Probe {
+            format,
+            codec,
+            id3v2_length,
+        }
+    }
+}
+
+/// Bytes read to recognize a file, enough for two MPEG frames.
+const MPEG_SYNC_WINDOW: u64 = 8192;
+
+/// Recognize the format of `path` from its first bytes, not from its extension.
+///
+/// Return None if it is not an mp3, flac or ogg file.
+pub fn probe<T: AsRef<Path>>(path: T) -> io::Result<Option<Probe>> {
+    probe_reader(&mut File::open(path)?)
+}
+
+/// Same as `probe`, over any seekable source.
+///
+/// A flac file may start with an ID3v2 tag as well.
+pub fn probe_reader<R: Read + Seek>(source: &mut R) -> io::Result<Option<Probe>> {
+    let buf = read_window(source, 0)?;
+    if buf.starts_with(b"fLaC") {
+        return Ok(Some(Probe::new(Format::Flac, Codec::Flac, 0)));
+    }
+    if buf.starts_with(b"OggS") {
+        return Ok(Some(Probe::new(Format::Ogg, ogg_codec(&buf), 0)));
+    }
+    if let Some(length) = id3v2_length(&buf) {
+        let buf = read_window(source, length)?;
+        if buf.starts_with(b"fLaC") {
+            return Ok(Some(Probe::new(Format::Flac, Codec::Flac, length)));
+        }
+        let codec = match find_first_frame(&buf) {
+            Ok((_, header)) => mpeg_codec(&header.layer),
+            Err(_) => Codec::Unknown,
+        };
+        return Ok(Some(Probe::new(Format::Mp3, codec, length)));
+    }
+    // an mp3 without ID3v2 may be padded with zeros before the first frame
+    let start = buf.iter().position(|&b| b != 0).unwrap_or(buf.len());
+    if let Ok((0, header)) = find_first_frame(&buf[start..]) {
+        let codec = mpeg_codec(&header.layer);
+        return Ok(Some(Probe::new(Format::Mp3, codec, 0)));
+    }
+    Ok(None)
+}
+
+/// Read up to `MPEG_SYNC_WINDOW` bytes from `offset`.
+fn read_window<R: Read + Seek>(source: &mut R, offset: u64) -> io::Result<Vec<u8>> {
+    let mut buf: Vec<u8> = Vec::new();
+    source.seek(SeekFrom::Start(offset))?;
+    source.take(MPEG_SYNC_WINDOW).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Length of the ID3v2 tag at the start of `head`, including its header and footer.
+pub(crate) fn id3v2_length(head: &[u8]) -> Option<u64> {
+    if head.len() < 10 || !head.starts_with(b"ID3") {
+        return None;
+    }
+    parse_protocol_header(&head[..10].to_vec())
+        .ok()
+        .map(|v| v.tag_length())
+}
+
+fn mpeg_codec(layer: &Layer) -> Codec {
+    match layer {
+        Layer::Layer1 => Codec::MpegLayer1,
+        Layer::Layer2 => Codec::MpegLayer2,
+        Layer::Layer3 => Codec::MpegLayer3,
+    }
+}
+
+/// Recognize the identification packet that starts the first page.
+fn ogg_codec(page: &[u8]) -> Codec {
+    let Some(&segments) = page.get(26) else {
+        return Codec::Unknown;
+    };
+    let packet = page.get(27 + segments as usize..).unwrap_or_default();
+    if packet.starts_with(b"\x01vorbis") {
+        Codec::Vorbis
+    } else if packet.starts_with(b"OpusHead") {
+        Codec::Opus
+    } else if packet.starts_with(b"Speex   ") {
+        Codec::Speex
+    } else if packet.starts_with(b"\x7FFLAC") {
+        Codec::OggFlac
+    } else {
+        Codec::Unknown
+    }
+}
+
+/// A parsed file, the parser keeps it open for further calls such as `load_picture`.
+#[allow(clippy::large_enum_variant)]
+pub enum AudioFile {
+    Mp3(ID3Parser),
+    Flac(FlacParser),
+    Ogg(OggParser),
+}
+
+impl AudioFile {
+    pub fn format(&self) -> Format {
+        match self {
+            AudioFile::Mp3(_) => Format::Mp3,
+            AudioFile::Flac(_) => Format::Flac,
+            AudioFile::Ogg(_) => Format::Ogg,
+        }
+    }
+
+    /// The tag of any format, see `Tag`.
+    pub fn tag(&self) -> Tag {
+        match self {
+            AudioFile::Mp3(parser) => parser.tag(),
+            AudioFile::Flac(parser) => parser.tag(),
+            AudioFile::Ogg(parser) => parser.tag(),
+        }
+    }
+    /// The regions of the file, see `Layout`.
+    pub fn layout(&mut self) -> io::Result<Layout> {
+        match self {
+            AudioFile::Mp3(parser) => parser.layout(),
+            AudioFile::Flac(parser) => parser.layout(),
+            AudioFile::Ogg(parser) => parser.layout(),
+        }
+    }
+}
+
+/// Recognize `path` with `probe` and parse it with the matching parser.
+///
+/// An mp3 file is parsed for ID3v2 and ID3v1.
+///
+/// Return `Error::NotThisFormat` if it is not an mp3, flac or Ogg Vorbis file.
+pub fn open<T: AsRef<Path>>(path: T) -> Result<AudioFile, Error> {
+    let path = path.as_ref();
+    match probe(path)? {
+        Some(probe) => open_probed(path, probe, ParseMode::default(), PictureLoading::default()),
+        None => Err(Error::NotThisFormat {
+            expected: "mp3, flac or ogg",
+        }),
+    }
+}
+
+/// Parse `path` whose format is already known, see `open`.
+pub(crate) fn open_probed(
+    path: &Path,
+    probe: Probe,
+    parse_mode: ParseMode,
+    picture_loading: PictureLoading,
+) -> Result<AudioFile, Error> {
+    let file = match probe.format {
+        Format::Mp3 => {
+            let mut parser = ID3Parser::new(path)?;
+            parser.set_parse_mode(parse_mode);
+            parser.set_picture_loading(picture_loading);
+            match parser.parse_id3v2() {
+                Ok(_) | Err(Error::NotThisFormat { .. }) => {}
+                Err(e) => return Err(e),
+            }
+            parser.parse_id3v1()?;
+            AudioFile::Mp3(parser)
+        }
+        Format::Flac => {
+            let mut parser = FlacParser::new(path)?;
+            parser.set_parse_mode(parse_mode);
+            parser.set_picture_loading(picture_loading);
+            parser.parse()?;
+            AudioFile::Flac(parser)
+        }
+        // only the comments of Vorbis are implemented
+        Format::Ogg if probe.codec != Codec::Vorbis => {
+            return Err(Error::NotThisFormat {
+                expected: "Ogg Vorbis",
+            })
+        }
+        Format::Ogg => {
+            let mut parser = OggParser::new(path)?;
+            parser.set_parse_mode(parse_mode);
+            parser.parse()?;
+            AudioFile::Ogg(parser)
+        }
+    };
+    Ok(file)
+}
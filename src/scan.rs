@@ -0,0 +1,237 @@
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+
+use crate::probe::{open_probed, probe, Codec, Format};
+use crate::{AudioFile, Error, ParseMode, PictureLoading};
+
+/// Walks a directory tree and parses the mp3, flac and Ogg Vorbis files in a thread pool.
+///
+/// Files are recognized by their content, not by their extension, see `probe`.
+///
+/// Other Ogg codecs are skipped like files that are not audio.
+pub struct Scanner {
+    root: PathBuf,
+    threads: usize,
+    parse_mode: ParseMode,
+    picture_loading: PictureLoading,
+    progress: Option<Arc<ProgressFn>>,
+    cancel: ScanCancel,
+}
+
+type ProgressFn = dyn Fn(&ScanProgress) + Send + Sync;
+
+impl Scanner {
+    /// Create a scanner of `root` using one thread per CPU.
+    pub fn new<T: AsRef<Path>>(root: T) -> Self {
+        Scanner {
+            root: root.as_ref().to_owned(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            parse_mode: ParseMode::default(),
+            picture_loading: PictureLoading::default(),
+            progress: None,
+            cancel: ScanCancel::default(),
+        }
+    }
+
+    /// Number of files parsed at the same time, at least 1.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Passed to every parser, see `ID3Parser::set_parse_mode`.
+    pub fn set_parse_mode(&mut self, mode: ParseMode) {
+        self.parse_mode = mode;
+    }
+
+    /// Passed to every parser, see `ID3Parser::set_picture_loading`.
+    pub fn set_picture_loading(&mut self, loading: PictureLoading) {
+        self.picture_loading = loading;
+    }
+
+    /// Call `f` from the worker threads after each file, audio or not.
+    pub fn set_progress<F>(&mut self, f: F)
+    where
+        F: Fn(&ScanProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(f));
+    }
+
+    /// Return a handle that stops the scan from another thread.
+    pub fn cancel_handle(&self) -> ScanCancel {
+        self.cancel.clone()
+    }
+
+    /// Start scanning in the background.
+    ///
+    /// The results arrive in the order the files are parsed, dropping the iterator cancels the scan.
+    pub fn scan(self) -> Scan {
+        let (sender, receiver) = mpsc::sync_channel(self.threads * 2);
+        let cancel = self.cancel.clone();
+        thread::spawn(move || self.run(sender));
+        Scan { receiver, cancel }
+    }
+
+    /// Walk the tree, then share the files between the workers.
+    fn run(self, sender: SyncSender<ScanEntry>) {
+        let mut files: Vec<PathBuf> = Vec::new();
+        if let Err(e) = walk(&self.root, &mut files, &sender, &self.cancel) {
+            let _ = sender.send(ScanEntry::new(self.root.clone(), Err(e.into())));
+        }
+        let files = Arc::new(files);
+        let next = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+        let scanner = Arc::new(self);
+        let workers: Vec<_> = (0..scanner.threads)
+            .map(|_| {
+                let (scanner, files, next, done, sender) = (
+                    scanner.clone(),
+                    files.clone(),
+                    next.clone(),
+                    done.clone(),
+                    sender.clone(),
+                );
+                thread::spawn(move || {
+                    while !scanner.cancel.is_cancelled() {
+                        let Some(path) = files.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                            break;
+                        };
+                        // a parser bug must not take the remaining files of the worker with it
+                        let result =
+                            panic::catch_unwind(AssertUnwindSafe(|| scanner.parse_file(path)))
+                                .unwrap_or_else(|_| {
+                                    Err(io::Error::other("the parser panicked on this file").into())
+                                })
+                                .transpose();
+                        let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                        if let Some(progress) = &scanner.progress {
+                            progress(&ScanProgress::new(done, files.len(), path.clone()));
+                        }
+                        if let Some(result) = result {
+                            if sender.send(ScanEntry::new(path.clone(), result)).is_err() {
+                                // the iterator is dropped
+                                break;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            let _ = worker.join();
+        }
+    }
+
+    /// Parse `path`, or return None if it is not an mp3, flac or Ogg Vorbis file.
+    fn parse_file(&self, path: &Path) -> Result<Option<AudioFile>, Error> {
+        match probe(path)? {
+            // Opus, Speex and FLAC in Ogg are recognized but their comments are not implemented
+            Some(probe) if probe.format == Format::Ogg && probe.codec != Codec::Vorbis => Ok(None),
+            Some(probe) => {
+                open_probed(path, probe, self.parse_mode, self.picture_loading).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Collect the regular files under `dir`, without following symbolic links to directories.
+///
+/// Unreadable subdirectories are reported through `sender` and skipped.
+fn walk(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+    sender: &SyncSender<ScanEntry>,
+    cancel: &ScanCancel,
+) -> io::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|v| v.ok().map(|v| v.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let is_link = fs::symlink_metadata(&path).is_ok_and(|v| v.file_type().is_symlink());
+        if path.is_dir() {
+            if is_link {
+                continue;
+            }
+            if let Err(e) = walk(&path, files, sender, cancel) {
+                let _ = sender.send(ScanEntry::new(path, Err(e.into())));
+            }
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Result of one file, or of a directory that cannot be read.
+pub struct ScanEntry {
+    pub path: PathBuf,
+    pub result: Result<AudioFile, Error>,
+}
+
+impl ScanEntry {
+    pub fn new(path: PathBuf, result: Result<AudioFile, Error>) -> Self {
+        ScanEntry { path, result }
+    }
+}
+
+/// Passed to the callback of `Scanner::set_progress`.
+#[derive(Debug, Clone)]
+pub struct ScanProgress {
+    /// files checked so far, including those that are not audio
+    pub done: usize,
+    /// files found in the tree
+    pub total: usize,
+    /// the file just checked
+    pub path: PathBuf,
+}
+
+impl ScanProgress {
+    pub fn new(done: usize, total: usize, path: PathBuf) -> Self {
+        ScanProgress { done, total, path }
+    }
+}
+
+/// Stops a scan, see `Scanner::cancel_handle`.
+#[derive(Debug, Default, Clone)]
+pub struct ScanCancel(Arc<AtomicBool>);
+
+impl ScanCancel {
+    /// The files being parsed are finished, the others are skipped.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Iterator over the results of `Scanner::scan`.
+pub struct Scan {
+    receiver: Receiver<ScanEntry>,
+    cancel: ScanCancel,
+}
+
+impl Iterator for Scan {
+    type Item = ScanEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for Scan {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+
+use crate::id3::id3v1_tag::{ID3v1, GENRES};
+
+/// Common tag fields, whatever the format.
+///
+/// https://docs.mp3tag.de/mapping/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StandardKey {
+    Title,
+    Artist,
+    AlbumArtist,
+    Album,
+    TrackNumber,
+    TrackTotal,
+    DiscNumber,
+    DiscTotal,
+    Date,
+    Genre,
+    Composer,
+    Lyricist,
+    Conductor,
+    Comment,
+    /// unsynchronised lyrics
+    Lyrics,
+    ISRC,
+    BPM,
+    Copyright,
+    Publisher,
+    /// person or organisation who encoded the file
+    EncodedBy,
+    /// software used to encode the file
+    Encoder,
+    MusicBrainzTrackId,
+    MusicBrainzAlbumId,
+    MusicBrainzArtistId,
+    MusicBrainzAlbumArtistId,
+    MusicBrainzReleaseGroupId,
+    ReplayGainTrackGain,
+    ReplayGainTrackPeak,
+    ReplayGainAlbumGain,
+    ReplayGainAlbumPeak,
+}
+
+impl StandardKey {
+    /// Map a Vorbis comment field name, case insensitive.
+    pub fn from_vorbis(key: &str) -> Option<Self> {
+        let key = match key.to_uppercase().as_str() {
+            "TITLE" => StandardKey::Title,
+            "ARTIST" => StandardKey::Artist,
+            "ALBUMARTIST" | "ALBUM ARTIST" => StandardKey::AlbumArtist,
+            "ALBUM" => StandardKey::Album,
+            "TRACKNUMBER" => StandardKey::TrackNumber,
+            "TRACKTOTAL" | "TOTALTRACKS" => StandardKey::TrackTotal,
+            "DISCNUMBER" => StandardKey::DiscNumber,
+            "DISCTOTAL" | "TOTALDISCS" => StandardKey::DiscTotal,
+            "DATE" | "YEAR" => StandardKey::Date,
+            "GENRE" => StandardKey::Genre,
+            "COMPOSER" => StandardKey::Composer,
+            "LYRICIST" => StandardKey::Lyricist,
+            "CONDUCTOR" => StandardKey::Conductor,
+            "COMMENT" | "DESCRIPTION" => StandardKey::Comment,
+            "LYRICS" | "UNSYNCEDLYRICS" => StandardKey::Lyrics,
+            "ISRC" => StandardKey::ISRC,
+            "BPM" => StandardKey::BPM,
+            "COPYRIGHT" => StandardKey::Copyright,
+            "ORGANIZATION" | "LABEL" | "PUBLISHER" => StandardKey::Publisher,
+            "ENCODEDBY" | "ENCODED-BY" => StandardKey::EncodedBy,
+            "ENCODER" => StandardKey::Encoder,
+            _ => return Self::from_txxx(key),
+        };
+        Some(key)
+    }
+
+    /// Map an ID3v2 frame identifier, such as "TIT2".
+    ///
+    /// TXXX, COMM and UFID depend on their description, see `from_txxx`.
+    pub fn from_id3(identifier: &str) -> Option<Self> {
+        let key = match identifier {
+            "TIT2" => StandardKey::Title,
+            "TPE1" => StandardKey::Artist,
+            "TPE2" => StandardKey::AlbumArtist,
+            "TALB" => StandardKey::Album,
+            "TRCK" => StandardKey::TrackNumber,
+            "TPOS" => StandardKey::DiscNumber,
+            "TDRC" | "TYER" => StandardKey::Date,
+            "TCON" => StandardKey::Genre,
+            "TCOM" => StandardKey::Composer,
+            "TEXT" => StandardKey::Lyricist,
+            "TPE3" => StandardKey::Conductor,
+            "USLT" => StandardKey::Lyrics,
+            "TSRC" => StandardKey::ISRC,
+            "TBPM" => StandardKey::BPM,
+            "TCOP" => StandardKey::Copyright,
+            "TPUB" => StandardKey::Publisher,
+            "TENC" => StandardKey::EncodedBy,
+            "TSSE" => StandardKey::Encoder,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    /// Map the description of an ID3v2 TXXX frame, case insensitive.
+    ///
+    /// The MusicBrainz and ReplayGain fields are stored this way,
+    ///
+    /// and their Vorbis names are accepted as well.
+    pub fn from_txxx(description: &str) -> Option<Self> {
+        let key = match description.to_uppercase().as_str() {
+            "MUSICBRAINZ RELEASE TRACK ID" | "MUSICBRAINZ_TRACKID" => {
+                StandardKey::MusicBrainzTrackId
+            }
+            "MUSICBRAINZ ALBUM ID" | "MUSICBRAINZ_ALBUMID" => StandardKey::MusicBrainzAlbumId,
+            "MUSICBRAINZ ARTIST ID" | "MUSICBRAINZ_ARTISTID" => StandardKey::MusicBrainzArtistId,
+            "MUSICBRAINZ ALBUM ARTIST ID" | "MUSICBRAINZ_ALBUMARTISTID" => {
+                StandardKey::MusicBrainzAlbumArtistId
+            }
+            "MUSICBRAINZ RELEASE GROUP ID" | "MUSICBRAINZ_RELEASEGROUPID" => {
+                StandardKey::MusicBrainzReleaseGroupId
+            }
+            "REPLAYGAIN_TRACK_GAIN" => StandardKey::ReplayGainTrackGain,
+            "REPLAYGAIN_TRACK_PEAK" => StandardKey::ReplayGainTrackPeak,
+            "REPLAYGAIN_ALBUM_GAIN" => StandardKey::ReplayGainAlbumGain,
+            "REPLAYGAIN_ALBUM_PEAK" => StandardKey::ReplayGainAlbumPeak,
+            _ => return None,
+        };
+        Some(key)
+    }
+}
+
+/// Tag fields of any format, under a `StandardKey` when there is one,
+///
+/// or under their native name otherwise, such as "MOOD" or "TXXX:MOOD".
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tag {
+    standard: BTreeMap<StandardKey, Vec<String>>,
+    /// the keys are in upper case
+    native: BTreeMap<String, Vec<String>>,
+}
+
+impl Tag {
+    /// Return all values of `key`.
+    pub fn get(&self, key: StandardKey) -> Option<&[String]> {
+        self.standard.get(&key).map(|v| v.as_slice())
+    }
+
+    /// Return the first value of `key`.
+    pub fn get_first(&self, key: StandardKey) -> Option<&str> {
+        self.get(key).and_then(|v| v.first()).map(|v| v.as_str())
+    }
+
+    /// Return all values of a field that has no standard key.
+    ///
+    /// This method is case insensitive.
+    pub fn get_native(&self, key: &str) -> Option<&[String]> {
+        self.native.get(&key.to_uppercase()).map(|v| v.as_slice())
+    }
+
+    /// Standard fields, in the order of `StandardKey`.
+    pub fn standard(&self) -> impl Iterator<Item = (&StandardKey, &Vec<String>)> {
+        self.standard.iter()
+    }
+
+    /// Fields that have no standard key, sorted by name.
+    pub fn native(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.native.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.standard.is_empty() && self.native.is_empty()
+    }
+
+    /// Append a value to `key`, empty values are ignored.
+    ///
+    /// "3/12" is split into the number and the total for TrackNumber and DiscNumber.
+    pub fn push(&mut self, key: StandardKey, value: &str) {
+        let value = value.trim_end_matches('\0');
+        if value.is_empty() {
+            return;
+        }
+        let total = match key {
+            StandardKey::TrackNumber => Some(StandardKey::TrackTotal),
+            StandardKey::DiscNumber => Some(StandardKey::DiscTotal),
+            _ => None,
+        };
+        if let (Some(total), Some((number, count))) = (total, value.split_once('/')) {
+            self.push(key, number);
+            self.push(total, count);
+            return;
+        }
+        let value = match key {
+            StandardKey::Genre => genre_name(value),
+            _ => value.to_string(),
+        };
+        self.standard.entry(key).or_default().push(value);
+    }
+
+    /// Append a value to a field that has no standard key.
+    pub fn push_native(&mut self, key: &str, value: &str) {
+        let value = value.trim_end_matches('\0');
+        if value.is_empty() {
+            return;
+        }
+        self.native
+            .entry(key.to_uppercase())
+            .or_default()
+            .push(value.to_string());
+    }
+
+    /// Build from Vorbis comments, as stored by `FlacParser` and `OggParser`.
+    pub(crate) fn from_vorbis_comments(
+        hm: &HashMap<String, usize>,
+        comment: &[Vec<String>],
+    ) -> Self {
+        let mut tag = Tag::default();
+        let mut keys: Vec<(&String, &usize)> = hm.iter().collect();
+        keys.sort();
+        for (key, index) in keys {
+            for value in comment[*index].iter() {
+                match StandardKey::from_vorbis(key) {
+                    Some(k) => tag.push(k, value),
+                    None => tag.push_native(key, value),
+                }
+            }
+        }
+        tag
+    }
+
+    /// Fill the fields that ID3v2 left empty from ID3v1.
+    pub(crate) fn merge_id3v1(&mut self, id3v1: &ID3v1) {
+        if id3v1.header != b"TAG" {
+            return;
+        }
+        let fields = [
+            (StandardKey::Title, id3v1.title_text()),
+            (StandardKey::Artist, id3v1.artist_text()),
+            (StandardKey::Album, id3v1.album_text()),
+            (StandardKey::Date, id3v1.charset.decode(&id3v1.year)),
+            (StandardKey::Comment, id3v1.comment_text()),
+            (
+                StandardKey::TrackNumber,
+                id3v1.track().map(|v| v.to_string()).unwrap_or_default(),
+            ),
+            (
+                StandardKey::Genre,
+                id3v1.genre_name().unwrap_or_default().to_string(),
+            ),
+        ];
+        for (key, value) in fields {
+            if self.get(key).is_none() {
+                self.push(key, value.trim());
+            }
+        }
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "\nTag {{")?;
+        for (key, values) in self.standard.iter() {
+            writeln!(f, "    {:?}: {:?}", key, values)?;
+        }
+        for (key, values) in self.native.iter() {
+            writeln!(f, "    {}: {:?}", key, values)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Resolve the ID3v1 genre index of ID3v2, such as "(17)" or "17".
+fn genre_name(value: &str) -> String {
+    let index = value
+        .strip_prefix('(')
+        .and_then(|v| v.strip_suffix(')'))
+        .unwrap_or(value);
+    match index.parse::<usize>().ok().and_then(|i| GENRES.get(i)) {
+        Some(name) => name.to_string(),
+        None => value.to_string(),
+    }
+}
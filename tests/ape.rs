@@ -0,0 +1,91 @@
+mod common;
+
+use common::{ape, id3v1, mpeg_frames};
+use music_metadata::{ApeValue, ID3Parser};
+
+#[test]
+fn parse_ape_items() {
+    let mut file = mpeg_frames(2);
+    let position = file.len() as u64;
+    let mut tag = ape(&[
+        ("Artist", 0, b"One\0Two"),
+        ("Cover Art (Front)", 0x02, b"cover.png\0\x89PNG"),
+        ("Source", 0x04, b"https://example.com"),
+        ("Locked", 0x01, b"yes"),
+    ]);
+    let size = tag.len() as u32;
+    file.append(&mut tag);
+    file.append(&mut id3v1("Title", 1, 0));
+
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_ape().unwrap();
+    assert_eq!(parser.ape.position, position);
+    assert_eq!(parser.ape.size(), size);
+    assert_eq!(parser.ape.footer.version, 2000);
+    assert_eq!(
+        parser.get_ape("ARTIST"),
+        Some(vec!["One".to_string(), "Two".to_string()])
+    );
+    assert_eq!(
+        parser.get_ape("source"),
+        Some(vec!["https://example.com".to_string()])
+    );
+    assert!(parser.ape.get("locked").unwrap().read_only);
+    let ApeValue::CoverArt(cover) = &parser.ape.get("cover art (front)").unwrap().value else {
+        panic!("not a cover");
+    };
+    assert_eq!(cover.filename, "cover.png");
+    assert_eq!(cover.data, b"\x89PNG");
+}
+
+#[test]
+fn ape_before_lyrics3() {
+    let mut file = mpeg_frames(1);
+    let position = file.len() as u64;
+    file.append(&mut ape(&[("Title", 0, b"Song")]));
+    let mut lyrics3 = b"LYRICSBEGINLYR00004text".to_vec();
+    lyrics3.extend_from_slice(format!("{:06}LYRICS200", lyrics3.len()).as_bytes());
+    file.append(&mut lyrics3);
+
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_ape().unwrap();
+    assert!(parser.ape.items.is_empty());
+    parser.parse_lyrics3().unwrap();
+    parser.parse_ape().unwrap();
+    assert_eq!(parser.ape.position, position);
+    assert_eq!(parser.get_ape("title"), Some(vec!["Song".to_string()]));
+}
+
+#[test]
+fn item_beyond_the_tag() {
+    let mut tag = ape(&[("Title", 0, b"Song")]);
+    // declare 99 bytes of value
+    tag[32] = 99;
+    let mut file = mpeg_frames(1);
+    file.append(&mut tag);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    assert!(parser.parse_ape().is_err());
+}
+
+#[test]
+fn unterminated_key() {
+    let mut tag = ape(&[("Title", 0, b"")]);
+    // replace the key terminator, the items end with the key
+    tag[32 + 8 + 5] = b'x';
+    let mut file = mpeg_frames(1);
+    file.append(&mut tag);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    assert!(parser.parse_ape().is_err());
+}
+
+#[test]
+fn footer_smaller_than_itself() {
+    let mut tag = ape(&[("Title", 0, b"Song")]);
+    let length = tag.len();
+    tag[length - 20..length - 16].copy_from_slice(&8u32.to_le_bytes());
+    let mut file = mpeg_frames(1);
+    file.append(&mut tag);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_ape().unwrap();
+    assert!(parser.ape.items.is_empty());
+}
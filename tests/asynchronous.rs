@@ -0,0 +1,131 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use std::path::Path;
+
+use common::{
+    apic, flac_block, flac_picture, id3v1, id3v2, mpeg_frames, ogg_vorbis, png, stream_info,
+    temp_file, text, vorbis_comment,
+};
+use music_metadata::{FlacParser, ID3Parser, OggParser, PictureLoading};
+
+/// A large image, not a valid one but recognizable by its signature.
+fn large_image() -> Vec<u8> {
+    let mut data = png();
+    data.extend((0..3000).map(|v| (v % 251) as u8));
+    data
+}
+
+fn mp3() -> Vec<u8> {
+    let frames = [
+        ("TIT2", text("Song")),
+        // not implemented, skipped
+        ("ZZZZ", vec![1, 2, 3]),
+        ("APIC", apic("image/png", 3, "cover", &large_image())),
+        // the description is longer than the head read by the lazy loading
+        ("APIC", apic("image/png", 4, &"d".repeat(1500), &png())),
+    ];
+    let mut file = id3v2(3, &frames, 32);
+    file.append(&mut mpeg_frames(2));
+    file.append(&mut id3v1("Old title", 3, 17));
+    file
+}
+
+async fn compare_mp3(path: &Path, loading: PictureLoading) {
+    let mut sync = ID3Parser::new(path).unwrap();
+    sync.set_picture_loading(loading);
+    sync.parse_id3v2().unwrap();
+    sync.parse_id3v1().unwrap();
+
+    let file = tokio::fs::File::open(path).await.unwrap();
+    let mut parser = ID3Parser::from_async_reader(file).await.unwrap();
+    parser.set_picture_loading(loading);
+    parser.parse_id3v2_async().await.unwrap();
+    parser.parse_id3v1_async().await.unwrap();
+
+    assert_eq!(
+        parser.frames_in_order().collect::<Vec<_>>(),
+        sync.frames_in_order().collect::<Vec<_>>()
+    );
+    assert_eq!(parser.padding_size, sync.padding_size);
+    assert_eq!(sync.warnings.len(), 1);
+    assert_eq!(
+        format!("{:?}", parser.warnings),
+        format!("{:?}", sync.warnings)
+    );
+    assert_eq!(parser.id3v1.title_text(), sync.id3v1.title_text());
+    for index in 0..2 {
+        assert_eq!(
+            parser.load_picture_async(index).await.unwrap(),
+            sync.load_picture(index).unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn mp3_like_the_sync_parser() {
+    let path = temp_file("asynchronous/song.mp3", &mp3());
+    compare_mp3(&path, PictureLoading::Eager).await;
+    compare_mp3(&path, PictureLoading::Lazy).await;
+}
+
+#[tokio::test]
+async fn flac_like_the_sync_parser() {
+    let mut file = b"fLaC".to_vec();
+    file.append(&mut flac_block(0, false, &stream_info()));
+    let comments = vorbis_comment("test", &["TITLE=Song", "ARTIST=Band"]);
+    file.append(&mut flac_block(4, false, &comments));
+    let picture = flac_picture(3, "image/png", "cover", &large_image());
+    file.append(&mut flac_block(6, false, &picture));
+    file.append(&mut flac_block(1, true, &[0; 64]));
+    file.extend_from_slice(&[0xFF, 0xF8, 0, 0]);
+    let path = temp_file("asynchronous/song.flac", &file);
+
+    for loading in [PictureLoading::Eager, PictureLoading::Lazy] {
+        let mut sync = FlacParser::new(&path).unwrap();
+        sync.set_picture_loading(loading);
+        sync.parse().unwrap();
+
+        let file = tokio::fs::File::open(&path).await.unwrap();
+        let mut parser = FlacParser::from_async_reader(file).unwrap();
+        parser.set_picture_loading(loading);
+        parser.parse_async().await.unwrap();
+
+        assert_eq!(
+            parser.blocks_in_order().collect::<Vec<_>>(),
+            sync.blocks_in_order().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            parser.comments_in_order().collect::<Vec<_>>(),
+            sync.comments_in_order().collect::<Vec<_>>()
+        );
+        assert_eq!(parser.padding_length, sync.padding_length);
+        assert_eq!(parser.audio_offset, sync.audio_offset);
+        assert_eq!(parser.picture[0].location, sync.picture[0].location);
+        assert_eq!(
+            parser.load_picture_async(0).await.unwrap(),
+            sync.load_picture(0).unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn ogg_like_the_sync_parser() {
+    let path = temp_file(
+        "asynchronous/song.ogg",
+        &ogg_vorbis(&["TITLE=Song", "ARTIST=Band"], 100),
+    );
+    let mut sync = OggParser::new(&path).unwrap();
+    sync.parse().unwrap();
+
+    let file = tokio::fs::File::open(&path).await.unwrap();
+    let mut parser = OggParser::from_async_reader(file);
+    parser.parse_async().await.unwrap();
+
+    assert_eq!(
+        parser.comments_in_order().collect::<Vec<_>>(),
+        sync.comments_in_order().collect::<Vec<_>>()
+    );
+    assert_eq!(parser.comments_in_order().count(), 2);
+}
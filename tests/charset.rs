@@ -0,0 +1,104 @@
+mod common;
+
+use std::io::Cursor;
+
+use common::{id3v1, id3v2, mpeg_frames};
+use music_metadata::{Charset, ID3Parser, TextDecoding};
+
+/// Payload of a text frame declared as ISO-8859-1 but written in `charset`.
+fn legacy_text(value: &str, charset: Charset) -> Vec<u8> {
+    let mut payload = vec![0x00];
+    payload.append(&mut charset.encode(value, 255));
+    payload
+}
+
+/// An ID3v2.3 tag with a title and an artist written in `charset`, then an ID3v1 tag with the same title.
+fn legacy_file(title: &str, artist: &str, charset: Charset) -> Vec<u8> {
+    let frames = [
+        ("TIT2", legacy_text(title, charset)),
+        ("TPE1", legacy_text(artist, charset)),
+    ];
+    let mut file = id3v2(3, &frames, 0);
+    file.append(&mut mpeg_frames(2));
+    let mut tag = id3v1("", 0, 12);
+    let title = charset.encode(title, 30);
+    tag[3..3 + title.len()].copy_from_slice(&title);
+    file.append(&mut tag);
+    file
+}
+
+fn parse(file: &[u8], decoding: TextDecoding) -> ID3Parser<Cursor<&[u8]>> {
+    let mut parser = ID3Parser::from_bytes(file).unwrap();
+    parser.set_text_decoding(decoding);
+    parser.parse_id3v2().unwrap();
+    parser.parse_id3v1().unwrap();
+    parser
+}
+
+fn title(parser: &ID3Parser<Cursor<&[u8]>>) -> String {
+    parser.get("TIT2").unwrap().remove(0)
+}
+
+#[test]
+fn gbk_declared_as_latin1() {
+    let file = legacy_file("七里香", "周杰伦", Charset::GBK);
+
+    let parser = parse(&file, TextDecoding::Latin1);
+    assert_ne!(title(&parser), "七里香");
+    assert!(parser.id3v2_charset.is_none());
+
+    let parser = parse(&file, TextDecoding::Detect);
+    assert_eq!(title(&parser), "七里香");
+    assert_eq!(parser.get("TPE1"), Some(vec!["周杰伦".to_string()]));
+    let detection = parser.id3v2_charset.unwrap();
+    assert_eq!(detection.charset, Charset::GBK);
+    assert!(detection.confidence > 0.5, "{}", detection.confidence);
+    assert_eq!(parser.id3v1.title_text(), "七里香");
+    assert_eq!(parser.id3v1_charset.unwrap().charset, Charset::GBK);
+
+    let parser = parse(&file, TextDecoding::Force(Charset::GBK));
+    assert_eq!(title(&parser), "七里香");
+    assert_eq!(parser.id3v1.title_text(), "七里香");
+    assert!(parser.id3v2_charset.is_none());
+    assert!(parser.id3v1_charset.is_none());
+}
+
+#[test]
+fn cp1251_declared_as_latin1() {
+    let file = legacy_file("Группа крови", "Кино", Charset::CP1251);
+
+    let parser = parse(&file, TextDecoding::Detect);
+    assert_eq!(title(&parser), "Группа крови");
+    assert_eq!(parser.get("TPE1"), Some(vec!["Кино".to_string()]));
+    let detection = parser.id3v2_charset.unwrap();
+    assert_eq!(detection.charset, Charset::CP1251);
+    assert!(detection.confidence > 0.5, "{}", detection.confidence);
+    assert_eq!(parser.id3v1.title_text(), "Группа крови");
+    assert_eq!(parser.id3v1_charset.unwrap().charset, Charset::CP1251);
+
+    let parser = parse(&file, TextDecoding::Force(Charset::CP1251));
+    assert_eq!(title(&parser), "Группа крови");
+    assert_eq!(parser.id3v1.title_text(), "Группа крови");
+    assert!(parser.id3v2_charset.is_none());
+}
+
+#[test]
+fn ascii_is_reported_as_latin1() {
+    let file = legacy_file("Song", "Band", Charset::ISO_8859_1);
+    let parser = parse(&file, TextDecoding::Detect);
+    assert_eq!(title(&parser), "Song");
+    let detection = parser.id3v2_charset.unwrap();
+    assert_eq!(detection.charset, Charset::ISO_8859_1);
+    assert_eq!(detection.confidence, 1.0);
+}
+
+#[test]
+fn forced_charset_does_not_touch_unicode_frames() {
+    let mut payload = vec![0x03];
+    payload.extend_from_slice("Группа крови".as_bytes());
+    let file = id3v2(4, &[("TIT2", payload)], 0);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.set_text_decoding(TextDecoding::Force(Charset::CP1251));
+    parser.parse_id3v2().unwrap();
+    assert_eq!(title(&parser), "Группа крови");
+}
@@ -0,0 +1,205 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use common::{apic, flac, id3v1, id3v2, mpeg_frames, png, temp_file, text};
+use music_metadata::FlacParser;
+
+fn run(args: &[&str], files: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_music-metadata"))
+        .args(args)
+        .args(files)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// Payload of a USLT frame in ISO-8859-1.
+fn uslt(lyrics: &str) -> Vec<u8> {
+    let mut payload = b"\0eng\0".to_vec();
+    payload.extend_from_slice(lyrics.as_bytes());
+    payload
+}
+
+/// An mp3 file with a title, a picture and lyrics in ID3v2, then an ID3v1 tag.
+fn mp3(name: &str) -> PathBuf {
+    let frames = [
+        ("TIT2", text("Song")),
+        ("APIC", apic("image/png", 3, "", &png())),
+        ("USLT", uslt("la la la")),
+    ];
+    let mut file = id3v2(3, &frames, 0);
+    file.append(&mut mpeg_frames(4));
+    file.append(&mut id3v1("Old title", 3, 17));
+    temp_file(name, &file)
+}
+
+#[test]
+fn dump_and_layout() {
+    let path = mp3("cli/dump.mp3");
+    let output = run(&["dump"], &[&path]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Song"));
+    assert!(stdout(&output).contains("picture 0: Png 3x2"));
+
+    let output = run(&["dump", "--json", "--pictures"], &[&path]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let dumps: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(dumps.as_array().unwrap().len(), 1);
+    assert!(dumps[0]["dump"]["Mp3"]["frames"].is_array());
+
+    let output = run(&["layout"], &[&path]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with(&format!("{}: ", path.display())));
+}
+
+#[test]
+fn get_standard_and_native_keys() {
+    let path = mp3("cli/get.mp3");
+    assert_eq!(stdout(&run(&["get", "title"], &[&path])), "Song\n");
+    assert_eq!(stdout(&run(&["get", "TIT2"], &[&path])), "Song\n");
+    // from ID3v1
+    assert_eq!(stdout(&run(&["get", "tracknumber"], &[&path])), "3\n");
+    assert_eq!(stdout(&run(&["get", "genre"], &[&path])), "Rock\n");
+    let output = run(&["get", "composer"], &[&path]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn set_then_get() {
+    let path = temp_file("cli/plain.mp3", &mpeg_frames(4));
+    let output = run(&["set", "title=New"], &[&path]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&run(&["get", "title"], &[&path])), "New\n");
+    assert!(run(&["set", "tracknumber=7/9"], &[&path]).status.success());
+    assert_eq!(stdout(&run(&["get", "tracknumber"], &[&path])), "7\n");
+    assert!(run(&["remove", "title"], &[&path]).status.success());
+    assert_eq!(stdout(&run(&["get", "title"], &[&path])), "");
+
+    let path = temp_file("cli/song.flac", &flac(&["TITLE=Song"], Some(64), 100));
+    assert!(run(&["set", "title=New"], &[&path]).status.success());
+    assert!(run(&["set", "MOOD=Calm"], &[&path]).status.success());
+    assert_eq!(stdout(&run(&["get", "title"], &[&path])), "New\n");
+    assert_eq!(stdout(&run(&["get", "mood"], &[&path])), "Calm\n");
+    assert!(run(&["remove", "title"], &[&path]).status.success());
+    assert_eq!(stdout(&run(&["get", "title"], &[&path])), "");
+}
+
+#[test]
+fn set_a_field_held_by_id3v2() {
+    let path = mp3("cli/held.mp3");
+    let before = fs::read(&path).unwrap();
+    for args in [&["set", "title=New"][..], &["remove", "title"]] {
+        let output = run(args, &[&path]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stderr(&output).contains("ID3v2"), "{}", stderr(&output));
+        assert_eq!(fs::read(&path).unwrap(), before);
+    }
+    assert_eq!(stdout(&run(&["get", "title"], &[&path])), "Song\n");
+
+    // only in ID3v1
+    assert!(run(&["set", "album=Album"], &[&path]).status.success());
+    assert_eq!(stdout(&run(&["get", "album"], &[&path])), "Album\n");
+    let output = run(&["set", "MOOD=Calm"], &[&path]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn pictures_and_lyrics() {
+    let path = mp3("cli/pictures/song.mp3");
+    let out = path.with_file_name("out");
+    fs::create_dir_all(&out).unwrap();
+    let out = out.to_str().unwrap();
+    let output = run(&["pictures", "extract", "--out", out], &[&path]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let written = Path::new(out).join("song_mp3_0.png");
+    assert_eq!(fs::read(written).unwrap(), png());
+    let cover = temp_file("cli/pictures/cover.png", &png());
+    let output = run(&["pictures", "add", cover.to_str().unwrap()], &[&path]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = run(&["lyrics", "export"], &[&path]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let written = path.with_file_name("song.txt");
+    assert_eq!(fs::read_to_string(written).unwrap(), "la la la");
+
+    let flac_path = temp_file("cli/pictures/song.flac", &flac(&[], Some(64), 100));
+    let output = run(&["pictures", "add", cover.to_str().unwrap()], &[&flac_path]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(run(&["pictures", "extract"], &[&flac_path])
+        .status
+        .success());
+    let written = flac_path.with_file_name("song_flac_0.png");
+    assert_eq!(fs::read(written).unwrap(), png());
+    assert!(run(&["pictures", "remove"], &[&flac_path]).status.success());
+    let mut parser = FlacParser::new(&flac_path).unwrap();
+    parser.parse().unwrap();
+    assert!(parser.picture.is_empty());
+}
+
+#[test]
+fn verify() {
+    let path = mp3("cli/verify/good.mp3");
+    let output = run(&["verify"], &[&path]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), format!("{}: ok\n", path.display()));
+
+    // junk between the frames
+    let mut file = mpeg_frames(2);
+    file.extend_from_slice(&[0x55; 100]);
+    file.append(&mut mpeg_frames(2));
+    let broken = temp_file("cli/verify/broken.mp3", &file);
+    let output = run(&["verify"], &[&broken]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).contains("1 junk regions"),
+        "{}",
+        stderr(&output)
+    );
+    // the other files are still checked
+    let output = run(&["verify"], &[&broken, &path]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), format!("{}: ok\n", path.display()));
+}
+
+#[test]
+fn glob_expansion() {
+    let a = temp_file("cli/glob/a.flac", &flac(&["TITLE=A"], None, 10));
+    let b = temp_file("cli/glob/b.flac", &flac(&["TITLE=B"], None, 10));
+    temp_file("cli/glob/c.txt", b"not audio");
+    let dir = a.parent().unwrap();
+    let pattern = dir.join("*.flac");
+    let output = run(&["get", "title"], &[&pattern]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!("{}: A\n{}: B\n", a.display(), b.display())
+    );
+
+    let output = run(&["get", "title"], &[&dir.join("*.ogg"), &a]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("no such file"));
+    assert_eq!(stdout(&output), "A\n");
+}
+
+#[test]
+fn usage_error() {
+    let output = run(&[], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = run(&["get", "title"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = run(&["frobnicate"], &[Path::new("song.mp3")]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,231 @@
+//! Builders of small synthetic files, shared by the integration tests.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Encode `n` on 4 bytes of 7 bits, as the sizes of ID3v2.4.
+pub fn synchsafe(n: u32) -> [u8; 4] {
+    [
+        (n >> 21) as u8 & 0x7F,
+        (n >> 14) as u8 & 0x7F,
+        (n >> 7) as u8 & 0x7F,
+        n as u8 & 0x7F,
+    ]
+}
+
+/// An ID3v2 tag of major version `version` (3 or 4) holding `frames`, followed by `padding` zeros.
+pub fn id3v2(version: u8, frames: &[(&str, Vec<u8>)], padding: usize) -> Vec<u8> {
+    let mut body: Vec<u8> = Vec::new();
+    for (id, payload) in frames {
+        body.extend_from_slice(id.as_bytes());
+        match version {
+            4 => body.extend_from_slice(&synchsafe(payload.len() as u32)),
+            _ => body.extend_from_slice(&(payload.len() as u32).to_be_bytes()),
+        }
+        body.extend_from_slice(&[0, 0]);
+        body.extend_from_slice(payload);
+    }
+    body.resize(body.len() + padding, 0);
+    let mut tag = vec![b'I', b'D', b'3', version, 0, 0];
+    tag.extend_from_slice(&synchsafe(body.len() as u32));
+    tag.append(&mut body);
+    tag
+}
+
+/// Payload of a text frame in ISO-8859-1.
+pub fn text(value: &str) -> Vec<u8> {
+    let mut payload = vec![0x00];
+    payload.extend_from_slice(value.as_bytes());
+    payload
+}
+
+/// `count` MPEG-1 Layer III frames of 417 bytes, 128 kbit/s, 44100 Hz, joint stereo, without CRC.
+pub fn mpeg_frames(count: usize) -> Vec<u8> {
+    let mut frames: Vec<u8> = Vec::new();
+    for _ in 0..count {
+        let mut frame = vec![0u8; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x40]);
+        frames.append(&mut frame);
+    }
+    frames
+}
+
+/// A 128-byte ID3v1.1 tag.
+pub fn id3v1(title: &str, track: u8, genre: u8) -> Vec<u8> {
+    let mut tag = b"TAG".to_vec();
+    let mut field = |value: &[u8], length: usize| {
+        let mut bytes = value.to_vec();
+        bytes.resize(length, 0);
+        tag.extend_from_slice(&bytes);
+    };
+    field(title.as_bytes(), 30);
+    field(b"", 30);
+    field(b"", 30);
+    field(b"2001", 4);
+    field(b"", 28);
+    field(&[0, track], 2);
+    tag.push(genre);
+    tag
+}
+
+/// Payload of an APIC frame in ISO-8859-1.
+pub fn apic(mime: &str, pic_type: u8, description: &str, data: &[u8]) -> Vec<u8> {
+    let mut payload = vec![0x00];
+    payload.extend_from_slice(mime.as_bytes());
+    payload.push(0);
+    payload.push(pic_type);
+    payload.extend_from_slice(description.as_bytes());
+    payload.push(0);
+    payload.extend_from_slice(data);
+    payload
+}
+
+/// A FLAC metadata block, `kind` is the block type number.
+pub fn flac_block(kind: u8, is_last: bool, data: &[u8]) -> Vec<u8> {
+    let length = (data.len() as u32).to_be_bytes();
+    let flag = if is_last { 0x80 } else { 0x00 };
+    let mut block = vec![flag | kind, length[1], length[2], length[3]];
+    block.extend_from_slice(data);
+    block
+}
+
+/// STREAMINFO of a 44100 Hz, 2 channels, 16 bits stream.
+pub fn stream_info() -> Vec<u8> {
+    let mut data = vec![0x10, 0x00, 0x10, 0x00, 0, 0, 0, 0, 0, 0];
+    // 44100 Hz on 20 bits, 2 channels on 3 bits, 16 bits per sample on 5 bits
+    data.extend_from_slice(&[0x0A, 0xC4, 0x42, 0xF0]);
+    data.resize(34, 0);
+    data
+}
+
+/// Data of a VORBIS_COMMENT block.
+pub fn vorbis_comment(vendor: &str, comments: &[&str]) -> Vec<u8> {
+    let mut data = (vendor.len() as u32).to_le_bytes().to_vec();
+    data.extend_from_slice(vendor.as_bytes());
+    data.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in comments {
+        data.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        data.extend_from_slice(comment.as_bytes());
+    }
+    data
+}
+
+/// Data of a PICTURE block, with zero dimensions.
+pub fn flac_picture(pic_type: u32, mime: &str, description: &str, data: &[u8]) -> Vec<u8> {
+    let mut block = pic_type.to_be_bytes().to_vec();
+    for field in [mime.as_bytes(), description.as_bytes()] {
+        block.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        block.extend_from_slice(field);
+    }
+    block.extend_from_slice(&[0; 16]);
+    block.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    block.extend_from_slice(data);
+    block
+}
+
+/// A FLAC file with STREAMINFO, the comments, `padding` bytes of PADDING if any, and `audio` bytes of frames.
+pub fn flac(comments: &[&str], padding: Option<usize>, audio: usize) -> Vec<u8> {
+    let mut file = b"fLaC".to_vec();
+    file.append(&mut flac_block(0, false, &stream_info()));
+    let data = vorbis_comment("test", comments);
+    file.append(&mut flac_block(4, padding.is_none(), &data));
+    if let Some(padding) = padding {
+        file.append(&mut flac_block(1, true, &vec![0; padding]));
+    }
+    file.extend((0..audio).map(|v| (v % 251) as u8));
+    file
+}
+
+/// The first page of an Ogg stream holding `packet`, with a zero CRC.
+pub fn ogg_page(packet: &[u8]) -> Vec<u8> {
+    ogg_packets_page(0x02, &[packet])
+}
+
+/// An Ogg page holding whole `packets`, `header_type` is 0x02 for the first page and 0x04 for the last one.
+pub fn ogg_packets_page(header_type: u8, packets: &[&[u8]]) -> Vec<u8> {
+    let mut page = b"OggS".to_vec();
+    // version, header type, granule position, serial number, sequence number, CRC
+    page.extend_from_slice(&[0, header_type]);
+    page.resize(26, 0);
+    let mut segments: Vec<u8> = Vec::new();
+    for packet in packets {
+        segments.extend(std::iter::repeat_n(255, packet.len() / 255));
+        segments.push((packet.len() % 255) as u8);
+    }
+    page.push(segments.len() as u8);
+    page.append(&mut segments);
+    for packet in packets {
+        page.extend_from_slice(packet);
+    }
+    page
+}
+
+/// An Ogg Vorbis file of 44100 Hz stereo with the comments, followed by a page of `audio` bytes.
+pub fn ogg_vorbis(comments: &[&str], audio: usize) -> Vec<u8> {
+    let mut identification = b"\x01vorbis".to_vec();
+    identification.extend_from_slice(&[0, 0, 0, 0, 2]);
+    identification.extend_from_slice(&44100u32.to_le_bytes());
+    identification.extend_from_slice(&[0; 12]);
+    identification.extend_from_slice(&[0xB8, 0x01]);
+    let mut comment = b"\x03vorbis".to_vec();
+    comment.append(&mut vorbis_comment("test", comments));
+    comment.push(0x01);
+    let mut setup = b"\x05vorbis".to_vec();
+    setup.resize(300, 0x42);
+    setup.push(0x01);
+    let audio: Vec<u8> = (0..audio).map(|v| (v % 251) as u8).collect();
+
+    let mut file = ogg_packets_page(0x02, &[&identification]);
+    file.append(&mut ogg_packets_page(0x00, &[&comment, &setup]));
+    file.append(&mut ogg_packets_page(0x04, &[&audio]));
+    file
+}
+
+/// An APEv2 tag with a header, each item is a key, flags and value.
+pub fn ape(items: &[(&str, u32, &[u8])]) -> Vec<u8> {
+    let mut body: Vec<u8> = Vec::new();
+    for (key, flags, value) in items {
+        body.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        body.extend_from_slice(&flags.to_le_bytes());
+        body.extend_from_slice(key.as_bytes());
+        body.push(0);
+        body.extend_from_slice(value);
+    }
+    let size = body.len() as u32 + 32;
+    let header_or_footer = |flags: u32| {
+        let mut block = b"APETAGEX".to_vec();
+        block.extend_from_slice(&2000u32.to_le_bytes());
+        block.extend_from_slice(&size.to_le_bytes());
+        block.extend_from_slice(&(items.len() as u32).to_le_bytes());
+        block.extend_from_slice(&flags.to_le_bytes());
+        block.extend_from_slice(&[0; 8]);
+        block
+    };
+    let mut tag = header_or_footer(0xA0000000);
+    tag.append(&mut body);
+    tag.append(&mut header_or_footer(0x80000000));
+    tag
+}
+
+/// The smallest valid PNG header, 3x2 pixels in RGBA.
+pub fn png() -> Vec<u8> {
+    let mut data = b"\x89PNG\r\n\x1A\n\x00\x00\x00\x0DIHDR".to_vec();
+    data.extend_from_slice(&3u32.to_be_bytes());
+    data.extend_from_slice(&2u32.to_be_bytes());
+    // 8 bits, RGBA
+    data.extend_from_slice(&[8, 6, 0, 0, 0]);
+    data.extend_from_slice(&[0; 4]);
+    data
+}
+
+/// Write `bytes` to a new file named after `name` in the temporary directory.
+///
+/// `name` may contain subdirectories, which are created.
+pub fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("music-metadata-{}", std::process::id()));
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, bytes).unwrap();
+    path
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::{
+    ape, apic, flac_block, flac_picture, id3v1, id3v2, mpeg_frames, ogg_vorbis, png, stream_info,
+    text, vorbis_comment,
+};
+use music_metadata::{
+    ApeValue, FlacDump, FlacParser, FrameDump, ID3Dump, ID3Parser, OggDump, OggParser, PictureData,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Serialize `dump` to JSON and back, the result must serialize to the same JSON.
+fn round_trip<T: Serialize + DeserializeOwned>(dump: &T) -> T {
+    let json = serde_json::to_string(dump).unwrap();
+    let back: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        serde_json::to_value(&back).unwrap(),
+        serde_json::to_value(dump).unwrap()
+    );
+    back
+}
+
+fn mp3() -> Vec<u8> {
+    let frames = [
+        ("TIT2", text("Song")),
+        ("APIC", apic("image/png", 3, "cover", &png())),
+    ];
+    let mut file = id3v2(3, &frames, 16);
+    file.append(&mut mpeg_frames(2));
+    file.append(&mut ape(&[
+        ("Artist", 0, b"Band"),
+        ("Cover Art (Front)", 0x02, b"cover.png\0\x89PNG"),
+    ]));
+    file.append(&mut id3v1("Song", 3, 17));
+    file
+}
+
+fn mp3_dump(pictures: PictureData) -> ID3Dump {
+    let file = mp3();
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v2().unwrap();
+    parser.parse_ape().unwrap();
+    parser.parse_id3v1().unwrap();
+    parser.dump(pictures)
+}
+
+fn frame<'a>(dump: &'a ID3Dump, identifier: &str) -> &'a FrameDump {
+    dump.frames
+        .iter()
+        .find(|v| v.identifier == identifier)
+        .unwrap()
+}
+
+fn cover_art(dump: &ID3Dump) -> &[u8] {
+    let ApeValue::CoverArt(cover) = &dump.ape.get("cover art (front)").unwrap().value else {
+        panic!("not a cover");
+    };
+    &cover.data
+}
+
+#[test]
+fn mp3_round_trip() {
+    let dump = round_trip(&mp3_dump(PictureData::Base64));
+    assert_eq!(dump.frames.len(), 2);
+    // followed by the picture type, as `get_raw`
+    assert_eq!(frame(&dump, "APIC").raw, [png(), vec![3]].concat());
+    assert_eq!(frame(&dump, "APIC").message, "cover");
+    assert_eq!(cover_art(&dump), b"\x89PNG");
+    assert_eq!(dump.id3v1.title_text(), "Song");
+
+    let dump = round_trip(&mp3_dump(PictureData::Omit));
+    assert!(frame(&dump, "APIC").raw.is_empty());
+    assert_eq!(frame(&dump, "APIC").message, "cover");
+    assert!(cover_art(&dump).is_empty());
+    assert_eq!(dump.ape.items.len(), 2);
+}
+
+fn flac_dump(pictures: PictureData) -> FlacDump {
+    let mut file = b"fLaC".to_vec();
+    file.append(&mut flac_block(0, false, &stream_info()));
+    file.append(&mut flac_block(
+        4,
+        false,
+        &vorbis_comment("test", &["TITLE=Song"]),
+    ));
+    let picture = flac_picture(3, "image/png", "cover", &png());
+    file.append(&mut flac_block(6, true, &picture));
+    file.extend_from_slice(&[0xFF, 0xF8, 0, 0]);
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    parser.parse().unwrap();
+    parser.dump(pictures)
+}
+
+#[test]
+fn flac_round_trip() {
+    let dump = round_trip(&flac_dump(PictureData::Base64));
+    assert_eq!(dump.picture[0].data, png());
+    assert_eq!(dump.picture[0].description, "cover");
+
+    let dump = round_trip(&flac_dump(PictureData::Omit));
+    assert!(dump.picture[0].data.is_empty());
+    assert_eq!(dump.picture[0].mime, "image/png");
+}
+
+#[test]
+fn ogg_round_trip() {
+    let file = ogg_vorbis(&["TITLE=Song", "ARTIST=Band"], 100);
+    let mut parser = OggParser::from_bytes(&file);
+    parser.parse().unwrap();
+    let dump: OggDump = round_trip(&parser.dump());
+    let title = dump.vorbis_comment.hm["TITLE"];
+    assert_eq!(dump.vorbis_comment.comment[title], ["Song"]);
+    assert_eq!(dump.vorbis_comment.hm.len(), 2);
+}
@@ -0,0 +1,209 @@
+mod common;
+
+use std::fs;
+
+use common::{flac, id3v2, png, temp_file, text};
+use music_metadata::{Error, FlacParser, FlacPicType, ParseMode, PictureLoading};
+
+#[test]
+fn parse_comments() {
+    let file = flac(&["TITLE=Song", "artist=A", "ARTIST=B"], Some(64), 100);
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    parser.parse().unwrap();
+    assert_eq!(parser.get("title"), Some(vec!["Song".to_string()]));
+    assert_eq!(
+        parser.get("Artist"),
+        Some(vec!["A".to_string(), "B".to_string()])
+    );
+    assert_eq!(parser.stream_info.sample_rate, 44100);
+    assert_eq!(parser.padding_length, 64);
+    assert_eq!(parser.audio_offset, file.len() as u64 - 100);
+}
+
+#[test]
+fn lenient_mode_skips_broken_comments() {
+    let file = flac(&["TITLE=Song", "no separator"], None, 10);
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    assert!(matches!(parser.parse(), Err(Error::InvalidFrame { .. })));
+
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    parser.set_parse_mode(ParseMode::Lenient);
+    parser.parse().unwrap();
+    assert_eq!(parser.get("title"), Some(vec!["Song".to_string()]));
+    assert_eq!(parser.warnings.len(), 1);
+    assert_eq!(parser.warnings[0].identifier, "VORBISCOMMENT");
+}
+
+#[test]
+fn not_flac() {
+    let mut parser = FlacParser::from_bytes(b"OggS and more bytes").unwrap();
+    assert!(matches!(
+        parser.parse(),
+        Err(Error::NotThisFormat { expected: "FLAC" })
+    ));
+}
+
+/// Type and "last" flag of each metadata block of a file without ID3v2.
+fn block_chain(file: &[u8]) -> Vec<(u8, bool)> {
+    let mut chain = Vec::new();
+    let mut position = 4;
+    loop {
+        let header = &file[position..position + 4];
+        let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        chain.push((header[0] & 0x7F, header[0] & 0x80 != 0));
+        if header[0] & 0x80 != 0 {
+            return chain;
+        }
+        position += 4 + length;
+    }
+}
+
+fn audio(file: &[u8], parser: &FlacParser) -> Vec<u8> {
+    file[parser.audio_offset as usize..].to_vec()
+}
+
+#[test]
+fn save_within_the_padding() {
+    let original = flac(&["TITLE=Song", "ARTIST=Band"], Some(64), 500);
+    let path = temp_file("flac/padding.flac", &original);
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    let audio_offset = parser.audio_offset;
+
+    parser.set_comment("title", "A longer title").unwrap();
+    parser.remove_comment("artist");
+    parser.add_comment("GENRE", "Rock").unwrap();
+    parser.save().unwrap();
+
+    let file = fs::read(&path).unwrap();
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    assert_eq!(
+        parser.get("TITLE"),
+        Some(vec!["A longer title".to_string()])
+    );
+    assert_eq!(parser.get("ARTIST"), None);
+    assert_eq!(parser.get("genre"), Some(vec!["Rock".to_string()]));
+    assert_eq!(parser.audio_offset, audio_offset);
+    assert_eq!(file.len(), original.len());
+    // 10 more bytes for the title, 15 less for the artist, 14 more for the genre
+    assert_eq!(parser.padding_length, 64 - 9);
+    assert_eq!(audio(&file, &parser), audio(&original, &parser));
+    assert_eq!(block_chain(&file), [(0, false), (4, false), (1, true)]);
+}
+
+#[test]
+fn save_filling_the_room_exactly() {
+    let original = flac(&["TITLE=Song"], None, 500);
+    let path = temp_file("flac/exact.flac", &original);
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    parser.set_comment("TITLE", "Tune").unwrap();
+    parser.save().unwrap();
+
+    let file = fs::read(&path).unwrap();
+    assert_eq!(file.len(), original.len());
+    assert_eq!(parser.get("TITLE"), Some(vec!["Tune".to_string()]));
+    assert_eq!(parser.padding_length, 0);
+    assert_eq!(block_chain(&file), [(0, false), (4, true)]);
+}
+
+#[test]
+fn save_without_enough_room_rewrites_the_file() {
+    // 4 bytes of room, the comment grows by 2: too little for a PADDING block
+    let original = flac(&["TITLE=Song"], Some(0), 500);
+    let path = temp_file("flac/rewrite.flac", &original);
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    let old_audio = audio(&original, &parser);
+    let audio_offset = parser.audio_offset;
+    parser.set_comment("TITLE", "Song 2").unwrap();
+    parser.save().unwrap();
+
+    let file = fs::read(&path).unwrap();
+    assert_eq!(parser.padding_length, 4096);
+    assert_eq!(parser.audio_offset, audio_offset - 4 + 2 + 4 + 4096);
+    assert_eq!(audio(&file, &parser), old_audio);
+    assert_eq!(parser.get("TITLE"), Some(vec!["Song 2".to_string()]));
+    assert_eq!(block_chain(&file), [(0, false), (4, false), (1, true)]);
+    let mut temp = path.clone().into_os_string();
+    temp.push(".tmp");
+    assert!(!std::path::Path::new(&temp).exists());
+}
+
+#[test]
+fn invalid_comment_names_are_rejected() {
+    let file = flac(&["TITLE=Song"], Some(64), 100);
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    parser.parse().unwrap();
+    for key in ["", "A=B", "T\u{e9}TRE", "~KEY"] {
+        assert!(parser.set_comment(key, "value").is_err());
+        assert!(parser.add_comment(key, "value").is_err());
+    }
+    assert_eq!(parser.get("TITLE"), Some(vec!["Song".to_string()]));
+}
+
+#[test]
+fn save_requires_a_parsed_file() {
+    let file = flac(&["TITLE=Song"], Some(64), 10);
+    let path = temp_file("flac/unparsed.flac", &file);
+    let mut parser = FlacParser::new(&path).unwrap();
+    assert!(parser.save().is_err());
+    assert_eq!(fs::read(&path).unwrap(), file);
+}
+
+#[test]
+fn rewrite_keeps_the_id3v2_tag() {
+    let mut original = id3v2(3, &[("TIT2", text("Song"))], 0);
+    let marker_offset = original.len();
+    original.append(&mut flac(&["TITLE=Song"], None, 500));
+    let path = temp_file("flac/id3v2.flac", &original);
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    parser.add_comment("ALBUM", "Record").unwrap();
+    parser.save().unwrap();
+
+    let file = fs::read(&path).unwrap();
+    assert_eq!(file[..marker_offset + 4], original[..marker_offset + 4]);
+    assert_eq!(parser.marker_offset, marker_offset as u64);
+    assert_eq!(parser.get("album"), Some(vec!["Record".to_string()]));
+    assert_eq!(file[file.len() - 500..], original[original.len() - 500..]);
+}
+
+#[test]
+fn save_pictures() {
+    let original = flac(&["TITLE=Song"], Some(64), 500);
+    let path = temp_file("flac/pictures.flac", &original);
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    parser
+        .add_picture(FlacPicType::BandOrOrchestra, "band", png())
+        .unwrap();
+    parser.set_front_cover(png()).unwrap();
+    assert!(parser
+        .add_picture(FlacPicType::Other, "", b"text".to_vec())
+        .is_err());
+    parser.save().unwrap();
+
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    assert_eq!(parser.picture.len(), 2);
+    let artist = &parser.picture[0];
+    assert_eq!(artist.pic_type, FlacPicType::BandOrOrchestra);
+    assert_eq!(artist.mime, "image/png");
+    assert_eq!(artist.description, "band");
+    assert_eq!((artist.width, artist.height, artist.bit_depth), (3, 2, 32));
+    assert_eq!(artist.data, png());
+    assert_eq!(parser.picture[1].pic_type, FlacPicType::FrontCover);
+    assert_eq!(parser.get("TITLE"), Some(vec!["Song".to_string()]));
+
+    // lazily loaded pictures are copied from the file
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.set_picture_loading(PictureLoading::Lazy);
+    parser.parse().unwrap();
+    assert!(parser.picture[0].data.is_empty());
+    assert_eq!(parser.remove_pictures(&FlacPicType::FrontCover), 1);
+    parser.save().unwrap();
+    assert_eq!(parser.picture.len(), 1);
+    assert_eq!(parser.load_picture(0).unwrap(), png());
+}
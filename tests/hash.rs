@@ -0,0 +1,43 @@
+mod common;
+
+use common::{flac, id3v1, id3v2, mpeg_frames, text};
+use music_metadata::{FlacParser, ID3Parser};
+
+#[test]
+fn mp3_tags_are_not_hashed() {
+    let audio = mpeg_frames(3);
+    let mut first = id3v2(3, &[("TIT2", text("Song"))], 0);
+    first.extend_from_slice(&audio);
+    let mut second = id3v2(4, &[("TPE1", text("Band"))], 100);
+    second.extend_from_slice(&audio);
+    second.append(&mut id3v1("Song", 1, 0));
+
+    let first_hash = ID3Parser::from_bytes(&first).unwrap().audio_hash().unwrap();
+    let second_hash = ID3Parser::from_bytes(&second)
+        .unwrap()
+        .audio_hash()
+        .unwrap();
+    assert_eq!(first_hash.digest, second_hash.digest);
+    assert_eq!(first_hash.end - first_hash.start, audio.len() as u64);
+    assert_eq!(
+        second_hash.start,
+        second.len() as u64 - 128 - audio.len() as u64
+    );
+}
+
+#[test]
+fn flac_metadata_is_not_hashed() {
+    let first = flac(&["TITLE=Song"], None, 500);
+    let second = flac(&["TITLE=Another song", "ARTIST=Band"], Some(64), 500);
+    let first_hash = FlacParser::from_bytes(&first)
+        .unwrap()
+        .audio_hash()
+        .unwrap();
+    let second_hash = FlacParser::from_bytes(&second)
+        .unwrap()
+        .audio_hash()
+        .unwrap();
+    assert_eq!(first_hash.digest, second_hash.digest);
+    assert_eq!(second_hash.end, second.len() as u64);
+    assert_eq!(second_hash.end - second_hash.start, 500);
+}
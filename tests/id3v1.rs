@@ -0,0 +1,88 @@
+mod common;
+
+use std::fs;
+
+use common::{id3v1, mpeg_frames, temp_file};
+use music_metadata::{Charset, ID3Parser, ID3v1};
+
+#[test]
+fn parse_id3v1_1() {
+    let mut file = mpeg_frames(2);
+    file.append(&mut id3v1("Title", 7, 17));
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v1().unwrap();
+    assert_eq!(parser.id3v1.title_text(), "Title");
+    assert_eq!(parser.id3v1.track(), Some(7));
+    assert_eq!(parser.id3v1.genre_name(), Some("Rock"));
+}
+
+#[test]
+fn file_shorter_than_a_tag() {
+    let mut parser = ID3Parser::from_bytes(b"TAG too short").unwrap();
+    assert!(parser.parse_id3v1().is_err());
+}
+
+#[test]
+fn default_genre_is_unset() {
+    let id3v1 = ID3v1::default();
+    assert_eq!(id3v1.genre, 0xFF);
+    assert_eq!(id3v1.genre_name(), None);
+}
+
+#[test]
+fn removing_a_missing_track_keeps_the_comment() {
+    let mut id3v1 = ID3v1::default();
+    id3v1.set_comment(&"c".repeat(30), &Charset::ISO_8859_1);
+    id3v1.set_track(0);
+    assert_eq!(id3v1.comment_text(), "c".repeat(30));
+
+    id3v1.set_track(4);
+    assert_eq!(id3v1.track(), Some(4));
+    assert_eq!(id3v1.comment_text(), "c".repeat(28));
+    id3v1.set_track(0);
+    assert_eq!(id3v1.track(), None);
+}
+
+#[test]
+fn write_and_remove() {
+    let audio = mpeg_frames(2);
+    let path = temp_file("id3v1/write.mp3", &audio);
+    let mut parser = ID3Parser::new(&path).unwrap();
+    parser.parse_id3v1().unwrap();
+    assert_ne!(parser.id3v1.header, b"TAG");
+
+    let charset = Charset::ISO_8859_1;
+    parser.id3v1 = ID3v1::default();
+    parser.id3v1.set_title("Title", &charset);
+    parser.id3v1.set_year("1999");
+    parser.id3v1.set_track(5);
+    assert!(parser.id3v1.set_genre("rock"));
+    parser.write_id3v1().unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().len(), audio.len() as u64 + 128);
+
+    let mut parser = ID3Parser::new(&path).unwrap();
+    parser.parse_id3v1().unwrap();
+    assert_eq!(parser.id3v1.title_text(), "Title");
+    assert_eq!(parser.id3v1.year, b"1999");
+    assert_eq!(parser.id3v1.track(), Some(5));
+    assert_eq!(parser.id3v1.genre, 17);
+    assert!(!parser.id3v1.enhanced);
+
+    // replaces the tag, with a "TAG+" block for the long title
+    let title = "A title longer than thirty bytes";
+    parser.id3v1.set_title(title, &charset);
+    parser.write_id3v1().unwrap();
+    assert_eq!(
+        fs::metadata(&path).unwrap().len(),
+        audio.len() as u64 + 227 + 128
+    );
+    let mut parser = ID3Parser::new(&path).unwrap();
+    parser.parse_id3v1().unwrap();
+    assert!(parser.id3v1.enhanced);
+    assert_eq!(parser.id3v1.title_text(), title);
+    assert_eq!(parser.id3v1.track(), Some(5));
+
+    parser.remove_id3v1().unwrap();
+    assert_eq!(fs::read(&path).unwrap(), audio);
+    assert_eq!(parser.id3v1.genre_name(), None);
+}
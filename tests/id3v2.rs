@@ -0,0 +1,132 @@
+mod common;
+
+use std::io::Cursor;
+
+use common::{id3v1, id3v2, mpeg_frames, text};
+use music_metadata::{Error, Frame, ID3Parser, ParseMode};
+
+#[test]
+fn parse_text_frames() {
+    let mut file = id3v2(3, &[("TIT2", text("Song")), ("TPE1", text("Band"))], 16);
+    file.append(&mut mpeg_frames(2));
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v2().unwrap();
+    assert_eq!(parser.get("tit2"), Some(vec!["Song".to_string()]));
+    assert_eq!(parser.get("TPE1"), Some(vec!["Band".to_string()]));
+    assert_eq!(parser.padding_size, 16);
+}
+
+#[test]
+fn unknown_text_and_url_frames_are_skipped() {
+    let frames = [
+        ("TCMP", text("1")),
+        ("WFED", b"https://example.com/feed".to_vec()),
+        ("TIT2", text("Song")),
+    ];
+    let file = id3v2(4, &frames, 0);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v2().unwrap();
+    assert_eq!(parser.get("TIT2"), Some(vec!["Song".to_string()]));
+    assert_eq!(parser.get("TCMP"), None);
+    let skipped: Vec<&str> = parser
+        .warnings
+        .iter()
+        .map(|v| v.identifier.as_str())
+        .collect();
+    assert_eq!(skipped, ["TCMP", "WFED"]);
+    let identifiers: Vec<String> = parser
+        .frames_in_order()
+        .map(|(_, frame)| frame.identifier())
+        .collect();
+    assert_eq!(identifiers, ["TCMP", "WFED", "TIT2"]);
+}
+
+#[test]
+fn frame_beyond_the_tag_is_truncated() {
+    let mut file = id3v2(3, &[("TIT2", text("Song"))], 0);
+    // declare a frame longer than the tag
+    file[17] = 0x40;
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    assert!(matches!(
+        parser.parse_id3v2(),
+        Err(Error::Truncated { offset: 10, .. })
+    ));
+}
+
+#[test]
+fn id3v2_2_is_not_supported() {
+    let mut file = id3v2(3, &[("TIT2", text("Song"))], 0);
+    file[3] = 2;
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    let error = parser.parse_id3v2().unwrap_err();
+    assert!(matches!(
+        &error,
+        Error::UnsupportedVersion { offset: 0, version } if version == "ID3v2.2"
+    ));
+    assert_eq!(error.offset(), Some(0));
+}
+
+#[test]
+fn typed_frames() {
+    let file = id3v2(3, &[("TALB", text("Album"))], 0);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v2().unwrap();
+    let frames = parser.get_frames("talb").unwrap();
+    assert_eq!(
+        frames,
+        [Frame::Text {
+            identifier: "TALB".to_string(),
+            text: "Album".to_string()
+        }]
+    );
+}
+
+#[test]
+fn lenient_mode_records_unknown_and_broken_frames() {
+    // an encoding byte that does not exist
+    let broken = vec![0x09, b'x'];
+    let frames = [
+        ("TCMP", text("1")),
+        ("TALB", broken),
+        ("TIT2", text("Song")),
+    ];
+    let file = id3v2(3, &frames, 0);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    assert!(parser.parse_id3v2().is_err());
+
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.set_parse_mode(ParseMode::Lenient);
+    parser.parse_id3v2().unwrap();
+    assert_eq!(parser.get("TIT2"), Some(vec!["Song".to_string()]));
+    let warnings: Vec<(u64, &str)> = parser
+        .warnings
+        .iter()
+        .map(|v| (v.offset, v.identifier.as_str()))
+        .collect();
+    assert_eq!(warnings, [(10, "TCMP"), (22, "TALB")]);
+}
+
+#[test]
+fn change_source_forgets_the_previous_file() {
+    let mut first = id3v2(3, &[("TIT2", text("Song"))], 16);
+    first.append(&mut mpeg_frames(4));
+    first.append(&mut id3v1("Old", 3, 17));
+    let second = mpeg_frames(4);
+
+    let mut parser = ID3Parser::from_bytes(&first).unwrap();
+    parser.parse_id3v2().unwrap();
+    parser.parse_id3v1().unwrap();
+    parser.parse_audio_properties().unwrap();
+    parser.scan_audio_stream().unwrap();
+    assert_eq!(parser.id3v1.track(), Some(3));
+    assert_eq!(parser.stream_scan.frame_count, 4);
+
+    parser.change_source(Cursor::new(&second)).unwrap();
+    assert_eq!(parser.get("TIT2"), None);
+    assert_eq!(parser.pheader.size, 0);
+    assert_eq!(parser.padding_size, 0);
+    assert!(parser.id3v1.title.is_empty());
+    assert_eq!(parser.stream_scan.frame_count, 0);
+    assert_eq!(parser.audio_properties.sample_rate, 0);
+    assert!(parser.warnings.is_empty());
+}
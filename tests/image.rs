@@ -0,0 +1,180 @@
+mod common;
+
+use std::fs;
+
+use common::{apic, flac_block, flac_picture, id3v2, png, stream_info, temp_file};
+use music_metadata::{FlacParser, ID3Parser, ImageFormat, ImageInfo};
+
+/// format, width, height, depth and colours found in `data`
+fn detect(data: &[u8]) -> (ImageFormat, u32, u32, u32, u32) {
+    let info = ImageInfo::detect(data, "").unwrap();
+    (
+        info.format,
+        info.width,
+        info.height,
+        info.depth,
+        info.colors,
+    )
+}
+
+#[test]
+fn jpeg() {
+    // APP0, then SOF0 of 64x32 pixels with 3 components of 8 bits
+    let mut data = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00];
+    data.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00, 0x20, 0x00, 0x40, 0x03]);
+    data.resize(40, 0);
+    assert_eq!(detect(&data), (ImageFormat::Jpeg, 64, 32, 24, 0));
+
+    // a fill byte and DHT before SOF2, progressive with 1 component
+    let mut data = vec![0xFF, 0xD8, 0xFF, 0xFF, 0xC4, 0x00, 0x04, 0x00, 0x00];
+    data.extend_from_slice(&[0xFF, 0xC2, 0x00, 0x0B, 0x08, 0x01, 0x00, 0x02, 0x00, 0x01]);
+    data.resize(40, 0);
+    assert_eq!(detect(&data), (ImageFormat::Jpeg, 512, 256, 8, 0));
+}
+
+#[test]
+fn png_truecolour_and_palette() {
+    assert_eq!(detect(&png()), (ImageFormat::Png, 3, 2, 32, 0));
+
+    // 4 bits per pixel, palette of 3 colours
+    let mut data = png();
+    data[24..26].copy_from_slice(&[4, 3]);
+    data.extend_from_slice(&9u32.to_be_bytes());
+    data.extend_from_slice(b"PLTE");
+    data.extend_from_slice(&[0; 9 + 4]);
+    data.extend_from_slice(&[0, 0, 0, 0]);
+    data.extend_from_slice(b"IDAT");
+    assert_eq!(detect(&data), (ImageFormat::Png, 3, 2, 4, 3));
+}
+
+#[test]
+fn gif() {
+    // global colour table of 8 colours
+    let mut data = b"GIF89a".to_vec();
+    data.extend_from_slice(&[10, 0, 20, 0, 0xF2, 0, 0]);
+    assert_eq!(detect(&data), (ImageFormat::Gif, 10, 20, 3, 8));
+}
+
+fn webp(chunk: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut file = b"RIFF\0\0\0\0WEBP".to_vec();
+    file.extend_from_slice(chunk);
+    file.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    file.extend_from_slice(data);
+    file
+}
+
+#[test]
+fn webp_lossy_lossless_and_extended() {
+    let mut vp8 = vec![0, 0, 0, 0x9D, 0x01, 0x2A];
+    vp8.extend_from_slice(&100u16.to_le_bytes());
+    vp8.extend_from_slice(&50u16.to_le_bytes());
+    assert_eq!(
+        detect(&webp(b"VP8 ", &vp8)),
+        (ImageFormat::WebP, 100, 50, 24, 0)
+    );
+
+    // 4x3 pixels with alpha
+    let bits: u32 = 3 | (2 << 14) | (1 << 28);
+    let mut vp8l = vec![0x2F];
+    vp8l.extend_from_slice(&bits.to_le_bytes());
+    assert_eq!(
+        detect(&webp(b"VP8L", &vp8l)),
+        (ImageFormat::WebP, 4, 3, 32, 0)
+    );
+
+    // 640x480 pixels without alpha
+    let mut vp8x = vec![0, 0, 0, 0];
+    vp8x.extend_from_slice(&639u32.to_le_bytes()[..3]);
+    vp8x.extend_from_slice(&479u32.to_le_bytes()[..3]);
+    assert_eq!(
+        detect(&webp(b"VP8X", &vp8x)),
+        (ImageFormat::WebP, 640, 480, 24, 0)
+    );
+}
+
+#[test]
+fn bmp_top_down() {
+    // BITMAPINFOHEADER of 16x8 pixels stored top-down, 8 bits per pixel
+    let mut data = b"BM".to_vec();
+    data.resize(14, 0);
+    data.extend_from_slice(&40u32.to_le_bytes());
+    data.extend_from_slice(&16i32.to_le_bytes());
+    data.extend_from_slice(&(-8i32).to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&8u16.to_le_bytes());
+    data.resize(54, 0);
+    assert_eq!(detect(&data), (ImageFormat::Bmp, 16, 8, 8, 256));
+}
+
+#[test]
+fn truncated_images() {
+    for data in [&b""[..], b"\xFF", b"\x89PNG", b"GIF8", b"RIFF\0\0\0\0WE"] {
+        assert_eq!(ImageInfo::detect(data, ""), None, "{data:?}");
+    }
+    // the signature is there but the header is cut
+    let nothing = |format| (format, 0, 0, 0, 0);
+    assert_eq!(detect(&png()[..20]), nothing(ImageFormat::Png));
+    let sof = b"\xFF\xD8\xFF\xC0\x00\x11\x08\x00\x20\x00";
+    assert_eq!(detect(sof), nothing(ImageFormat::Jpeg));
+    assert_eq!(detect(b"GIF89a\x0A\x00\x14"), nothing(ImageFormat::Gif));
+    let vp8l = webp(b"VP8L", &[0x2F, 3, 0]);
+    assert_eq!(detect(&vp8l), nothing(ImageFormat::WebP));
+    let mut bmp = b"BM".to_vec();
+    bmp.resize(14, 0);
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&16i32.to_le_bytes());
+    assert_eq!(detect(&bmp), nothing(ImageFormat::Bmp));
+}
+
+#[test]
+fn declared_mime_type() {
+    assert!(ImageInfo::detect(&png(), "image/png").unwrap().mime_matches);
+    assert!(
+        ImageInfo::detect(&png(), " Image/PNG ")
+            .unwrap()
+            .mime_matches
+    );
+    assert!(ImageInfo::detect(&png(), "PNG").unwrap().mime_matches);
+    assert!(
+        !ImageInfo::detect(&png(), "image/jpeg")
+            .unwrap()
+            .mime_matches
+    );
+    assert!(!ImageInfo::detect(&png(), "").unwrap().mime_matches);
+    assert!(ImageFormat::Jpeg.matches_mime("image/jpg"));
+    assert!(ImageFormat::Jpeg.matches_mime("image/pjpeg"));
+    assert!(ImageFormat::Bmp.matches_mime("image/x-ms-bmp"));
+    assert!(!ImageFormat::Gif.matches_mime("image/webp"));
+}
+
+#[test]
+fn image_info_of_a_mislabelled_picture() {
+    let file = id3v2(3, &[("APIC", apic("image/jpeg", 3, "", &png()))], 0);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v2().unwrap();
+    let info = parser.image_info(0).unwrap().unwrap();
+    assert_eq!(info.format, ImageFormat::Png);
+    assert!(!info.mime_matches);
+}
+
+#[test]
+fn write_image_uses_the_detected_extension() {
+    let file = id3v2(3, &[("APIC", apic("image/jpeg", 3, "", &png()))], 0);
+    let path = temp_file("image/song.mp3", &file);
+    let mut parser = ID3Parser::new(&path).unwrap();
+    parser.parse_id3v2().unwrap();
+    parser.write_image().unwrap();
+    let written = path.with_file_name("song_mp3_FrontCover.png");
+    assert_eq!(fs::read(written).unwrap(), png());
+
+    let mut file = b"fLaC".to_vec();
+    file.append(&mut flac_block(0, false, &stream_info()));
+    let picture = flac_picture(3, "image/jpeg", "", &png());
+    file.append(&mut flac_block(6, true, &picture));
+    let path = temp_file("image/song.flac", &file);
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    parser.write_image().unwrap();
+    let written = path.with_file_name("song_flac_FrontCover.png");
+    assert_eq!(fs::read(written).unwrap(), png());
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::{flac, id3v1, id3v2, mpeg_frames, text};
+use music_metadata::{BlockType, FlacParser, ID3Parser, Layout, RegionKind};
+
+fn regions(layout: &Layout) -> Vec<(RegionKind, u64, u64)> {
+    layout
+        .regions
+        .iter()
+        .map(|v| (v.kind, v.offset, v.length))
+        .collect()
+}
+
+#[test]
+fn mp3_without_tags() {
+    let file = mpeg_frames(3);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    let layout = parser.layout().unwrap();
+    assert_eq!(regions(&layout), [(RegionKind::Audio, 0, 3 * 417)]);
+}
+
+#[test]
+fn mp3_with_tags() {
+    let mut file = id3v2(3, &[("TIT2", text("Song"))], 16);
+    file.append(&mut mpeg_frames(2));
+    file.append(&mut id3v1("Song", 1, 0));
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+
+    // the padding is only known after parsing
+    let layout = parser.layout().unwrap();
+    assert_eq!(layout.regions[0].kind, RegionKind::ID3v2Tag);
+    assert_eq!(layout.regions[0].length, 10 + 15 + 16);
+
+    parser.parse_id3v2().unwrap();
+    let layout = parser.layout().unwrap();
+    assert_eq!(
+        regions(&layout),
+        [
+            (RegionKind::ID3v2Tag, 0, 25),
+            (RegionKind::Padding, 25, 16),
+            (RegionKind::Audio, 41, 2 * 417),
+            (RegionKind::ID3v1, 41 + 2 * 417, 128),
+        ]
+    );
+}
+
+#[test]
+fn id3v2_footer() {
+    let mut file = id3v2(4, &[("TIT2", text("Song"))], 0);
+    // footer flag, then the footer which is the header with "3DI"
+    file[5] |= 0x10;
+    let mut footer = file[..10].to_vec();
+    footer[..3].copy_from_slice(b"3DI");
+    file.append(&mut footer);
+    file.append(&mut mpeg_frames(2));
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v2().unwrap();
+    let layout = parser.layout().unwrap();
+    assert_eq!(
+        regions(&layout),
+        [
+            (RegionKind::ID3v2Tag, 0, 25),
+            (RegionKind::ID3v2Footer, 25, 10),
+            (RegionKind::Audio, 35, 2 * 417),
+        ]
+    );
+}
+
+#[test]
+fn junk_around_the_frames() {
+    let mut file = vec![0; 100];
+    file.append(&mut mpeg_frames(2));
+    file.extend_from_slice(&[0x55; 50]);
+    file.append(&mut mpeg_frames(2));
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_audio_properties().unwrap();
+    let layout = parser.layout().unwrap();
+    assert_eq!(
+        regions(&layout)[..2],
+        [(RegionKind::Junk, 0, 100), (RegionKind::Audio, 100, 1718)]
+    );
+
+    parser.scan_audio_stream().unwrap();
+    let layout = parser.layout().unwrap();
+    assert_eq!(
+        regions(&layout),
+        [
+            (RegionKind::Junk, 0, 100),
+            (RegionKind::Audio, 100, 2 * 417),
+            (RegionKind::Junk, 934, 50),
+            (RegionKind::Audio, 984, 2 * 417),
+        ]
+    );
+    assert_eq!(layout.length_of(RegionKind::Junk), 150);
+}
+
+#[test]
+fn flac_without_id3() {
+    let file = flac(&["TITLE=Song"], Some(64), 100);
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    let layout = parser.layout().unwrap();
+    // block header, vendor, count and "TITLE=Song"
+    let comment = 4 + (4 + 4) + 4 + (4 + 10);
+    assert_eq!(
+        regions(&layout),
+        [
+            (RegionKind::FlacMarker, 0, 4),
+            (RegionKind::MetadataBlock(BlockType::STREAMINFO), 4, 38),
+            (
+                RegionKind::MetadataBlock(BlockType::VORBISCOMMENT),
+                42,
+                comment
+            ),
+            (RegionKind::Padding, 42 + comment, 68),
+            (RegionKind::Audio, 42 + comment + 68, 100),
+        ]
+    );
+}
+
+#[test]
+fn flac_between_id3v2_and_id3v1() {
+    let mut file = id3v2(3, &[("TIT2", text("Song"))], 0);
+    file.append(&mut flac(&[], None, 100));
+    file.append(&mut id3v1("Song", 1, 0));
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    let layout = parser.layout().unwrap();
+    let kinds: Vec<RegionKind> = layout.regions.iter().map(|v| v.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            RegionKind::ID3v2Tag,
+            RegionKind::FlacMarker,
+            RegionKind::MetadataBlock(BlockType::STREAMINFO),
+            RegionKind::MetadataBlock(BlockType::VORBISCOMMENT),
+            RegionKind::Audio,
+            RegionKind::ID3v1,
+        ]
+    );
+    assert_eq!(layout.regions[0].length, 25);
+    assert_eq!(layout.length_of(RegionKind::Audio), 100);
+    assert_eq!(layout.length_of(RegionKind::Unknown), 0);
+}
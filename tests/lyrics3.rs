@@ -0,0 +1,81 @@
+mod common;
+
+use common::{id3v1, mpeg_frames};
+use music_metadata::{ID3Parser, Lyrics3Version};
+
+/// A Lyrics3v2 block holding `fields`, each an identifier and its data.
+fn lyrics3v2(fields: &[(&str, &str)]) -> Vec<u8> {
+    let mut block = b"LYRICSBEGIN".to_vec();
+    for (id, data) in fields {
+        block.extend_from_slice(id.as_bytes());
+        block.extend_from_slice(format!("{:05}", data.len()).as_bytes());
+        block.extend_from_slice(data.as_bytes());
+    }
+    block.extend_from_slice(format!("{:06}", block.len()).as_bytes());
+    block.extend_from_slice(b"LYRICS200");
+    block
+}
+
+#[test]
+fn parse_lyrics3v2() {
+    let lyrics = "[00:01]Hello\r\n[01:02][00:03]World\r\nno timestamp";
+    let mut file = mpeg_frames(2);
+    let position = file.len() as u64;
+    let mut block = lyrics3v2(&[("IND", "11"), ("LYR", lyrics), ("ETT", "Title")]);
+    let size = block.len() as u32;
+    file.append(&mut block);
+    file.append(&mut id3v1("Title", 1, 0));
+
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_lyrics3().unwrap();
+    let lyrics3 = &parser.lyrics3;
+    assert!(matches!(lyrics3.version, Lyrics3Version::V2));
+    assert_eq!((lyrics3.position, lyrics3.size), (position, size));
+    assert_eq!(lyrics3.indications, "11");
+    assert_eq!(lyrics3.title, "Title");
+    assert_eq!(lyrics3.lyrics, lyrics);
+    let synced: Vec<(u32, &str)> = lyrics3
+        .synced_lyrics
+        .iter()
+        .map(|v| (v.timestamp, v.text.as_str()))
+        .collect();
+    assert_eq!(synced, [(1000, "Hello"), (3000, "World"), (62000, "World")]);
+}
+
+#[test]
+fn parse_lyrics3v1_without_id3v1() {
+    let mut file = mpeg_frames(1);
+    let position = file.len() as u64;
+    file.extend_from_slice(b"LYRICSBEGIN[00:05]Hi\r\nLYRICSEND");
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_lyrics3().unwrap();
+    let lyrics3 = &parser.lyrics3;
+    assert!(matches!(lyrics3.version, Lyrics3Version::V1));
+    assert_eq!((lyrics3.position, lyrics3.size), (position, 31));
+    assert_eq!(lyrics3.synced_lyrics[0].timestamp, 5000);
+    assert_eq!(lyrics3.synced_lyrics[0].text, "Hi");
+}
+
+#[test]
+fn field_beyond_the_block() {
+    let mut block = lyrics3v2(&[("LYR", "[00:01]Hello")]);
+    // declare 99 bytes of lyrics
+    block[14..19].copy_from_slice(b"00099");
+    let mut file = mpeg_frames(1);
+    file.append(&mut block);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    assert!(parser.parse_lyrics3().is_err());
+}
+
+#[test]
+fn invalid_size_is_not_lyrics3() {
+    let mut block = lyrics3v2(&[("LYR", "text")]);
+    let length = block.len();
+    block[length - 15..length - 9].copy_from_slice(b"99999x");
+    let mut file = mpeg_frames(1);
+    file.append(&mut block);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_lyrics3().unwrap();
+    assert!(matches!(parser.lyrics3.version, Lyrics3Version::Default));
+    assert!(parser.lyrics3.lyrics.is_empty());
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::mpeg_frames;
+use music_metadata::{ID3Parser, VbrMethod};
+
+/// The Xing header of MPEG-1 stereo follows 32 bytes of side information.
+const XING_OFFSET: usize = 4 + 32;
+
+/// `count` frames whose first one holds `header` instead of audio.
+fn frames_with(header: &[u8], count: usize) -> Vec<u8> {
+    let mut file = mpeg_frames(count);
+    file[XING_OFFSET..XING_OFFSET + header.len()].copy_from_slice(header);
+    file
+}
+
+/// LAME 3.100, VBR mtrh, 19.5 kHz lowpass, track gain -6.5 dB, 576 samples of delay and 1000 of padding.
+fn lame() -> Vec<u8> {
+    let mut lame = b"LAME3.100".to_vec();
+    lame.extend_from_slice(&[0x14, 195]);
+    lame.extend_from_slice(&(1u32 << 23).to_be_bytes());
+    // name code 1, originator 1, negative, 65
+    lame.extend_from_slice(&((1u16 << 13) | (1 << 10) | 0x0200 | 65).to_be_bytes());
+    lame.extend_from_slice(&[0, 0, 0, 128]);
+    lame.extend_from_slice(&[0x24, 0x03, 0xE8]);
+    lame.resize(36, 0);
+    lame
+}
+
+fn parse(file: &[u8]) -> ID3Parser<std::io::Cursor<&[u8]>> {
+    let mut parser = ID3Parser::from_bytes(file).unwrap();
+    parser.parse_audio_properties().unwrap();
+    parser
+}
+
+#[test]
+fn cbr_without_vbr_header() {
+    let file = mpeg_frames(10);
+    let properties = parse(&file).audio_properties;
+    assert_eq!(properties.frame_count, 10);
+    assert_eq!(
+        (
+            properties.bitrate,
+            properties.sample_rate,
+            properties.channels
+        ),
+        (128, 44100, 2)
+    );
+    assert_eq!(properties.duration.as_millis(), 10 * 417 * 8 / 128);
+    assert!(!properties.is_vbr);
+    assert!(properties.xing.is_none() && properties.vbri.is_none());
+}
+
+#[test]
+fn xing_and_lame() {
+    let mut header = b"Xing".to_vec();
+    header.extend_from_slice(&0x0Fu32.to_be_bytes());
+    header.extend_from_slice(&100u32.to_be_bytes());
+    header.extend_from_slice(&41700u32.to_be_bytes());
+    header.extend((0..100).map(|v| (v * 2) as u8));
+    header.extend_from_slice(&50u32.to_be_bytes());
+    header.append(&mut lame());
+    let file = frames_with(&header, 4);
+
+    let properties = parse(&file).audio_properties;
+    assert!(properties.is_vbr);
+    assert_eq!(properties.frame_count, 100);
+    assert_eq!(properties.total_samples, Some(100 * 1152 - 576 - 1000));
+    let xing = properties.xing.unwrap();
+    assert_eq!((xing.bytes, xing.quality), (Some(41700), Some(50)));
+    assert_eq!(xing.toc.unwrap()[99], 198);
+    let lame = xing.lame.unwrap();
+    assert_eq!(lame.encoder, "LAME3.100");
+    assert_eq!(lame.revision, 1);
+    assert!(matches!(lame.vbr_method, VbrMethod::Vbr2));
+    assert_eq!(lame.lowpass, 19500);
+    assert_eq!(lame.track_peak, 1.0);
+    assert_eq!((lame.track_gain, lame.album_gain), (Some(-6.5), None));
+    assert_eq!((lame.encoder_delay, lame.encoder_padding), (576, 1000));
+}
+
+#[test]
+fn info_is_cbr() {
+    let mut header = b"Info".to_vec();
+    header.extend_from_slice(&0x01u32.to_be_bytes());
+    header.extend_from_slice(&9u32.to_be_bytes());
+    let file = frames_with(&header, 10);
+    let properties = parse(&file).audio_properties;
+    assert!(!properties.is_vbr);
+    assert_eq!(properties.frame_count, 9);
+    assert!(properties.xing.unwrap().lame.is_none());
+}
+
+#[test]
+fn vbri() {
+    let mut header = b"VBRI".to_vec();
+    for value in [1u16, 576, 75] {
+        header.extend_from_slice(&value.to_be_bytes());
+    }
+    header.extend_from_slice(&20850u32.to_be_bytes());
+    header.extend_from_slice(&50u32.to_be_bytes());
+    // 2 entries of 2 bytes, scaled by 1, 25 frames each
+    for value in [2u16, 1, 2, 25, 1000, 2000] {
+        header.extend_from_slice(&value.to_be_bytes());
+    }
+    let file = frames_with(&header, 4);
+    let properties = parse(&file).audio_properties;
+    assert!(properties.is_vbr);
+    assert_eq!(properties.frame_count, 50);
+    assert_eq!(properties.total_samples, Some(50 * 1152 - 576));
+    let vbri = properties.vbri.unwrap();
+    assert_eq!((vbri.version, vbri.quality, vbri.bytes), (1, 75, 20850));
+    assert_eq!(vbri.toc, [1000, 2000]);
+}
+
+#[test]
+fn truncated_xing_header() {
+    let mut header = b"Xing".to_vec();
+    // frames, bytes and toc, cut by the end of the file
+    header.extend_from_slice(&0x07u32.to_be_bytes());
+    header.extend_from_slice(&[0; 20]);
+    let mut file = frames_with(&header, 1);
+    file.truncate(XING_OFFSET + header.len());
+    let properties = parse(&file).audio_properties;
+    assert!(properties.xing.is_none());
+    assert!(!properties.is_vbr);
+}
+
+#[test]
+fn unknown_encoder_is_not_lame() {
+    let mut header = b"Xing".to_vec();
+    header.extend_from_slice(&0x01u32.to_be_bytes());
+    header.extend_from_slice(&3u32.to_be_bytes());
+    let mut lame = lame();
+    lame[..4].copy_from_slice(b"ABCD");
+    header.append(&mut lame);
+    let file = frames_with(&header, 4);
+    let properties = parse(&file).audio_properties;
+    assert!(properties.xing.unwrap().lame.is_none());
+    assert_eq!(properties.total_samples, None);
+}
+
+#[test]
+fn no_frame() {
+    let file = vec![0x55; 2000];
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    assert!(parser.parse_audio_properties().is_err());
+}
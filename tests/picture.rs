@@ -0,0 +1,159 @@
+mod common;
+
+use std::fs;
+use std::io::{Read, Seek};
+
+use common::{apic, flac_block, flac_picture, id3v2, png, stream_info, temp_file, text};
+use music_metadata::{
+    FlacParser, FlacPicType, Frame, ID3Parser, PicType, PictureContent, PictureLoading,
+    PictureLocation,
+};
+
+const URL: &str = "https://example.com/cover.jpg";
+
+/// A FLAC file holding the PICTURE block `picture`, followed by 64 bytes of padding.
+fn flac_with_picture(picture: &[u8]) -> Vec<u8> {
+    let mut file = b"fLaC".to_vec();
+    file.append(&mut flac_block(0, false, &stream_info()));
+    file.append(&mut flac_block(6, false, picture));
+    file.append(&mut flac_block(1, true, &[0; 64]));
+    file
+}
+
+fn picture_types(parser: &ID3Parser) -> Vec<PicType> {
+    parser
+        .get_frames("APIC")
+        .unwrap()
+        .into_iter()
+        .map(|frame| match frame {
+            Frame::Picture { picture_type, .. } => picture_type,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+#[test]
+fn undefined_apic_picture_types() {
+    let frames = [
+        ("APIC", apic("image/png", 0x15, "", &png())),
+        ("APIC", apic("image/png", 0xFF, "", &png())),
+    ];
+    let path = temp_file("picture/unknown.mp3", &id3v2(3, &frames, 0));
+    let mut parser = ID3Parser::new(&path).unwrap();
+    parser.parse_id3v2().unwrap();
+    assert_eq!(
+        picture_types(&parser),
+        [PicType::Unknown(0x15), PicType::Unknown(0xFF)]
+    );
+    assert_eq!(parser.load_picture(1).unwrap(), png());
+
+    parser.write_image().unwrap();
+    let written = path.with_file_name("unknown_mp3_Unknown21.png");
+    assert_eq!(fs::read(written).unwrap(), png());
+    let written = path.with_file_name("unknown_mp3_Unknown255_1.png");
+    assert_eq!(fs::read(written).unwrap(), png());
+}
+
+#[test]
+fn undefined_flac_picture_type_is_saved_back() {
+    let picture = flac_picture(21, "image/png", "", &png());
+    let path = temp_file("picture/unknown.flac", &flac_with_picture(&picture));
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    assert_eq!(parser.picture[0].pic_type, FlacPicType::Unknown(21));
+
+    parser.add_comment("TITLE", "Song").unwrap();
+    parser.save().unwrap();
+    let file = fs::read(&path).unwrap();
+    let mut parser = FlacParser::new(&path).unwrap();
+    parser.parse().unwrap();
+    assert_eq!(parser.picture[0].pic_type, FlacPicType::Unknown(21));
+    assert!(file.windows(picture.len()).any(|v| v == picture));
+}
+
+#[test]
+fn linked_apic() {
+    let frames = [("APIC", apic("-->", 3, "cover", URL.as_bytes()))];
+    let file = id3v2(3, &frames, 0);
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v2().unwrap();
+    assert_eq!(
+        parser.picture_content(0).unwrap(),
+        PictureContent::Url(URL.to_string())
+    );
+    assert!(parser.load_picture(0).is_err());
+    assert_eq!(parser.image_info(0).unwrap(), None);
+}
+
+#[test]
+fn linked_flac_picture() {
+    let picture = flac_picture(3, "-->", "cover", URL.as_bytes());
+    let file = flac_with_picture(&picture);
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    parser.parse().unwrap();
+    assert_eq!(
+        parser.picture_content(0).unwrap(),
+        PictureContent::Url(URL.to_string())
+    );
+    assert!(parser.load_picture(0).is_err());
+    assert_eq!(parser.image_info(0).unwrap(), None);
+}
+
+/// A large image, not a valid one but recognizable by its signature.
+fn large_image() -> Vec<u8> {
+    let mut data = png();
+    data.extend((0..3000).map(|v| (v % 251) as u8));
+    data
+}
+
+fn picture_location<R: Read + Seek>(parser: &ID3Parser<R>) -> Option<PictureLocation> {
+    match &parser.get_frames("APIC").unwrap()[0] {
+        Frame::Picture { location, .. } => *location,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn lazy_apic_with_a_long_description() {
+    // the description alone is longer than the bytes read first
+    let description = "d".repeat(1500);
+    let frames = [
+        ("TIT2", text("Song")),
+        ("APIC", apic("image/png", 3, &description, &large_image())),
+    ];
+    let file = id3v2(3, &frames, 16);
+    let mut eager = ID3Parser::from_bytes(&file).unwrap();
+    eager.parse_id3v2().unwrap();
+    assert_eq!(picture_location(&eager), None);
+
+    let mut lazy = ID3Parser::from_bytes(&file).unwrap();
+    lazy.set_picture_loading(PictureLoading::Lazy);
+    lazy.parse_id3v2().unwrap();
+    // protocol header, TIT2, APIC header, encoding, MIME type, picture type and description
+    let offset = 10 + 15 + 10 + 1 + 10 + 1 + 1501;
+    let location = PictureLocation::new(offset, large_image().len() as u64);
+    assert_eq!(picture_location(&lazy), Some(location));
+    assert_eq!(&file[offset as usize..][..8], &png()[..8]);
+    assert_eq!(
+        lazy.load_picture(0).unwrap(),
+        eager.load_picture(0).unwrap()
+    );
+    assert_eq!(lazy.load_picture(0).unwrap(), large_image());
+    match &lazy.get_frames("APIC").unwrap()[0] {
+        Frame::Picture { description: d, .. } => assert_eq!(d.as_deref(), Some(&*description)),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn lazy_apic_with_a_short_description() {
+    let frames = [("APIC", apic("image/png", 3, "cover", &large_image()))];
+    let file = id3v2(4, &frames, 0);
+    let mut lazy = ID3Parser::from_bytes(&file).unwrap();
+    lazy.set_picture_loading(PictureLoading::Lazy);
+    lazy.parse_id3v2().unwrap();
+    let offset = 10 + 10 + 1 + 10 + 1 + 6;
+    let location = PictureLocation::new(offset, large_image().len() as u64);
+    assert_eq!(picture_location(&lazy), Some(location));
+    assert_eq!(lazy.load_picture(0).unwrap(), large_image());
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use std::fs;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use common::{id3v2, mpeg_frames, ogg_page, temp_file, text};
+use music_metadata::{open, probe, AudioFile, Codec, Error, ParseMode, Scanner};
+
+#[test]
+fn lenient_scan_reports_unknown_frames() {
+    let mut file = id3v2(4, &[("TCMP", text("1")), ("TIT2", text("Song"))], 0);
+    file.append(&mut mpeg_frames(4));
+    let path = temp_file("scan/song.mp3", &file);
+    fs::write(path.with_file_name("notes.txt"), b"not audio").unwrap();
+
+    let mut scanner = Scanner::new(path.parent().unwrap());
+    scanner.set_threads(2);
+    scanner.set_parse_mode(ParseMode::Lenient);
+    let entries: Vec<_> = scanner.scan().collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path, path);
+    let Ok(AudioFile::Mp3(parser)) = &entries[0].result else {
+        panic!("the mp3 file is not parsed");
+    };
+    assert_eq!(parser.get("TIT2"), Some(vec!["Song".to_string()]));
+    assert_eq!(parser.warnings.len(), 1);
+    assert_eq!(parser.warnings[0].identifier, "TCMP");
+}
+
+#[test]
+fn other_ogg_codecs_are_skipped() {
+    let mut opus = b"OpusHead".to_vec();
+    opus.extend_from_slice(&[1, 2, 0x38, 0x01, 0x80, 0xBB, 0, 0, 0, 0, 0]);
+    let path = temp_file("scan-ogg/voice.opus", &ogg_page(&opus));
+    temp_file("scan-ogg/speech.spx", &ogg_page(b"Speex   1.2"));
+    assert_eq!(probe(&path).unwrap().unwrap().codec, Codec::Opus);
+    assert!(matches!(
+        open(&path),
+        Err(Error::NotThisFormat {
+            expected: "Ogg Vorbis"
+        })
+    ));
+
+    let mut scanner = Scanner::new(path.parent().unwrap());
+    let checked = Arc::new(AtomicUsize::new(0));
+    let counter = checked.clone();
+    scanner.set_progress(move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    assert_eq!(scanner.scan().count(), 0);
+    assert_eq!(checked.load(Ordering::Relaxed), 2);
+}
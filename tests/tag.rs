@@ -0,0 +1,152 @@
+mod common;
+
+use common::{flac, id3v1, id3v2, mpeg_frames, ogg_vorbis, text};
+use music_metadata::{FlacParser, ID3Parser, OggParser, StandardKey, Tag};
+
+/// Payload of a TXXX frame in ISO-8859-1.
+fn txxx(description: &str, value: &str) -> Vec<u8> {
+    let mut payload = text(description);
+    payload.push(0);
+    payload.extend_from_slice(value.as_bytes());
+    payload
+}
+
+/// Payload of a COMM frame in ISO-8859-1.
+fn comm(description: &str, value: &str) -> Vec<u8> {
+    let mut payload = b"\0eng".to_vec();
+    payload.extend_from_slice(description.as_bytes());
+    payload.push(0);
+    payload.extend_from_slice(value.as_bytes());
+    payload
+}
+
+const ALBUM_ID: &str = "1f2e3d4c-0000-4000-8000-000000000001";
+
+fn id3_tag(frames: &[(&str, Vec<u8>)], id3v1_tag: Option<Vec<u8>>) -> Tag {
+    let mut file = id3v2(3, frames, 0);
+    file.append(&mut mpeg_frames(2));
+    if let Some(mut tag) = id3v1_tag {
+        file.append(&mut tag);
+    }
+    let mut parser = ID3Parser::from_bytes(&file).unwrap();
+    parser.parse_id3v2().unwrap();
+    parser.parse_id3v1().unwrap();
+    parser.tag()
+}
+
+#[test]
+fn id3v2_frames() {
+    let frames = [
+        ("TIT2", text("Song")),
+        ("TPE1", text("Band")),
+        ("TRCK", text("3/12")),
+        ("TCON", text("(17)")),
+        ("TXXX", txxx("MusicBrainz Album Id", ALBUM_ID)),
+        ("TXXX", txxx("replaygain_track_gain", "-6.50 dB")),
+        ("COMM", comm("", "Nice")),
+    ];
+    let tag = id3_tag(&frames, None);
+    assert_eq!(tag.get_first(StandardKey::Title), Some("Song"));
+    assert_eq!(tag.get_first(StandardKey::Artist), Some("Band"));
+    assert_eq!(tag.get_first(StandardKey::TrackNumber), Some("3"));
+    assert_eq!(tag.get_first(StandardKey::TrackTotal), Some("12"));
+    assert_eq!(tag.get_first(StandardKey::Genre), Some("Rock"));
+    assert_eq!(
+        tag.get_first(StandardKey::MusicBrainzAlbumId),
+        Some(ALBUM_ID)
+    );
+    assert_eq!(
+        tag.get_first(StandardKey::ReplayGainTrackGain),
+        Some("-6.50 dB")
+    );
+    assert_eq!(tag.get_first(StandardKey::Comment), Some("Nice"));
+}
+
+#[test]
+fn id3v2_fields_without_standard_key() {
+    let frames = [
+        ("TIT2", text("Song")),
+        ("TXXX", txxx("MOOD", "Calm")),
+        ("TLAN", text("eng")),
+        ("COMM", comm("iTunNORM", "0000")),
+    ];
+    let tag = id3_tag(&frames, None);
+    assert_eq!(tag.get_native("txxx:mood"), Some(&["Calm".to_string()][..]));
+    assert_eq!(tag.get_native("TLAN"), Some(&["eng".to_string()][..]));
+    assert_eq!(
+        tag.get_native("COMM:iTunNORM"),
+        Some(&["0000".to_string()][..])
+    );
+    assert_eq!(tag.get(StandardKey::Comment), None);
+    assert_eq!(tag.get_native("TIT2"), None);
+}
+
+#[test]
+fn genre_that_is_not_an_index() {
+    let tag = id3_tag(&[("TCON", text("Shoegaze"))], None);
+    assert_eq!(tag.get_first(StandardKey::Genre), Some("Shoegaze"));
+    // beyond the ID3v1 list
+    let tag = id3_tag(&[("TCON", text("(250)"))], None);
+    assert_eq!(tag.get_first(StandardKey::Genre), Some("(250)"));
+}
+
+#[test]
+fn id3v1_fills_the_empty_fields_only() {
+    let frames = [("TIT2", text("Song")), ("TRCK", text("5"))];
+    let tag = id3_tag(&frames, Some(id3v1("Old title", 3, 17)));
+    assert_eq!(tag.get(StandardKey::Title), Some(&["Song".to_string()][..]));
+    assert_eq!(
+        tag.get(StandardKey::TrackNumber),
+        Some(&["5".to_string()][..])
+    );
+    assert_eq!(tag.get_first(StandardKey::Genre), Some("Rock"));
+    assert_eq!(tag.get_first(StandardKey::Date), Some("2001"));
+    // empty in both
+    assert_eq!(tag.get(StandardKey::Artist), None);
+
+    let tag = id3_tag(&[], Some(id3v1("Old title", 3, 17)));
+    assert_eq!(tag.get_first(StandardKey::Title), Some("Old title"));
+    assert_eq!(tag.get_first(StandardKey::TrackNumber), Some("3"));
+}
+
+const COMMENTS: [&str; 6] = [
+    "TITLE=Song",
+    "tracknumber=3/12",
+    "GENRE=(17)",
+    "MUSICBRAINZ_ALBUMID=1f2e3d4c-0000-4000-8000-000000000001",
+    "REPLAYGAIN_TRACK_GAIN=-6.50 dB",
+    "MOOD=Calm",
+];
+
+fn check_vorbis_tag(tag: &Tag) {
+    assert_eq!(tag.get_first(StandardKey::Title), Some("Song"));
+    assert_eq!(tag.get_first(StandardKey::TrackNumber), Some("3"));
+    assert_eq!(tag.get_first(StandardKey::TrackTotal), Some("12"));
+    assert_eq!(tag.get_first(StandardKey::Genre), Some("Rock"));
+    assert_eq!(
+        tag.get_first(StandardKey::MusicBrainzAlbumId),
+        Some(ALBUM_ID)
+    );
+    assert_eq!(
+        tag.get_first(StandardKey::ReplayGainTrackGain),
+        Some("-6.50 dB")
+    );
+    assert_eq!(tag.get_native("mood"), Some(&["Calm".to_string()][..]));
+    assert_eq!(tag.get_native("TITLE"), None);
+}
+
+#[test]
+fn flac_comments() {
+    let file = flac(&COMMENTS, None, 100);
+    let mut parser = FlacParser::from_bytes(&file).unwrap();
+    parser.parse().unwrap();
+    check_vorbis_tag(&parser.tag());
+}
+
+#[test]
+fn ogg_comments() {
+    let file = ogg_vorbis(&COMMENTS, 100);
+    let mut parser = OggParser::from_bytes(&file);
+    parser.parse().unwrap();
+    check_vorbis_tag(&parser.tag());
+}